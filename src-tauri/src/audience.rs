@@ -0,0 +1,907 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    net::{IpAddr, SocketAddr},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    Json, Router,
+    extract::{ConnectInfo, State as AxumState},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    response::{Html, IntoResponse, Response},
+    routing::{get, post},
+};
+use rusqlite::{Connection, OptionalExtension, params};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tauri::{AppHandle, Emitter, Manager};
+use uuid::Uuid;
+
+use crate::devices::{now_ts, open_device_db};
+use crate::streaming::SlideUpdate;
+use crate::streaming::manager::{StreamingState, publish_slide};
+
+const AUDIENCE_TABLES_SQL: &str = r#"
+CREATE TABLE IF NOT EXISTS audience_polls (
+    id TEXT PRIMARY KEY,
+    question TEXT NOT NULL,
+    options TEXT NOT NULL,
+    is_open INTEGER NOT NULL DEFAULT 1,
+    created_at INTEGER NOT NULL,
+    closed_at INTEGER
+);
+CREATE TABLE IF NOT EXISTS audience_votes (
+    poll_id TEXT NOT NULL,
+    client_id TEXT NOT NULL,
+    option_index INTEGER NOT NULL,
+    voted_at INTEGER NOT NULL,
+    PRIMARY KEY (poll_id, client_id)
+);
+CREATE TABLE IF NOT EXISTS audience_voters (
+    id TEXT PRIMARY KEY,
+    address TEXT NOT NULL,
+    issued_at INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS audience_questions (
+    id TEXT PRIMARY KEY,
+    text TEXT NOT NULL,
+    author TEXT,
+    status TEXT NOT NULL DEFAULT 'pending',
+    submitted_at INTEGER NOT NULL
+);
+"#;
+
+const MAX_POLL_OPTIONS: usize = 10;
+const MAX_QUESTION_LENGTH: usize = 280;
+const MAX_AUTHOR_LENGTH: usize = 40;
+const MAX_CLIENT_ID_LENGTH: usize = 64;
+
+const VOTER_COOKIE: &str = "lumen_voter";
+/// Voter ids are kept for a week, long enough for a multi-day event.
+const VOTER_MAX_AGE_SECS: u64 = 7 * 24 * 60 * 60;
+
+const VOTE_LIMIT: usize = 10;
+const QUESTION_LIMIT: usize = 3;
+/// New voter ids handed to one address per minute. Phones behind the same
+/// Wi-Fi share an address, so this cannot be one per address.
+const VOTER_LIMIT: usize = 20;
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AudiencePoll {
+    pub id: String,
+    pub question: String,
+    pub options: Vec<String>,
+    pub is_open: bool,
+    pub created_at: u64,
+    pub closed_at: Option<u64>,
+    pub results: Vec<u64>,
+    pub total_votes: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuestionStatus {
+    Pending,
+    Approved,
+    Rejected,
+    Answered,
+}
+
+impl QuestionStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Approved => "approved",
+            Self::Rejected => "rejected",
+            Self::Answered => "answered",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "pending" => Some(Self::Pending),
+            "approved" => Some(Self::Approved),
+            "rejected" => Some(Self::Rejected),
+            "answered" => Some(Self::Answered),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AudienceQuestion {
+    pub id: String,
+    pub text: String,
+    pub author: Option<String>,
+    pub status: QuestionStatus,
+    pub submitted_at: u64,
+}
+
+/// Sliding-window limiter keyed by client address. Audience endpoints need no
+/// pairing, so this is the only thing between a crowded room and the database.
+struct RateLimiter {
+    limit: usize,
+    hits: Mutex<HashMap<IpAddr, VecDeque<Instant>>>,
+}
+
+impl RateLimiter {
+    fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            hits: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn check(&self, ip: IpAddr) -> bool {
+        let Ok(mut hits) = self.hits.lock() else {
+            return false;
+        };
+
+        let now = Instant::now();
+        hits.retain(|_, window| {
+            while window
+                .front()
+                .is_some_and(|hit| now.duration_since(*hit) > RATE_WINDOW)
+            {
+                window.pop_front();
+            }
+            !window.is_empty()
+        });
+
+        let window = hits.entry(ip).or_default();
+        if window.len() >= self.limit {
+            return false;
+        }
+        window.push_back(now);
+        true
+    }
+}
+
+#[derive(Clone)]
+struct AudienceRouteState {
+    app: AppHandle,
+    votes: Arc<RateLimiter>,
+    questions: Arc<RateLimiter>,
+    voters: Arc<RateLimiter>,
+}
+
+#[derive(Debug, Deserialize)]
+struct VoteRequest {
+    poll_id: String,
+    option: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct QuestionRequest {
+    text: String,
+    #[serde(default)]
+    author: Option<String>,
+}
+
+/// Public audience pages and endpoints, mounted on the HTML server.
+pub fn router(app: AppHandle) -> Router {
+    let state = AudienceRouteState {
+        app,
+        votes: Arc::new(RateLimiter::new(VOTE_LIMIT)),
+        questions: Arc::new(RateLimiter::new(QUESTION_LIMIT)),
+        voters: Arc::new(RateLimiter::new(VOTER_LIMIT)),
+    };
+
+    Router::new()
+        .route("/audience", get(audience_index))
+        .route("/api/audience/poll", get(audience_current_poll))
+        .route("/api/audience/vote", post(audience_vote))
+        .route("/api/audience/questions", post(audience_submit_question))
+        .with_state(state)
+}
+
+/// Creates the audience tables and forgets expired voter ids. Runs once at
+/// startup so requests only open the database.
+pub fn prepare_audience_storage() -> Result<(), String> {
+    let connection = open_device_db()?;
+    connection
+        .execute_batch(AUDIENCE_TABLES_SQL)
+        .map_err(|e| e.to_string())?;
+    connection
+        .execute(
+            "DELETE FROM audience_voters WHERE issued_at < ?1",
+            params![now_ts().saturating_sub(VOTER_MAX_AGE_SECS) as i64],
+        )
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_audience_polls() -> Result<Vec<AudiencePoll>, String> {
+    let connection = open_device_db()?;
+    let ids = {
+        let mut statement = connection
+            .prepare("SELECT id FROM audience_polls ORDER BY created_at DESC")
+            .map_err(|e| e.to_string())?;
+        let rows = statement
+            .query_map([], |row| row.get::<_, String>(0))
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?
+    };
+
+    let mut polls = Vec::with_capacity(ids.len());
+    for id in ids {
+        if let Some(poll) = load_poll(&connection, &id)? {
+            polls.push(poll);
+        }
+    }
+    Ok(polls)
+}
+
+/// Opens a new poll. Only one poll is open at a time, so any poll still open
+/// is closed first.
+#[tauri::command]
+pub fn open_audience_poll(
+    app: AppHandle,
+    question: String,
+    options: Vec<String>,
+) -> Result<AudiencePoll, String> {
+    let question = question.trim().to_string();
+    if question.is_empty() {
+        return Err("Poll question cannot be empty".to_string());
+    }
+
+    let options = options
+        .into_iter()
+        .map(|option| option.trim().to_string())
+        .filter(|option| !option.is_empty())
+        .collect::<Vec<_>>();
+    if options.len() < 2 || options.len() > MAX_POLL_OPTIONS {
+        return Err(format!(
+            "A poll needs between 2 and {} options",
+            MAX_POLL_OPTIONS
+        ));
+    }
+
+    let connection = open_device_db()?;
+    let now = now_ts();
+    connection
+        .execute(
+            "UPDATE audience_polls SET is_open = 0, closed_at = ?1 WHERE is_open = 1",
+            params![now as i64],
+        )
+        .map_err(|e| e.to_string())?;
+
+    let id = Uuid::new_v4().to_string();
+    let encoded_options = serde_json::to_string(&options).map_err(|e| e.to_string())?;
+    connection
+        .execute(
+            "INSERT INTO audience_polls (id, question, options, is_open, created_at) VALUES (?1, ?2, ?3, 1, ?4)",
+            params![id, question, encoded_options, now as i64],
+        )
+        .map_err(|e| e.to_string())?;
+
+    let poll = load_poll(&connection, &id)?.ok_or_else(|| "Poll not found".to_string())?;
+    let _ = app.emit("audience_poll_updated", poll.clone());
+    Ok(poll)
+}
+
+#[tauri::command]
+pub fn close_audience_poll(app: AppHandle, poll_id: String) -> Result<AudiencePoll, String> {
+    let connection = open_device_db()?;
+    connection
+        .execute(
+            "UPDATE audience_polls SET is_open = 0, closed_at = ?1 WHERE id = ?2 AND is_open = 1",
+            params![now_ts() as i64, poll_id],
+        )
+        .map_err(|e| e.to_string())?;
+
+    let poll = load_poll(&connection, &poll_id)?.ok_or_else(|| "Poll not found".to_string())?;
+    let _ = app.emit("audience_poll_updated", poll.clone());
+    Ok(poll)
+}
+
+#[tauri::command]
+pub fn get_audience_questions(status: Option<String>) -> Result<Vec<AudienceQuestion>, String> {
+    let status = status
+        .map(|value| {
+            QuestionStatus::parse(&value).ok_or_else(|| format!("Unknown status: {}", value))
+        })
+        .transpose()?;
+
+    let connection = open_device_db()?;
+    let mut statement = connection
+        .prepare(
+            r#"
+            SELECT id, text, author, status, submitted_at
+            FROM audience_questions
+            WHERE ?1 IS NULL OR status = ?1
+            ORDER BY submitted_at ASC
+            "#,
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows = statement
+        .query_map(
+            params![status.map(QuestionStatus::as_str)],
+            question_from_row,
+        )
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn moderate_audience_question(
+    app: AppHandle,
+    question_id: String,
+    status: QuestionStatus,
+) -> Result<AudienceQuestion, String> {
+    let connection = open_device_db()?;
+    let changed = connection
+        .execute(
+            "UPDATE audience_questions SET status = ?1 WHERE id = ?2",
+            params![status.as_str(), question_id],
+        )
+        .map_err(|e| e.to_string())?;
+    if changed == 0 {
+        return Err("Question not found".to_string());
+    }
+
+    let question = load_question(&connection, &question_id)?
+        .ok_or_else(|| "Question not found".to_string())?;
+    let _ = app.emit("audience_question_updated", question.clone());
+    Ok(question)
+}
+
+/// Shows the tally of a poll on every output, one line per option.
+#[tauri::command]
+pub async fn push_audience_poll_results(app: AppHandle, poll_id: String) -> Result<(), String> {
+    let poll = run_blocking(move || {
+        let connection = open_device_db()?;
+        load_poll(&connection, &poll_id)?.ok_or_else(|| "Poll not found".to_string())
+    })
+    .await?;
+
+    let mut lines = vec![poll.question.clone()];
+    for (option, votes) in poll.options.iter().zip(&poll.results) {
+        let percent = (votes * 100 + poll.total_votes / 2)
+            .checked_div(poll.total_votes)
+            .unwrap_or(0);
+        lines.push(format!("{} — {} ({}%)", option, votes, percent));
+    }
+
+    publish_slide(&app, single_slide(lines)).await
+}
+
+/// Shows an approved question on every output.
+#[tauri::command]
+pub async fn push_audience_question(app: AppHandle, question_id: String) -> Result<(), String> {
+    let question = run_blocking(move || {
+        let connection = open_device_db()?;
+        load_question(&connection, &question_id)?.ok_or_else(|| "Question not found".to_string())
+    })
+    .await?;
+
+    if !matches!(
+        question.status,
+        QuestionStatus::Approved | QuestionStatus::Answered
+    ) {
+        return Err("Only approved questions can be shown".to_string());
+    }
+
+    let mut lines = vec![question.text];
+    if let Some(author) = question.author {
+        lines.push(format!("— {}", author));
+    }
+
+    publish_slide(&app, single_slide(lines)).await
+}
+
+async fn audience_index() -> impl IntoResponse {
+    Html(AUDIENCE_TEMPLATE)
+}
+
+async fn audience_current_poll(
+    AxumState(state): AxumState<AudienceRouteState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+) -> Response {
+    if !audience_enabled(&state.app).await {
+        return Json(json!({ "poll": null })).into_response();
+    }
+
+    let ip = addr.ip();
+    let cookie = voter_cookie(&headers);
+    let voters = state.voters.clone();
+    let result = run_blocking(move || {
+        let connection = open_device_db()?;
+
+        // Each browser gets a voter id from the server, tied to the address
+        // it was issued to, so votes cannot be multiplied by inventing ids.
+        let known = match &cookie {
+            Some(voter) => is_known_voter(&connection, voter, ip)?,
+            None => false,
+        };
+        let mut issued = None;
+        let voter = if known {
+            cookie
+        } else if voters.check(ip) {
+            let voter = issue_voter(&connection, ip)?;
+            issued = Some(voter.clone());
+            Some(voter)
+        } else {
+            None
+        };
+
+        let Some(poll) = current_or_latest_poll(&connection)? else {
+            return Ok((json!({ "poll": null }), issued));
+        };
+
+        let voted = match voter.as_deref() {
+            Some(voter) => connection
+                .query_row(
+                    "SELECT option_index FROM audience_votes WHERE poll_id = ?1 AND client_id = ?2",
+                    params![poll.id, voter],
+                    |row| row.get::<_, i64>(0),
+                )
+                .optional()
+                .map_err(|e| e.to_string())?,
+            None => None,
+        };
+
+        // Tallies stay hidden while voting is open so early results do not
+        // sway the room.
+        let body = json!({
+            "poll": {
+                "id": poll.id,
+                "question": poll.question,
+                "options": poll.options,
+                "is_open": poll.is_open,
+                "voted": voted,
+                "results": (!poll.is_open).then_some(&poll.results),
+                "total_votes": (!poll.is_open).then_some(poll.total_votes),
+            }
+        });
+        Ok((body, issued))
+    })
+    .await;
+
+    match result {
+        Ok((body, issued)) => {
+            let mut response = Json(body).into_response();
+            let cookie = issued.and_then(|voter| {
+                HeaderValue::from_str(&format!(
+                    "{}={}; Path=/; Max-Age={}; HttpOnly; SameSite=Strict",
+                    VOTER_COOKIE, voter, VOTER_MAX_AGE_SECS
+                ))
+                .ok()
+            });
+            if let Some(cookie) = cookie {
+                response.headers_mut().insert(header::SET_COOKIE, cookie);
+            }
+            response
+        }
+        Err(error) => error_response(StatusCode::INTERNAL_SERVER_ERROR, &error),
+    }
+}
+
+async fn audience_vote(
+    AxumState(state): AxumState<AudienceRouteState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Json(request): Json<VoteRequest>,
+) -> Response {
+    if !audience_enabled(&state.app).await {
+        return error_response(StatusCode::FORBIDDEN, "Audience interaction is disabled");
+    }
+    if !state.votes.check(addr.ip()) {
+        return error_response(StatusCode::TOO_MANY_REQUESTS, "Too many votes, slow down");
+    }
+
+    let Some(voter) = voter_cookie(&headers) else {
+        return error_response(StatusCode::FORBIDDEN, "Reload the page to vote");
+    };
+
+    let ip = addr.ip();
+    let result = run_blocking(move || {
+        let connection = open_device_db()?;
+        if !is_known_voter(&connection, &voter, ip)? {
+            return Err("Reload the page to vote".to_string());
+        }
+        let poll = load_poll(&connection, &request.poll_id)?
+            .filter(|poll| poll.is_open)
+            .ok_or_else(|| "Poll is not open".to_string())?;
+        if request.option >= poll.options.len() {
+            return Err("Unknown option".to_string());
+        }
+
+        // A client may change its mind while the poll is open; the latest
+        // vote replaces the earlier one.
+        connection
+            .execute(
+                r#"
+                INSERT INTO audience_votes (poll_id, client_id, option_index, voted_at)
+                VALUES (?1, ?2, ?3, ?4)
+                ON CONFLICT(poll_id, client_id) DO UPDATE SET
+                    option_index = excluded.option_index,
+                    voted_at = excluded.voted_at
+                "#,
+                params![poll.id, voter, request.option as i64, now_ts() as i64],
+            )
+            .map_err(|e| e.to_string())?;
+
+        load_poll(&connection, &poll.id)?.ok_or_else(|| "Poll not found".to_string())
+    })
+    .await;
+
+    match result {
+        Ok(poll) => {
+            let _ = state.app.emit("audience_poll_updated", poll);
+            Json(json!({ "status": "ok" })).into_response()
+        }
+        Err(error) => error_response(StatusCode::BAD_REQUEST, &error),
+    }
+}
+
+async fn audience_submit_question(
+    AxumState(state): AxumState<AudienceRouteState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Json(request): Json<QuestionRequest>,
+) -> Response {
+    if !audience_enabled(&state.app).await {
+        return error_response(StatusCode::FORBIDDEN, "Audience interaction is disabled");
+    }
+    if !state.questions.check(addr.ip()) {
+        return error_response(
+            StatusCode::TOO_MANY_REQUESTS,
+            "Too many questions, try again in a minute",
+        );
+    }
+
+    let text = request.text.trim();
+    if text.is_empty() || text.chars().count() > MAX_QUESTION_LENGTH {
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            &format!("Questions must be 1 to {} characters", MAX_QUESTION_LENGTH),
+        );
+    }
+    let author = request
+        .author
+        .map(|value| {
+            value
+                .trim()
+                .chars()
+                .take(MAX_AUTHOR_LENGTH)
+                .collect::<String>()
+        })
+        .filter(|value| !value.is_empty());
+
+    let text = text.to_string();
+    let result = run_blocking(move || {
+        let connection = open_device_db()?;
+        let id = Uuid::new_v4().to_string();
+        connection
+            .execute(
+                "INSERT INTO audience_questions (id, text, author, status, submitted_at) VALUES (?1, ?2, ?3, 'pending', ?4)",
+                params![id, text, author, now_ts() as i64],
+            )
+            .map_err(|e| e.to_string())?;
+        load_question(&connection, &id)?.ok_or_else(|| "Question not found".to_string())
+    })
+    .await;
+
+    match result {
+        Ok(question) => {
+            let _ = state.app.emit("audience_question_submitted", question);
+            Json(json!({ "status": "ok" })).into_response()
+        }
+        Err(error) => error_response(StatusCode::INTERNAL_SERVER_ERROR, &error),
+    }
+}
+
+async fn audience_enabled(app: &AppHandle) -> bool {
+    let Some(streaming) = app.try_state::<StreamingState>() else {
+        return false;
+    };
+    streaming.manager.lock().await.config.audience_enabled
+}
+
+fn error_response(status: StatusCode, message: &str) -> Response {
+    (status, Json(json!({ "error": message }))).into_response()
+}
+
+fn single_slide(lines: Vec<String>) -> SlideUpdate {
+    SlideUpdate {
+        lines,
+        language: None,
+        translations: Vec::new(),
+        font: None,
+        font_size: None,
+        alignment: None,
+        background: None,
+        slide_index: 0,
+        total_slides: 1,
+        active: true,
+    }
+}
+
+/// Runs database work off the async runtime's worker threads.
+async fn run_blocking<T: Send + 'static>(
+    work: impl FnOnce() -> Result<T, String> + Send + 'static,
+) -> Result<T, String> {
+    tokio::task::spawn_blocking(work)
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+fn voter_cookie(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(name, _)| *name == VOTER_COOKIE)
+        .map(|(_, value)| value.to_string())
+        .filter(|value| !value.is_empty() && value.len() <= MAX_CLIENT_ID_LENGTH)
+}
+
+fn is_known_voter(connection: &Connection, voter: &str, ip: IpAddr) -> Result<bool, String> {
+    connection
+        .query_row(
+            "SELECT 1 FROM audience_voters WHERE id = ?1 AND address = ?2",
+            params![voter, ip.to_string()],
+            |_| Ok(()),
+        )
+        .optional()
+        .map(|found| found.is_some())
+        .map_err(|e| e.to_string())
+}
+
+fn issue_voter(connection: &Connection, ip: IpAddr) -> Result<String, String> {
+    let voter = Uuid::new_v4().simple().to_string();
+    connection
+        .execute(
+            "INSERT INTO audience_voters (id, address, issued_at) VALUES (?1, ?2, ?3)",
+            params![voter, ip.to_string(), now_ts() as i64],
+        )
+        .map_err(|e| e.to_string())?;
+    Ok(voter)
+}
+
+/// The open poll, or the most recently closed one so phones can still see
+/// the final results.
+fn current_or_latest_poll(connection: &Connection) -> Result<Option<AudiencePoll>, String> {
+    let id = connection
+        .query_row(
+            "SELECT id FROM audience_polls ORDER BY is_open DESC, created_at DESC LIMIT 1",
+            [],
+            |row| row.get::<_, String>(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+
+    match id {
+        Some(id) => load_poll(connection, &id),
+        None => Ok(None),
+    }
+}
+
+fn load_poll(connection: &Connection, poll_id: &str) -> Result<Option<AudiencePoll>, String> {
+    let row = connection
+        .query_row(
+            "SELECT id, question, options, is_open, created_at, closed_at FROM audience_polls WHERE id = ?1",
+            params![poll_id],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, i64>(3)? == 1,
+                    row.get::<_, i64>(4)? as u64,
+                    row.get::<_, Option<i64>>(5)?.map(|value| value as u64),
+                ))
+            },
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+
+    let Some((id, question, options, is_open, created_at, closed_at)) = row else {
+        return Ok(None);
+    };
+    let options: Vec<String> = serde_json::from_str(&options).map_err(|e| e.to_string())?;
+
+    let mut results = vec![0u64; options.len()];
+    let mut statement = connection
+        .prepare(
+            "SELECT option_index, COUNT(*) FROM audience_votes WHERE poll_id = ?1 GROUP BY option_index",
+        )
+        .map_err(|e| e.to_string())?;
+    let counts = statement
+        .query_map(params![id], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?))
+        })
+        .map_err(|e| e.to_string())?;
+    for count in counts {
+        let (index, votes) = count.map_err(|e| e.to_string())?;
+        if let Some(slot) = usize::try_from(index)
+            .ok()
+            .and_then(|index| results.get_mut(index))
+        {
+            *slot = votes as u64;
+        }
+    }
+
+    Ok(Some(AudiencePoll {
+        total_votes: results.iter().sum(),
+        id,
+        question,
+        options,
+        is_open,
+        created_at,
+        closed_at,
+        results,
+    }))
+}
+
+fn load_question(
+    connection: &Connection,
+    question_id: &str,
+) -> Result<Option<AudienceQuestion>, String> {
+    connection
+        .query_row(
+            "SELECT id, text, author, status, submitted_at FROM audience_questions WHERE id = ?1",
+            params![question_id],
+            question_from_row,
+        )
+        .optional()
+        .map_err(|e| e.to_string())
+}
+
+fn question_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<AudienceQuestion> {
+    Ok(AudienceQuestion {
+        id: row.get(0)?,
+        text: row.get(1)?,
+        author: row.get(2)?,
+        status: QuestionStatus::parse(&row.get::<_, String>(3)?).unwrap_or(QuestionStatus::Pending),
+        submitted_at: row.get::<_, i64>(4)? as u64,
+    })
+}
+
+const AUDIENCE_TEMPLATE: &str = r#"<!doctype html>
+<html lang="en">
+<head>
+  <meta charset="utf-8" />
+  <meta name="viewport" content="width=device-width, initial-scale=1" />
+  <title>Lumen Audience</title>
+  <style>
+    body {
+      margin: 0;
+      padding: 1.25rem;
+      font-family: Inter, Segoe UI, sans-serif;
+      background: #0b0b0f;
+      color: #f5f5f5;
+    }
+    main { max-width: 32rem; margin: 0 auto; }
+    section {
+      background: #17171f;
+      border-radius: 12px;
+      padding: 1rem;
+      margin-bottom: 1rem;
+    }
+    h1 { font-size: 1.1rem; margin: 0 0 0.75rem; }
+    button, textarea, input {
+      width: 100%;
+      box-sizing: border-box;
+      font: inherit;
+      border-radius: 8px;
+      border: 1px solid #2c2c38;
+      background: #22222c;
+      color: inherit;
+      padding: 0.75rem;
+      margin-top: 0.5rem;
+    }
+    button.option[aria-pressed="true"] { border-color: #6d5dfc; background: #2d2860; }
+    button.submit { background: #6d5dfc; border-color: #6d5dfc; }
+    .bar { height: 6px; background: #6d5dfc; border-radius: 3px; margin-top: 0.25rem; }
+    .muted { color: #9a9aa8; font-size: 0.9rem; }
+  </style>
+</head>
+<body>
+  <main>
+    <section id="poll"><p class="muted">No poll right now.</p></section>
+    <section>
+      <h1>Ask a question</h1>
+      <form id="question-form">
+        <textarea id="question-text" rows="3" maxlength="280" required placeholder="Your question"></textarea>
+        <input id="question-author" maxlength="40" placeholder="Name (optional)" />
+        <button class="submit" type="submit">Send</button>
+      </form>
+      <p id="question-status" class="muted"></p>
+    </section>
+  </main>
+  <script>
+    const pollSection = document.getElementById('poll');
+
+    function renderPoll(poll) {
+      pollSection.innerHTML = '';
+      if (!poll) {
+        const empty = document.createElement('p');
+        empty.className = 'muted';
+        empty.textContent = 'No poll right now.';
+        pollSection.appendChild(empty);
+        return;
+      }
+      const title = document.createElement('h1');
+      title.textContent = poll.question;
+      pollSection.appendChild(title);
+      poll.options.forEach((option, index) => {
+        const button = document.createElement('button');
+        button.className = 'option';
+        button.textContent = option;
+        button.disabled = !poll.is_open;
+        button.setAttribute('aria-pressed', String(poll.voted === index));
+        button.addEventListener('click', () => vote(poll.id, index));
+        pollSection.appendChild(button);
+        if (poll.results) {
+          const total = poll.total_votes || 0;
+          const share = total ? Math.round((poll.results[index] / total) * 100) : 0;
+          const bar = document.createElement('div');
+          bar.className = 'bar';
+          bar.style.width = `${share}%`;
+          pollSection.appendChild(bar);
+        }
+      });
+      const note = document.createElement('p');
+      note.className = 'muted';
+      note.textContent = poll.is_open
+        ? (poll.voted === null ? 'Tap an option to vote.' : 'Vote received. You can still change it.')
+        : 'Voting is closed.';
+      pollSection.appendChild(note);
+    }
+
+    async function refresh() {
+      try {
+        const response = await fetch('/api/audience/poll');
+        const body = await response.json();
+        renderPoll(body.poll);
+      } catch (_) {}
+    }
+
+    async function vote(pollId, option) {
+      const response = await fetch('/api/audience/vote', {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify({ poll_id: pollId, option }),
+      });
+      if (!response.ok) {
+        const body = await response.json().catch(() => ({}));
+        alert(body.error || 'Vote failed');
+      }
+      refresh();
+    }
+
+    document.getElementById('question-form').addEventListener('submit', async (event) => {
+      event.preventDefault();
+      const text = document.getElementById('question-text');
+      const author = document.getElementById('question-author');
+      const status = document.getElementById('question-status');
+      const response = await fetch('/api/audience/questions', {
+        method: 'POST',
+        headers: { 'Content-Type': 'application/json' },
+        body: JSON.stringify({ text: text.value, author: author.value || null }),
+      });
+      const body = await response.json().catch(() => ({}));
+      if (response.ok) {
+        text.value = '';
+        status.textContent = 'Thanks! Your question was sent.';
+      } else {
+        status.textContent = body.error || 'Could not send your question.';
+      }
+    });
+
+    refresh();
+    window.setInterval(refresh, 4000);
+  </script>
+</body>
+</html>
+"#;
@@ -0,0 +1,491 @@
+use std::{
+    fs,
+    sync::{
+        Mutex,
+        mpsc::{self, Receiver, Sender},
+    },
+    thread,
+};
+
+use rusqlite::{Connection, params, params_from_iter};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+
+use crate::devices::{
+    DeviceState, RemoteAccessSettings, now_ts, open_device_db, set_audit_retention_setting,
+};
+use crate::media_library::escape_like;
+
+const DEFAULT_PAGE_SIZE: u32 = 100;
+const MAX_PAGE_SIZE: u32 = 1000;
+const MAX_RETENTION_DAYS: u32 = 3650;
+/// Longest payload summary kept per entry.
+const MAX_SUMMARY_CHARS: usize = 512;
+/// Old entries are pruned after this many inserts, besides at startup.
+const PRUNE_EVERY: i64 = 500;
+
+pub const AUTH: &str = "auth";
+pub const PAIRING: &str = "pairing";
+pub const COMMAND: &str = "command";
+pub const PERMISSIONS: &str = "permissions";
+pub const STREAM: &str = "stream";
+
+/// Who an audited action came from. Desktop-side actions leave every field
+/// empty.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AuditActor<'a> {
+    pub device_id: Option<&'a str>,
+    pub session_id: Option<&'a str>,
+    pub peer: Option<&'a str>,
+}
+
+impl AuditActor<'_> {
+    pub fn desktop() -> Self {
+        Self::default()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum AuditOutcome<'a> {
+    Ok,
+    Denied,
+    Failed(&'a str),
+}
+
+impl<'a> AuditOutcome<'a> {
+    pub fn of<T>(result: &'a Result<T, String>) -> Self {
+        match result {
+            Ok(_) => Self::Ok,
+            Err(reason) => Self::Failed(reason),
+        }
+    }
+
+    fn parts(self) -> (&'static str, Option<&'a str>) {
+        match self {
+            Self::Ok => ("ok", None),
+            Self::Denied => ("denied", None),
+            Self::Failed(reason) => ("failed", Some(reason)),
+        }
+    }
+}
+
+/// Hands entries to the writer thread, so recording never waits on the
+/// database. `None` until `start_audit_writer` runs.
+pub struct AuditState {
+    sender: Mutex<Option<Sender<PendingEntry>>>,
+}
+
+pub fn default_audit_state() -> AuditState {
+    AuditState {
+        sender: Mutex::new(None),
+    }
+}
+
+struct PendingEntry {
+    at: u64,
+    category: String,
+    action: String,
+    device_id: Option<String>,
+    session_id: Option<String>,
+    peer: Option<String>,
+    summary: String,
+    outcome: &'static str,
+    reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditEntry {
+    pub id: i64,
+    pub at: u64,
+    pub category: String,
+    pub action: String,
+    pub device_id: Option<String>,
+    pub session_id: Option<String>,
+    pub peer: Option<String>,
+    pub summary: String,
+    pub outcome: String,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct AuditFilter {
+    pub device_id: Option<String>,
+    pub category: Option<String>,
+    pub outcome: Option<String>,
+    /// Unix seconds, inclusive.
+    pub since: Option<u64>,
+    /// Unix seconds, exclusive.
+    pub until: Option<u64>,
+    /// Matched against the action and summary.
+    pub search: Option<String>,
+    pub offset: Option<u32>,
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditPage {
+    pub entries: Vec<AuditEntry>,
+    pub total: u64,
+    pub offset: u32,
+    pub limit: u32,
+}
+
+/// Queues one audit entry for the writer thread. Failures are logged rather
+/// than returned so that auditing never blocks the action itself.
+pub fn record(
+    app: &AppHandle,
+    actor: AuditActor<'_>,
+    category: &str,
+    action: &str,
+    summary: &str,
+    outcome: AuditOutcome<'_>,
+) {
+    let (outcome, reason) = outcome.parts();
+    let entry = PendingEntry {
+        at: now_ts(),
+        category: category.to_string(),
+        action: action.to_string(),
+        device_id: actor.device_id.map(str::to_string),
+        session_id: actor.session_id.map(str::to_string),
+        peer: actor.peer.map(str::to_string),
+        summary: summary.chars().take(MAX_SUMMARY_CHARS).collect(),
+        outcome,
+        reason: reason.map(str::to_string),
+    };
+
+    let state = app.state::<AuditState>();
+    let queued = match state.sender.lock() {
+        Ok(sender) => sender
+            .as_ref()
+            .is_some_and(|sender| sender.send(entry).is_ok()),
+        Err(_) => false,
+    };
+    if !queued {
+        eprintln!(
+            "Failed to write audit entry for {}: audit log is not running",
+            action
+        );
+    }
+}
+
+/// Creates the audit table and drops entries past the retention period.
+pub fn prepare_audit_log(settings: &RemoteAccessSettings) -> Result<(), String> {
+    let connection = open_device_db()?;
+    ensure_audit_table(&connection)?;
+    prune(&connection, settings.audit_retention_days)?;
+    Ok(())
+}
+
+/// Starts the thread that writes recorded entries through one connection.
+/// Call after `prepare_audit_log` has created the table.
+pub fn start_audit_writer(app: &AppHandle) -> Result<(), String> {
+    let state = app.state::<AuditState>();
+    let mut sender = state.sender.lock().map_err(|e| e.to_string())?;
+    if sender.is_some() {
+        return Ok(());
+    }
+
+    let connection = open_device_db()?;
+    let (entries, receiver) = mpsc::channel();
+    let app = app.clone();
+    thread::Builder::new()
+        .name("audit-log".to_string())
+        .spawn(move || write_entries(&app, &connection, receiver))
+        .map_err(|e| e.to_string())?;
+    *sender = Some(entries);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_audit_log(filter: Option<AuditFilter>) -> Result<AuditPage, String> {
+    let filter = filter.unwrap_or_default();
+    let offset = filter.offset.unwrap_or(0);
+    let limit = filter
+        .limit
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+        .clamp(1, MAX_PAGE_SIZE);
+
+    let connection = open_device_db()?;
+    let (clause, values) = where_clause(&filter);
+
+    let total = connection
+        .query_row(
+            &format!("SELECT COUNT(*) FROM audit_log{}", clause),
+            params_from_iter(values.iter()),
+            |row| row.get::<_, i64>(0),
+        )
+        .map_err(|e| e.to_string())?;
+    let entries = select_entries(
+        &connection,
+        &clause,
+        &format!(" LIMIT {} OFFSET {}", limit, offset),
+        &values,
+    )?;
+
+    Ok(AuditPage {
+        entries,
+        total: total as u64,
+        offset,
+        limit,
+    })
+}
+
+/// Writes every entry matching `filter` (ignoring paging) to `path` as CSV
+/// and returns how many were written.
+#[tauri::command]
+pub fn export_audit_log(filter: Option<AuditFilter>, path: String) -> Result<usize, String> {
+    let filter = filter.unwrap_or_default();
+    let connection = open_device_db()?;
+    let (clause, values) = where_clause(&filter);
+    let entries = select_entries(&connection, &clause, "", &values)?;
+
+    let mut csv =
+        String::from("id,at,category,action,device_id,session_id,peer,summary,outcome,reason\n");
+    for entry in &entries {
+        let fields = [
+            entry.id.to_string(),
+            entry.at.to_string(),
+            entry.category.clone(),
+            entry.action.clone(),
+            entry.device_id.clone().unwrap_or_default(),
+            entry.session_id.clone().unwrap_or_default(),
+            entry.peer.clone().unwrap_or_default(),
+            entry.summary.clone(),
+            entry.outcome.clone(),
+            entry.reason.clone().unwrap_or_default(),
+        ];
+        let row = fields
+            .iter()
+            .map(|field| csv_field(field))
+            .collect::<Vec<_>>()
+            .join(",");
+        csv.push_str(&row);
+        csv.push('\n');
+    }
+
+    fs::write(&path, csv).map_err(|e| e.to_string())?;
+    println!("Exported {} audit entries to {}", entries.len(), path);
+    Ok(entries.len())
+}
+
+/// Sets how many days audit entries are kept and prunes older ones.
+#[tauri::command]
+pub fn set_audit_retention(
+    state: State<'_, DeviceState>,
+    retention_days: u32,
+) -> Result<RemoteAccessSettings, String> {
+    if !(1..=MAX_RETENTION_DAYS).contains(&retention_days) {
+        return Err(format!(
+            "Audit retention must be between 1 and {} days",
+            MAX_RETENTION_DAYS
+        ));
+    }
+
+    let settings = set_audit_retention_setting(&state, retention_days)?;
+    prepare_audit_log(&settings)?;
+    Ok(settings)
+}
+
+fn write_entries(app: &AppHandle, connection: &Connection, entries: Receiver<PendingEntry>) {
+    for entry in entries {
+        if let Err(error) = insert_entry(app, connection, &entry) {
+            eprintln!(
+                "Failed to write audit entry for {}: {}",
+                entry.action, error
+            );
+        }
+    }
+}
+
+fn insert_entry(
+    app: &AppHandle,
+    connection: &Connection,
+    entry: &PendingEntry,
+) -> Result<(), String> {
+    connection
+        .execute(
+            "INSERT INTO audit_log
+             (at, category, action, device_id, session_id, peer, summary, outcome, reason)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                entry.at as i64,
+                entry.category,
+                entry.action,
+                entry.device_id,
+                entry.session_id,
+                entry.peer,
+                entry.summary,
+                entry.outcome,
+                entry.reason,
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+
+    if connection.last_insert_rowid() % PRUNE_EVERY == 0 {
+        let retention_days = app
+            .state::<DeviceState>()
+            .remote_settings
+            .lock()
+            .map_err(|e| e.to_string())?
+            .audit_retention_days;
+        prune(connection, retention_days)?;
+    }
+    Ok(())
+}
+
+fn ensure_audit_table(connection: &Connection) -> Result<(), String> {
+    connection
+        .execute_batch(
+            "CREATE TABLE IF NOT EXISTS audit_log (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                at INTEGER NOT NULL,
+                category TEXT NOT NULL,
+                action TEXT NOT NULL,
+                device_id TEXT,
+                session_id TEXT,
+                peer TEXT,
+                summary TEXT NOT NULL,
+                outcome TEXT NOT NULL,
+                reason TEXT
+            );
+            CREATE INDEX IF NOT EXISTS idx_audit_log_at ON audit_log(at);
+            CREATE INDEX IF NOT EXISTS idx_audit_log_device ON audit_log(device_id, at);",
+        )
+        .map_err(|e| e.to_string())
+}
+
+fn prune(connection: &Connection, retention_days: u32) -> Result<(), String> {
+    let cutoff = now_ts().saturating_sub(u64::from(retention_days) * 24 * 60 * 60);
+    let removed = connection
+        .execute("DELETE FROM audit_log WHERE at < ?1", [cutoff as i64])
+        .map_err(|e| e.to_string())?;
+    if removed > 0 {
+        println!(
+            "Pruned {} audit entries older than {} days",
+            removed, retention_days
+        );
+    }
+    Ok(())
+}
+
+fn where_clause(filter: &AuditFilter) -> (String, Vec<String>) {
+    let mut conditions = Vec::new();
+    let mut values = Vec::new();
+
+    for (column, value) in [
+        ("device_id", &filter.device_id),
+        ("category", &filter.category),
+        ("outcome", &filter.outcome),
+    ] {
+        if let Some(value) = value {
+            conditions.push(format!("{} = ?", column));
+            values.push(value.clone());
+        }
+    }
+    if let Some(since) = filter.since {
+        conditions.push("at >= ?".to_string());
+        values.push(since.to_string());
+    }
+    if let Some(until) = filter.until {
+        conditions.push("at < ?".to_string());
+        values.push(until.to_string());
+    }
+    for term in filter
+        .search
+        .as_deref()
+        .unwrap_or_default()
+        .split_whitespace()
+    {
+        let pattern = format!("%{}%", escape_like(term));
+        conditions.push("(action LIKE ? ESCAPE '#' OR summary LIKE ? ESCAPE '#')".to_string());
+        values.push(pattern.clone());
+        values.push(pattern);
+    }
+
+    let clause = if conditions.is_empty() {
+        String::new()
+    } else {
+        format!(" WHERE {}", conditions.join(" AND "))
+    };
+    (clause, values)
+}
+
+fn select_entries(
+    connection: &Connection,
+    clause: &str,
+    paging: &str,
+    values: &[String],
+) -> Result<Vec<AuditEntry>, String> {
+    let mut statement = connection
+        .prepare(&format!(
+            "SELECT id, at, category, action, device_id, session_id, peer, summary, outcome, reason
+             FROM audit_log{} ORDER BY at DESC, id DESC{}",
+            clause, paging
+        ))
+        .map_err(|e| e.to_string())?;
+    let rows = statement
+        .query_map(params_from_iter(values.iter()), |row| {
+            Ok(AuditEntry {
+                id: row.get(0)?,
+                at: row.get::<_, i64>(1)? as u64,
+                category: row.get(2)?,
+                action: row.get(3)?,
+                device_id: row.get(4)?,
+                session_id: row.get(5)?,
+                peer: row.get(6)?,
+                summary: row.get(7)?,
+                outcome: row.get(8)?,
+                reason: row.get(9)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+
+    let mut entries = Vec::new();
+    for row in rows {
+        entries.push(row.map_err(|e| e.to_string())?);
+    }
+    Ok(entries)
+}
+
+/// Quotes a CSV field, and defuses values a spreadsheet would run as a
+/// formula since summaries come from remote devices.
+fn csv_field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    };
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_field_quotes_separators_and_quotes() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+        assert_eq!(csv_field(""), "");
+    }
+
+    #[test]
+    fn csv_field_defuses_formulas() {
+        assert_eq!(csv_field("=SUM(A1)"), "'=SUM(A1)");
+        assert_eq!(csv_field("+1"), "'+1");
+        assert_eq!(csv_field("-1"), "'-1");
+        assert_eq!(csv_field("@cmd"), "'@cmd");
+        assert_eq!(
+            csv_field("=HYPERLINK(\"x\",\"y\")"),
+            "\"'=HYPERLINK(\"\"x\"\",\"\"y\"\")\""
+        );
+        assert_eq!(csv_field("a=b"), "a=b");
+    }
+}
@@ -0,0 +1,356 @@
+use std::{
+    fs,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+
+use crate::devices::{DeviceState, RemoteSyncEnvelope, app_base_dir, broadcast_remote_event_inner};
+use crate::streaming::SlideUpdate;
+use crate::streaming::manager::publish_slide;
+
+const DEFAULT_SEARCH_LIMIT: usize = 20;
+const MAX_SEARCH_LIMIT: usize = 100;
+/// Longest passage that fits on one slide.
+const MAX_SHOWN_VERSES: usize = 30;
+
+/// A translation file from `lumen/files/bible/<id>.json`. `chapters` holds
+/// one array of verse texts per chapter, in order.
+#[derive(Debug, Clone, Deserialize)]
+pub struct BibleTranslation {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub language: Option<String>,
+    pub books: Vec<BibleBook>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BibleBook {
+    pub name: String,
+    #[serde(default)]
+    pub abbreviations: Vec<String>,
+    pub chapters: Vec<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BibleTranslationInfo {
+    pub id: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BibleVerse {
+    pub verse: usize,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BiblePassage {
+    pub translation: String,
+    pub reference: String,
+    pub book: String,
+    pub chapter: usize,
+    pub verses: Vec<BibleVerse>,
+}
+
+/// Translations read from disk on first use.
+pub struct BibleState {
+    translations: Mutex<Option<Arc<Vec<BibleTranslation>>>>,
+}
+
+pub fn default_bible_state() -> BibleState {
+    BibleState {
+        translations: Mutex::new(None),
+    }
+}
+
+/// Re-reads the translation files and lists them.
+#[tauri::command]
+pub fn get_bible_translations(
+    state: State<'_, BibleState>,
+) -> Result<Vec<BibleTranslationInfo>, String> {
+    let translations = Arc::new(load_translations()?);
+    *state.translations.lock().map_err(|e| e.to_string())? = Some(translations.clone());
+    Ok(translation_infos(&translations))
+}
+
+pub fn list_translations(app: &AppHandle) -> Result<Vec<BibleTranslationInfo>, String> {
+    Ok(translation_infos(&translations(app)?))
+}
+
+/// Looks up `query` as a reference such as `John 3:16-18` and otherwise
+/// searches verse text for every word in it.
+pub fn search(
+    app: &AppHandle,
+    query: &str,
+    translation: Option<&str>,
+    limit: Option<usize>,
+) -> Result<Vec<BiblePassage>, String> {
+    let translations = translations(app)?;
+    let translation = pick_translation(&translations, translation)?;
+    let query = query.trim();
+    if query.is_empty() {
+        return Err("Search query cannot be empty".to_string());
+    }
+
+    if let Some(passage) = lookup_reference(translation, query) {
+        return Ok(vec![passage]);
+    }
+
+    let limit = limit
+        .unwrap_or(DEFAULT_SEARCH_LIMIT)
+        .clamp(1, MAX_SEARCH_LIMIT);
+    let terms = query
+        .to_lowercase()
+        .split_whitespace()
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+
+    let mut results = Vec::new();
+    for book in &translation.books {
+        for (chapter_index, chapter) in book.chapters.iter().enumerate() {
+            for (verse_index, text) in chapter.iter().enumerate() {
+                let lowered = text.to_lowercase();
+                if !terms.iter().all(|term| lowered.contains(term.as_str())) {
+                    continue;
+                }
+                results.push(passage(
+                    translation,
+                    book,
+                    chapter_index + 1,
+                    verse_index + 1,
+                    verse_index + 1,
+                ));
+                if results.len() >= limit {
+                    return Ok(results);
+                }
+            }
+        }
+    }
+
+    Ok(results)
+}
+
+/// Puts a passage on every output and tells devices with Bible access.
+pub async fn show(
+    app: &AppHandle,
+    reference: &str,
+    translation: Option<&str>,
+) -> Result<BiblePassage, String> {
+    let (passage, language) = {
+        let translations = translations(app)?;
+        let translation = pick_translation(&translations, translation)?;
+        let passage = lookup_reference(translation, reference)
+            .ok_or_else(|| format!("Unknown reference: {}", reference.trim()))?;
+        if passage.verses.len() > MAX_SHOWN_VERSES {
+            return Err(format!(
+                "Passages can show at most {} verses",
+                MAX_SHOWN_VERSES
+            ));
+        }
+        (passage, translation.language.clone())
+    };
+
+    let mut lines = passage
+        .verses
+        .iter()
+        .map(|verse| format!("{} {}", verse.verse, verse.text))
+        .collect::<Vec<_>>();
+    lines.push(passage.reference.clone());
+
+    publish_slide(
+        app,
+        SlideUpdate {
+            lines,
+            language,
+            translations: Vec::new(),
+            font: None,
+            font_size: None,
+            alignment: None,
+            background: None,
+            slide_index: 0,
+            total_slides: 1,
+            active: true,
+        },
+    )
+    .await?;
+
+    let envelope = RemoteSyncEnvelope {
+        event: "bible_passage".to_string(),
+        payload: serde_json::to_value(&passage).map_err(|e| e.to_string())?,
+    };
+    broadcast_remote_event_inner(&app.state::<DeviceState>(), &envelope, Some("bible"))?;
+
+    Ok(passage)
+}
+
+fn translations(app: &AppHandle) -> Result<Arc<Vec<BibleTranslation>>, String> {
+    let state = app.state::<BibleState>();
+    let mut cached = state.translations.lock().map_err(|e| e.to_string())?;
+    if let Some(translations) = cached.as_ref() {
+        return Ok(translations.clone());
+    }
+
+    let translations = Arc::new(load_translations()?);
+    *cached = Some(translations.clone());
+    Ok(translations)
+}
+
+fn load_translations() -> Result<Vec<BibleTranslation>, String> {
+    let dir = bible_dir()?;
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut translations = Vec::new();
+    for entry in fs::read_dir(&dir).map_err(|e| e.to_string())? {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+            continue;
+        }
+
+        let parsed = fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| {
+                serde_json::from_str::<BibleTranslation>(&content).map_err(|e| e.to_string())
+            });
+        match parsed {
+            Ok(translation) => translations.push(translation),
+            Err(error) => eprintln!("Skipping Bible file {}: {}", path.display(), error),
+        }
+    }
+
+    translations.sort_by(|left, right| left.id.cmp(&right.id));
+    Ok(translations)
+}
+
+fn translation_infos(translations: &[BibleTranslation]) -> Vec<BibleTranslationInfo> {
+    translations
+        .iter()
+        .map(|translation| BibleTranslationInfo {
+            id: translation.id.clone(),
+            name: translation.name.clone(),
+            language: translation.language.clone(),
+        })
+        .collect()
+}
+
+/// The requested translation, or the first one installed.
+fn pick_translation<'a>(
+    translations: &'a [BibleTranslation],
+    id: Option<&str>,
+) -> Result<&'a BibleTranslation, String> {
+    match id {
+        Some(id) => translations
+            .iter()
+            .find(|translation| translation.id.eq_ignore_ascii_case(id))
+            .ok_or_else(|| format!("Unknown Bible translation: {}", id)),
+        None => translations
+            .first()
+            .ok_or_else(|| "No Bible translations are installed".to_string()),
+    }
+}
+
+/// Resolves `Book 3`, `Book 3:16` or `Book 3:16-18`.
+fn lookup_reference(translation: &BibleTranslation, reference: &str) -> Option<BiblePassage> {
+    let (book_name, location) = reference.trim().rsplit_once(char::is_whitespace)?;
+    if !location.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+    let book = find_book(translation, book_name)?;
+
+    let (chapter, verses) = match location.split_once(':') {
+        Some((chapter, verses)) => (chapter, Some(verses)),
+        None => (location, None),
+    };
+    let chapter = chapter.parse::<usize>().ok()?;
+    let verse_count = book.chapters.get(chapter.checked_sub(1)?)?.len();
+
+    let (first, last) = match verses {
+        Some(verses) => match verses.split_once('-') {
+            Some((first, last)) => (first.parse().ok()?, last.parse().ok()?),
+            None => {
+                let verse = verses.parse().ok()?;
+                (verse, verse)
+            }
+        },
+        None => (1, verse_count),
+    };
+    if first == 0 || first > last || last > verse_count {
+        return None;
+    }
+
+    Some(passage(translation, book, chapter, first, last))
+}
+
+fn find_book<'a>(translation: &'a BibleTranslation, name: &str) -> Option<&'a BibleBook> {
+    let wanted = normalize_book_name(name);
+    if wanted.is_empty() {
+        return None;
+    }
+
+    translation
+        .books
+        .iter()
+        .find(|book| {
+            normalize_book_name(&book.name) == wanted
+                || book
+                    .abbreviations
+                    .iter()
+                    .any(|abbreviation| normalize_book_name(abbreviation) == wanted)
+        })
+        .or_else(|| {
+            let mut matches = translation
+                .books
+                .iter()
+                .filter(|book| normalize_book_name(&book.name).starts_with(&wanted));
+            let first = matches.next()?;
+            matches.next().is_none().then_some(first)
+        })
+}
+
+fn normalize_book_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn passage(
+    translation: &BibleTranslation,
+    book: &BibleBook,
+    chapter: usize,
+    first: usize,
+    last: usize,
+) -> BiblePassage {
+    let texts = &book.chapters[chapter - 1];
+    let reference = if first == last {
+        format!("{} {}:{}", book.name, chapter, first)
+    } else if first == 1 && last == texts.len() {
+        format!("{} {}", book.name, chapter)
+    } else {
+        format!("{} {}:{}-{}", book.name, chapter, first, last)
+    };
+
+    BiblePassage {
+        translation: translation.id.clone(),
+        reference,
+        book: book.name.clone(),
+        chapter,
+        verses: (first..=last)
+            .map(|verse| BibleVerse {
+                verse,
+                text: texts[verse - 1].clone(),
+            })
+            .collect(),
+    }
+}
+
+fn bible_dir() -> Result<PathBuf, String> {
+    Ok(app_base_dir()?.join("files").join("bible"))
+}
@@ -0,0 +1,225 @@
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+
+use crate::devices::{DeviceState, RemoteAccessSettings, set_control_limits_setting};
+
+/// Largest frame or message accepted from the desktop's own windows, which
+/// send whole lyric sets.
+pub const INTERNAL_MAX_MESSAGE_BYTES: usize = 16 * 1024 * 1024;
+/// Close code for a frame or message above `max_message_bytes`.
+pub const MESSAGE_TOO_BIG_CLOSE_CODE: u16 = 1009;
+/// Close code for a client that keeps sending after being rate limited.
+pub const RATE_LIMITED_CLOSE_CODE: u16 = 4029;
+/// Close code for a connection over the per-IP cap.
+pub const TOO_MANY_CONNECTIONS_CLOSE_CODE: u16 = 4030;
+
+/// Limits applied to every external control-socket connection.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ControlLimits {
+    /// Largest accepted frame or reassembled message.
+    pub max_message_bytes: usize,
+    /// Sustained messages per second allowed per connection.
+    pub messages_per_second: u32,
+    /// Messages a connection may send at once before the rate applies.
+    pub burst: u32,
+    pub max_connections_per_ip: usize,
+}
+
+impl Default for ControlLimits {
+    fn default() -> Self {
+        Self {
+            max_message_bytes: 64 * 1024,
+            messages_per_second: 20,
+            burst: 40,
+            max_connections_per_ip: 4,
+        }
+    }
+}
+
+impl ControlLimits {
+    fn validate(&self) -> Result<(), String> {
+        if !(1024..=INTERNAL_MAX_MESSAGE_BYTES).contains(&self.max_message_bytes) {
+            return Err("Maximum message size must be between 1 KiB and 16 MiB".to_string());
+        }
+        if !(1..=1000).contains(&self.messages_per_second) {
+            return Err("Messages per second must be between 1 and 1000".to_string());
+        }
+        if self.burst < self.messages_per_second {
+            return Err("Burst must be at least the messages per second".to_string());
+        }
+        if !(1..=64).contains(&self.max_connections_per_ip) {
+            return Err("Connections per IP must be between 1 and 64".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[tauri::command]
+pub fn set_control_limits(
+    state: State<'_, DeviceState>,
+    limits: ControlLimits,
+) -> Result<RemoteAccessSettings, String> {
+    limits.validate()?;
+    set_control_limits_setting(&state, limits)
+}
+
+/// Refills `rate` tokens per second up to `capacity`; each message takes one.
+pub struct TokenBucket {
+    capacity: f64,
+    rate: f64,
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    pub fn new(rate: u32, capacity: u32) -> Self {
+        Self {
+            capacity: capacity as f64,
+            rate: rate as f64,
+            tokens: capacity as f64,
+            refilled_at: Instant::now(),
+        }
+    }
+
+    /// A bucket holding `capacity` tokens that regains one every `interval`,
+    /// for rates slower than one per second.
+    pub fn per_interval(capacity: u32, interval: Duration) -> Self {
+        Self {
+            capacity: capacity as f64,
+            rate: 1.0 / interval.as_secs_f64(),
+            tokens: capacity as f64,
+            refilled_at: Instant::now(),
+        }
+    }
+
+    pub fn try_take(&mut self) -> bool {
+        self.try_take_at(Instant::now())
+    }
+
+    /// Whether the bucket has refilled completely, so forgetting it changes
+    /// nothing.
+    pub fn is_full(&self) -> bool {
+        let elapsed = self.refilled_at.elapsed().as_secs_f64();
+        self.tokens + elapsed * self.rate >= self.capacity
+    }
+
+    fn try_take_at(&mut self, now: Instant) -> bool {
+        let elapsed = now.duration_since(self.refilled_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+        self.refilled_at = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Open external connections per remote IP.
+pub struct ConnectionLimitState {
+    per_ip: Mutex<HashMap<IpAddr, usize>>,
+}
+
+pub fn default_connection_limit_state() -> ConnectionLimitState {
+    ConnectionLimitState {
+        per_ip: Mutex::new(HashMap::new()),
+    }
+}
+
+/// Counts one connection from `ip` until dropped.
+pub struct ConnectionGuard {
+    app: AppHandle,
+    ip: IpAddr,
+}
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        let state = self.app.state::<ConnectionLimitState>();
+        let Ok(mut per_ip) = state.per_ip.lock() else {
+            return;
+        };
+        if let Some(count) = per_ip.get_mut(&self.ip) {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                per_ip.remove(&self.ip);
+            }
+        }
+    }
+}
+
+/// Registers a connection from `ip`, or returns `None` when it already has
+/// `max` open.
+pub fn acquire_connection(app: &AppHandle, ip: IpAddr, max: usize) -> Option<ConnectionGuard> {
+    let state = app.state::<ConnectionLimitState>();
+    let mut per_ip = state.per_ip.lock().ok()?;
+    let count = per_ip.entry(ip).or_insert(0);
+    if *count >= max {
+        return None;
+    }
+    *count += 1;
+
+    Some(ConnectionGuard {
+        app: app.clone(),
+        ip,
+    })
+}
+
+pub fn control_limits(state: &State<'_, DeviceState>) -> ControlLimits {
+    state
+        .remote_settings
+        .lock()
+        .map(|settings| settings.limits.clone())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_bucket_allows_a_burst_then_the_rate() {
+        let mut bucket = TokenBucket::new(2, 3);
+        let start = bucket.refilled_at;
+
+        assert!(bucket.try_take_at(start));
+        assert!(bucket.try_take_at(start));
+        assert!(bucket.try_take_at(start));
+        assert!(!bucket.try_take_at(start));
+
+        assert!(!bucket.try_take_at(start + Duration::from_millis(400)));
+        assert!(bucket.try_take_at(start + Duration::from_millis(500)));
+        assert!(!bucket.try_take_at(start + Duration::from_millis(500)));
+    }
+
+    #[test]
+    fn token_bucket_per_interval_regains_one_token_per_interval() {
+        let mut bucket = TokenBucket::per_interval(2, Duration::from_secs(60));
+        let start = bucket.refilled_at;
+
+        assert!(bucket.try_take_at(start));
+        assert!(bucket.try_take_at(start));
+        assert!(!bucket.try_take_at(start + Duration::from_secs(59)));
+        assert!(bucket.try_take_at(start + Duration::from_secs(61)));
+        assert!(!bucket.is_full());
+    }
+
+    #[test]
+    fn token_bucket_refills_no_further_than_its_capacity() {
+        let mut bucket = TokenBucket::new(10, 2);
+        let later = bucket.refilled_at + Duration::from_secs(60);
+
+        assert!(bucket.try_take_at(later));
+        assert!(bucket.try_take_at(later));
+        assert!(!bucket.try_take_at(later));
+    }
+}
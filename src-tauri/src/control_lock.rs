@@ -0,0 +1,436 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use serde::Serialize;
+use serde_json::json;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::mpsc::UnboundedSender;
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::audit::{self, AuditActor, AuditOutcome};
+use crate::devices::{
+    DeviceState, RemoteSyncEnvelope, WebSocketSession, broadcast_remote_event_inner, now_ts,
+};
+use crate::permissions::can_control;
+use crate::protocol::{ClientMessage, ErrorCode, ErrorReply};
+
+/// Areas a device can lock. Each covers the commands that change it.
+pub const LOCK_SCOPES: &[&str] = &["player", "lyrics", "bible", "media"];
+
+const DEFAULT_LEASE_SECS: u64 = 120;
+const MIN_LEASE_SECS: u64 = 10;
+const MAX_LEASE_SECS: u64 = 1800;
+const DESKTOP_HOLDER_NAME: &str = "Desktop";
+
+/// Who may change a scope right now. Every command from the holder extends
+/// the lease; a holder that goes quiet loses the lock when it runs out.
+#[derive(Debug, Clone, Serialize)]
+pub struct ControlLock {
+    pub scope: String,
+    /// `None` while the desktop holds the lock.
+    pub device_id: Option<String>,
+    pub device_name: String,
+    pub acquired_at: u64,
+    /// `None` for desktop locks, which last until released.
+    pub expires_at: Option<u64>,
+    #[serde(skip)]
+    session_id: Option<String>,
+    #[serde(skip)]
+    lease_secs: u64,
+}
+
+impl ControlLock {
+    fn is_expired(&self, now: u64) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+
+    fn is_held_by(&self, session_id: &str) -> bool {
+        self.session_id.as_deref() == Some(session_id)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LockChangeReason {
+    Acquired,
+    Released,
+    Expired,
+    TakenOver,
+    Disconnected,
+}
+
+/// Payload of `control_lock_changed`, emitted to the UI and broadcast to
+/// every remote session.
+#[derive(Debug, Clone, Serialize)]
+struct ControlLockChanged {
+    scope: String,
+    holder: Option<ControlLock>,
+    reason: LockChangeReason,
+}
+
+pub struct ControlLockState {
+    locks: Mutex<HashMap<String, ControlLock>>,
+}
+
+pub fn default_control_lock_state() -> ControlLockState {
+    ControlLockState {
+        locks: Mutex::new(HashMap::new()),
+    }
+}
+
+/// The lock scopes a message needs, if any. `next` and `previous` also move
+/// lyric slides, so they respect both the player and the lyrics lock.
+pub fn lock_scopes(message: &ClientMessage) -> &'static [&'static str] {
+    match message {
+        ClientMessage::Next | ClientMessage::Previous => &["player", "lyrics"],
+        ClientMessage::PlayPause
+        | ClientMessage::Stop
+        | ClientMessage::ManualPause
+        | ClientMessage::Seek { .. }
+        | ClientMessage::Progress { .. }
+        | ClientMessage::SetVolume { .. }
+        | ClientMessage::Mute
+        | ClientMessage::SetLoop { .. }
+        | ClientMessage::LoadUrl { .. }
+        | ClientMessage::Metadata { .. } => &["player"],
+        ClientMessage::LoadLyric { .. } | ClientMessage::LyricsSlide { .. } => &["lyrics"],
+        ClientMessage::BibleShow { .. } => &["bible"],
+        ClientMessage::MediaQueue { .. } | ClientMessage::MediaShow { .. } => &["media"],
+        _ => &[],
+    }
+}
+
+/// Grants `scope` to the session, or renews its lease when it already holds
+/// it.
+pub fn acquire(
+    app: &AppHandle,
+    session: &WebSocketSession,
+    scope: &str,
+    lease_secs: Option<u64>,
+) -> Result<ControlLock, ErrorReply> {
+    let reply = |code, message: String| ErrorReply::new(code, message, Some("request_control"));
+    validate_scope(scope).map_err(|reason| reply(ErrorCode::InvalidPayload, reason))?;
+    if !can_control(&session.permissions, scope) {
+        return Err(reply(
+            ErrorCode::PermissionDenied,
+            format!("Device cannot control `{}`", scope),
+        ));
+    }
+    let lease_secs = lease_secs
+        .unwrap_or(DEFAULT_LEASE_SECS)
+        .clamp(MIN_LEASE_SECS, MAX_LEASE_SECS);
+    expire_locks(app);
+    let device_name = app
+        .state::<DeviceState>()
+        .devices
+        .lock()
+        .map_err(|e| reply(ErrorCode::CommandFailed, e.to_string()))?
+        .get(&session.device_id)
+        .map(|device| device.device_name.clone())
+        .unwrap_or_else(|| session.device_id.clone());
+
+    let now = now_ts();
+    let (lock, acquired) = {
+        let state = app.state::<ControlLockState>();
+        let mut locks = state
+            .locks
+            .lock()
+            .map_err(|e| reply(ErrorCode::CommandFailed, e.to_string()))?;
+        match locks.get_mut(scope) {
+            Some(lock) if lock.is_held_by(&session.session_id) => {
+                lock.lease_secs = lease_secs;
+                lock.expires_at = Some(now + lease_secs);
+                (lock.clone(), false)
+            }
+            Some(lock) => {
+                return Err(reply(
+                    ErrorCode::ControlLocked,
+                    format!("`{}` is locked by {}", scope, lock.device_name),
+                ));
+            }
+            None => {
+                let lock = ControlLock {
+                    scope: scope.to_string(),
+                    device_id: Some(session.device_id.clone()),
+                    device_name,
+                    acquired_at: now,
+                    expires_at: Some(now + lease_secs),
+                    session_id: Some(session.session_id.clone()),
+                    lease_secs,
+                };
+                locks.insert(scope.to_string(), lock.clone());
+                (lock, true)
+            }
+        }
+    };
+
+    if acquired {
+        println!("Device {} locked {}", session.device_id, scope);
+        broadcast_change(app, scope, Some(lock.clone()), LockChangeReason::Acquired);
+    }
+    Ok(lock)
+}
+
+/// Gives up a lock the session holds.
+pub fn release(app: &AppHandle, session_id: &str, scope: &str) -> Result<(), ErrorReply> {
+    let reply = |code, message: String| ErrorReply::new(code, message, Some("release_control"));
+    validate_scope(scope).map_err(|reason| reply(ErrorCode::InvalidPayload, reason))?;
+    {
+        let state = app.state::<ControlLockState>();
+        let mut locks = state
+            .locks
+            .lock()
+            .map_err(|e| reply(ErrorCode::CommandFailed, e.to_string()))?;
+        if !locks
+            .get(scope)
+            .is_some_and(|lock| lock.is_held_by(session_id))
+        {
+            return Err(reply(
+                ErrorCode::CommandFailed,
+                format!("This device does not hold the `{}` lock", scope),
+            ));
+        }
+        locks.remove(scope);
+    }
+
+    broadcast_change(app, scope, None, LockChangeReason::Released);
+    Ok(())
+}
+
+/// Returns the lock blocking `message` for this session, if any. Commands
+/// the holder sends extend its lease.
+pub fn blocking_lock(
+    app: &AppHandle,
+    session_id: &str,
+    message: &ClientMessage,
+) -> Option<ControlLock> {
+    first_blocking(app, lock_scopes(message), |lock| {
+        lock.is_held_by(session_id)
+    })
+}
+
+/// Like [`blocking_lock`] for requests that arrive without a session, such
+/// as REST calls: a lock held by any session of the same device lets them
+/// through.
+pub fn blocking_device_lock(
+    app: &AppHandle,
+    device_id: &str,
+    scopes: &[&str],
+) -> Option<ControlLock> {
+    first_blocking(app, scopes, |lock| {
+        lock.device_id.as_deref() == Some(device_id)
+    })
+}
+
+/// For commands from sources that cannot hold a lock, such as OSC: any lock
+/// on `scopes` blocks them.
+pub fn blocking_any_lock(app: &AppHandle, scopes: &[&str]) -> Option<ControlLock> {
+    first_blocking(app, scopes, |_| false)
+}
+
+fn first_blocking(
+    app: &AppHandle,
+    scopes: &[&str],
+    is_holder: impl Fn(&ControlLock) -> bool,
+) -> Option<ControlLock> {
+    if scopes.is_empty() {
+        return None;
+    }
+    expire_locks(app);
+
+    let now = now_ts();
+    let state = app.state::<ControlLockState>();
+    let mut locks = state.locks.lock().ok()?;
+    for scope in scopes {
+        let Some(lock) = locks.get_mut(*scope) else {
+            continue;
+        };
+        if !is_holder(lock) {
+            return Some(lock.clone());
+        }
+        lock.expires_at = Some(now + lock.lease_secs);
+    }
+    None
+}
+
+/// Drops every lock held by a session that has closed.
+pub fn release_session(app: &AppHandle, session_id: &str) {
+    let released = {
+        let state = app.state::<ControlLockState>();
+        let Ok(mut locks) = state.locks.lock() else {
+            return;
+        };
+        let scopes = locks
+            .iter()
+            .filter(|(_, lock)| lock.is_held_by(session_id))
+            .map(|(scope, _)| scope.clone())
+            .collect::<Vec<_>>();
+        for scope in &scopes {
+            locks.remove(scope);
+        }
+        scopes
+    };
+
+    for scope in released {
+        broadcast_change(app, &scope, None, LockChangeReason::Disconnected);
+    }
+}
+
+/// Drops locks whose lease ran out.
+pub fn expire_locks(app: &AppHandle) {
+    let now = now_ts();
+    let expired = {
+        let state = app.state::<ControlLockState>();
+        let Ok(mut locks) = state.locks.lock() else {
+            return;
+        };
+        let scopes = locks
+            .iter()
+            .filter(|(_, lock)| lock.is_expired(now))
+            .map(|(scope, _)| scope.clone())
+            .collect::<Vec<_>>();
+        for scope in &scopes {
+            locks.remove(scope);
+        }
+        scopes
+    };
+
+    for scope in expired {
+        println!("Control lock on {} expired", scope);
+        broadcast_change(app, &scope, None, LockChangeReason::Expired);
+    }
+}
+
+/// Sends the current locks to one session, e.g. right after it connects.
+pub fn send_locks(app: &AppHandle, sender: &UnboundedSender<Message>) -> Result<(), String> {
+    let message = json!({
+        "event": "control_locks",
+        "locks": current_locks(app)?,
+    });
+    let _ = sender.send(Message::Text(message.to_string()));
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_control_locks(app: AppHandle) -> Result<Vec<ControlLock>, String> {
+    current_locks(&app)
+}
+
+/// Locks `scope` for the desktop, taking it from any device holding it.
+/// Remote devices stay read-only for that scope until it is released.
+#[tauri::command]
+pub fn take_control_lock(
+    app: AppHandle,
+    state: State<'_, ControlLockState>,
+    scope: String,
+) -> Result<ControlLock, String> {
+    validate_scope(&scope)?;
+    let lock = ControlLock {
+        scope: scope.clone(),
+        device_id: None,
+        device_name: DESKTOP_HOLDER_NAME.to_string(),
+        acquired_at: now_ts(),
+        expires_at: None,
+        session_id: None,
+        lease_secs: 0,
+    };
+    let previous = state
+        .locks
+        .lock()
+        .map_err(|e| e.to_string())?
+        .insert(scope.clone(), lock.clone());
+
+    let reason = match &previous {
+        Some(previous) if previous.device_id.is_some() => LockChangeReason::TakenOver,
+        _ => LockChangeReason::Acquired,
+    };
+    let summary = previous
+        .and_then(|previous| previous.device_id)
+        .map(|device_id| format!("{} (from {})", scope, device_id))
+        .unwrap_or_else(|| scope.clone());
+    audit::record(
+        &app,
+        AuditActor::desktop(),
+        audit::COMMAND,
+        "take_control_lock",
+        &summary,
+        AuditOutcome::Ok,
+    );
+    broadcast_change(&app, &scope, Some(lock.clone()), reason);
+    Ok(lock)
+}
+
+/// Releases `scope` whoever holds it.
+#[tauri::command]
+pub fn release_control_lock(
+    app: AppHandle,
+    state: State<'_, ControlLockState>,
+    scope: String,
+) -> Result<(), String> {
+    validate_scope(&scope)?;
+    let removed = state
+        .locks
+        .lock()
+        .map_err(|e| e.to_string())?
+        .remove(&scope);
+    let Some(removed) = removed else {
+        return Ok(());
+    };
+
+    let summary = match &removed.device_id {
+        Some(device_id) => format!("{} (from {})", scope, device_id),
+        None => scope.clone(),
+    };
+    audit::record(
+        &app,
+        AuditActor::desktop(),
+        audit::COMMAND,
+        "release_control_lock",
+        &summary,
+        AuditOutcome::Ok,
+    );
+    broadcast_change(&app, &scope, None, LockChangeReason::Released);
+    Ok(())
+}
+
+fn current_locks(app: &AppHandle) -> Result<Vec<ControlLock>, String> {
+    expire_locks(app);
+    let state = app.state::<ControlLockState>();
+    let locks = state.locks.lock().map_err(|e| e.to_string())?;
+    let mut locks = locks.values().cloned().collect::<Vec<_>>();
+    locks.sort_by(|a, b| a.scope.cmp(&b.scope));
+    Ok(locks)
+}
+
+fn validate_scope(scope: &str) -> Result<(), String> {
+    if LOCK_SCOPES.contains(&scope) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Unknown lock scope `{}`; expected one of {}",
+            scope,
+            LOCK_SCOPES.join(", ")
+        ))
+    }
+}
+
+fn broadcast_change(
+    app: &AppHandle,
+    scope: &str,
+    holder: Option<ControlLock>,
+    reason: LockChangeReason,
+) {
+    let change = ControlLockChanged {
+        scope: scope.to_string(),
+        holder,
+        reason,
+    };
+    let _ = app.emit("control_lock_changed", change.clone());
+
+    if let Ok(payload) = serde_json::to_value(&change) {
+        let envelope = RemoteSyncEnvelope {
+            event: "control_lock_changed".to_string(),
+            payload,
+        };
+        let _ = broadcast_remote_event_inner(&app.state::<DeviceState>(), &envelope, None);
+    }
+}
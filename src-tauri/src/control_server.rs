@@ -0,0 +1,320 @@
+use std::{
+    io::ErrorKind,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    sync::Mutex,
+    time::Duration,
+};
+
+use serde::Serialize;
+use socket2::{Domain, Protocol, Socket, Type};
+use tauri::{AppHandle, Emitter, Manager, State, async_runtime};
+use tokio::net::TcpListener;
+
+use crate::devices::{DeviceState, RemoteAccessSettings, set_control_server_setting};
+use crate::discovery::announce_control_service;
+use crate::websocket;
+
+pub const DEFAULT_CONTROL_PORT: u16 = 8080;
+/// Ports tried after the configured one when it is taken and fallback is on.
+const PORT_FALLBACK_ATTEMPTS: u16 = 10;
+const LISTEN_BACKLOG: i32 = 1024;
+const ACCEPT_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ControlServerStatus {
+    pub running: bool,
+    /// Addresses actually listened on, e.g. `[::]:8080`.
+    pub addresses: Vec<String>,
+    pub port: Option<u16>,
+    pub requested_port: u16,
+    /// Why the last start failed, while not running.
+    pub error: Option<String>,
+}
+
+/// The control socket listener. Connections already accepted outlive a
+/// rebind; only the accept loops are replaced.
+pub struct ControlServerState {
+    status: Mutex<ControlServerStatus>,
+    tasks: Mutex<Vec<async_runtime::JoinHandle<()>>>,
+}
+
+pub fn default_control_server_state() -> ControlServerState {
+    ControlServerState {
+        status: Mutex::new(ControlServerStatus::default()),
+        tasks: Mutex::new(Vec::new()),
+    }
+}
+
+/// Starts listening with the saved settings. A failure is logged and kept in
+/// the status instead of stopping the app.
+pub fn start(app: &AppHandle) {
+    let settings = app
+        .state::<DeviceState>()
+        .remote_settings
+        .lock()
+        .map(|settings| settings.clone())
+        .unwrap_or_default();
+
+    if let Err(error) = listen_with(app, &settings) {
+        report_failure(app, &settings, error);
+    }
+}
+
+/// The port remote devices should connect to, while the server runs.
+pub fn active_port(app: &AppHandle) -> Option<u16> {
+    app.state::<ControlServerState>()
+        .status
+        .lock()
+        .ok()
+        .and_then(|status| status.port)
+}
+
+#[tauri::command]
+pub fn get_control_server_status(
+    state: State<'_, ControlServerState>,
+) -> Result<ControlServerStatus, String> {
+    state
+        .status
+        .lock()
+        .map(|status| status.clone())
+        .map_err(|e| e.to_string())
+}
+
+/// Changes where the control socket listens and rebinds it. When the new
+/// address cannot be bound the previous one is restored and the settings
+/// stay as they were.
+#[tauri::command]
+pub async fn set_control_server(
+    app: AppHandle,
+    state: State<'_, DeviceState>,
+    bind_address: Option<String>,
+    port: u16,
+    port_fallback: bool,
+) -> Result<ControlServerStatus, String> {
+    if port == 0 {
+        return Err("Port must be between 1 and 65535".to_string());
+    }
+    let bind_address = bind_address
+        .map(|address| address.trim().to_string())
+        .filter(|address| !address.is_empty());
+    if let Some(address) = &bind_address {
+        address
+            .parse::<IpAddr>()
+            .map_err(|_| format!("Invalid bind address: {}", address))?;
+    }
+
+    let previous = state
+        .remote_settings
+        .lock()
+        .map_err(|e| e.to_string())?
+        .clone();
+    let mut next = previous.clone();
+    next.control_bind_address = bind_address;
+    next.control_port = port;
+    next.control_port_fallback = port_fallback;
+
+    stop(&app).await?;
+    match listen_with(&app, &next) {
+        Ok(status) => {
+            set_control_server_setting(&state, &next)?;
+            Ok(status)
+        }
+        Err(error) => {
+            if let Err(restore_error) = listen_with(&app, &previous) {
+                report_failure(&app, &previous, restore_error);
+            }
+            Err(error)
+        }
+    }
+}
+
+/// Stops accepting and waits until the listening sockets are closed.
+async fn stop(app: &AppHandle) -> Result<(), String> {
+    let tasks = {
+        let state = app.state::<ControlServerState>();
+        let mut tasks = state.tasks.lock().map_err(|e| e.to_string())?;
+        tasks.drain(..).collect::<Vec<_>>()
+    };
+    for task in tasks {
+        task.abort();
+        let _ = task.await;
+    }
+    Ok(())
+}
+
+fn listen_with(
+    app: &AppHandle,
+    settings: &RemoteAccessSettings,
+) -> Result<ControlServerStatus, String> {
+    let (listeners, port) = bind(settings)?;
+    let addresses = listeners
+        .iter()
+        .filter_map(|listener| listener.local_addr().ok())
+        .map(|address| address.to_string())
+        .collect::<Vec<_>>();
+
+    let state = app.state::<ControlServerState>();
+    {
+        let mut tasks = state.tasks.lock().map_err(|e| e.to_string())?;
+        for listener in listeners {
+            tasks.push(async_runtime::spawn(accept_loop(listener, app.clone())));
+        }
+    }
+
+    if port != settings.control_port {
+        eprintln!(
+            "Control port {} is in use; listening on {} instead",
+            settings.control_port, port
+        );
+    }
+    println!("Control server listening on {}", addresses.join(", "));
+
+    let status = ControlServerStatus {
+        running: true,
+        addresses,
+        port: Some(port),
+        requested_port: settings.control_port,
+        error: None,
+    };
+    set_status(app, status.clone());
+    announce_control_service(app, settings.remote_enabled);
+    Ok(status)
+}
+
+fn report_failure(app: &AppHandle, settings: &RemoteAccessSettings, error: String) {
+    eprintln!("Control server not started: {}", error);
+    set_status(
+        app,
+        ControlServerStatus {
+            requested_port: settings.control_port,
+            error: Some(error),
+            ..ControlServerStatus::default()
+        },
+    );
+    announce_control_service(app, false);
+}
+
+fn set_status(app: &AppHandle, status: ControlServerStatus) {
+    if let Ok(mut current) = app.state::<ControlServerState>().status.lock() {
+        *current = status.clone();
+    }
+    let _ = app.emit("control_server_status", status);
+}
+
+async fn accept_loop(listener: std::net::TcpListener, app: AppHandle) {
+    let listener = match TcpListener::from_std(listener) {
+        Ok(listener) => listener,
+        Err(error) => {
+            eprintln!("Control listener unusable: {}", error);
+            return;
+        }
+    };
+
+    loop {
+        match listener.accept().await {
+            Ok((stream, peer)) => {
+                // IPv4 clients of a dual-stack socket show up as
+                // `::ffff:a.b.c.d`; loopback checks need the plain address.
+                let peer = SocketAddr::new(peer.ip().to_canonical(), peer.port());
+                async_runtime::spawn(websocket::accept_connection(peer, stream, app.clone()));
+            }
+            Err(error) => {
+                // Usually out of file descriptors; give in-flight
+                // connections a moment to finish.
+                eprintln!("Control socket accept error: {}", error);
+                tokio::time::sleep(ACCEPT_RETRY_DELAY).await;
+            }
+        }
+    }
+}
+
+/// Binds the configured address, moving on to the next ports when the
+/// configured one is taken and fallback is on.
+fn bind(settings: &RemoteAccessSettings) -> Result<(Vec<std::net::TcpListener>, u16), String> {
+    let ip = match settings.control_bind_address.as_deref() {
+        Some(address) => Some(
+            address
+                .parse::<IpAddr>()
+                .map_err(|_| format!("Invalid bind address: {}", address))?,
+        ),
+        None => None,
+    };
+    let attempts = if settings.control_port_fallback {
+        PORT_FALLBACK_ATTEMPTS + 1
+    } else {
+        1
+    };
+
+    let mut last_error = None;
+    for offset in 0..attempts {
+        let Some(port) = settings.control_port.checked_add(offset) else {
+            break;
+        };
+        match bind_port(ip, port) {
+            Ok(listeners) => return Ok((listeners, port)),
+            Err(error) if error.kind() == ErrorKind::AddrInUse => last_error = Some(error),
+            Err(error) => return Err(format!("Cannot listen on port {}: {}", port, error)),
+        }
+    }
+
+    let error = last_error
+        .map(|error| error.to_string())
+        .unwrap_or_default();
+    Err(if attempts > 1 {
+        format!(
+            "Ports {}-{} are all in use ({})",
+            settings.control_port,
+            settings.control_port.saturating_add(attempts - 1),
+            error
+        )
+    } else {
+        format!("Port {} is in use ({})", settings.control_port, error)
+    })
+}
+
+/// The listeners for one port. Without an address this is one dual-stack
+/// socket, or IPv4 only where IPv6 is unavailable. A specific non-loopback
+/// address also gets a loopback listener so the desktop's own windows can
+/// still reach `localhost`.
+fn bind_port(ip: Option<IpAddr>, port: u16) -> std::io::Result<Vec<std::net::TcpListener>> {
+    let ip = match ip {
+        None | Some(IpAddr::V6(Ipv6Addr::UNSPECIFIED)) => {
+            return match listen(SocketAddr::new(Ipv6Addr::UNSPECIFIED.into(), port), true) {
+                Ok(listener) => Ok(vec![listener]),
+                Err(error) if error.kind() == ErrorKind::AddrInUse => Err(error),
+                Err(_) => Ok(vec![listen(
+                    SocketAddr::new(Ipv4Addr::UNSPECIFIED.into(), port),
+                    false,
+                )?]),
+            };
+        }
+        Some(ip) => ip,
+    };
+
+    let mut listeners = vec![listen(SocketAddr::new(ip, port), false)?];
+    if !ip.is_unspecified() && !ip.is_loopback() {
+        listeners.push(listen(
+            SocketAddr::new(Ipv4Addr::LOCALHOST.into(), port),
+            false,
+        )?);
+    }
+    Ok(listeners)
+}
+
+fn listen(address: SocketAddr, dual_stack: bool) -> std::io::Result<std::net::TcpListener> {
+    let socket = Socket::new(
+        Domain::for_address(address),
+        Type::STREAM,
+        Some(Protocol::TCP),
+    )?;
+    if address.is_ipv6() {
+        socket.set_only_v6(!dual_stack)?;
+    }
+    // On Windows SO_REUSEADDR would let two servers share the port.
+    #[cfg(not(windows))]
+    socket.set_reuse_address(true)?;
+    socket.set_nonblocking(true)?;
+    socket.bind(&address.into())?;
+    socket.listen(LISTEN_BACKLOG)?;
+    Ok(socket.into())
+}
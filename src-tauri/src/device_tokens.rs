@@ -0,0 +1,164 @@
+use blake3::Hash;
+use tauri::{AppHandle, State};
+use uuid::Uuid;
+
+use crate::devices::{
+    DeviceState, RemoteAccessSettings, revoke_all_tokens, set_token_policy_setting,
+};
+
+/// Marks a stored `blake3$<salt>$<hash>` value. Anything else in the
+/// `access_token` column is a plaintext token from before hashing.
+const HASH_SCHEME: &str = "blake3";
+const MAX_TOKEN_LIFETIME_DAYS: u32 = 3650;
+
+/// Close code for a device whose access token expired or was revoked.
+pub const TOKEN_REVOKED_CLOSE_CODE: u16 = 4006;
+
+/// A fresh access token with 244 random bits.
+pub fn generate_token() -> String {
+    format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+/// Salts and hashes `token` for storage.
+pub fn hash_token(token: &str) -> String {
+    let salt = Uuid::new_v4().simple().to_string();
+    format!("{}${}${}", HASH_SCHEME, salt, digest(&salt, token).to_hex())
+}
+
+pub fn is_hashed(stored: &str) -> bool {
+    stored
+        .strip_prefix(HASH_SCHEME)
+        .is_some_and(|rest| rest.starts_with('$'))
+}
+
+/// Whether `token` matches a value produced by `hash_token`.
+pub fn verify_token(stored: &str, token: &str) -> bool {
+    let mut parts = stored.splitn(3, '$');
+    let (Some(HASH_SCHEME), Some(salt), Some(hash)) = (parts.next(), parts.next(), parts.next())
+    else {
+        return false;
+    };
+    let Ok(expected) = Hash::from_hex(hash) else {
+        return false;
+    };
+
+    digests_match(&digest(salt, token), &expected)
+}
+
+/// Whether a presented secret equals the expected one, without leaking how
+/// much of it matched.
+pub fn same_secret(presented: &str, secret: &str) -> bool {
+    digests_match(
+        &blake3::hash(presented.as_bytes()),
+        &blake3::hash(secret.as_bytes()),
+    )
+}
+
+/// When a token issued at `now` expires, if tokens expire at all.
+pub fn token_expiry(settings: &RemoteAccessSettings, now: u64) -> Option<u64> {
+    settings
+        .token_lifetime_days
+        .map(|days| now + u64::from(days) * 24 * 60 * 60)
+}
+
+/// Sets how long access tokens live (`None` for no expiry) and whether each
+/// `auth` hands out a new one.
+#[tauri::command]
+pub fn set_token_policy(
+    state: State<'_, DeviceState>,
+    token_lifetime_days: Option<u32>,
+    rotate_tokens_on_auth: bool,
+) -> Result<RemoteAccessSettings, String> {
+    if token_lifetime_days.is_some_and(|days| !(1..=MAX_TOKEN_LIFETIME_DAYS).contains(&days)) {
+        return Err(format!(
+            "Token lifetime must be between 1 and {} days",
+            MAX_TOKEN_LIFETIME_DAYS
+        ));
+    }
+
+    set_token_policy_setting(&state, token_lifetime_days, rotate_tokens_on_auth)
+}
+
+/// Invalidates every paired device's token and disconnects it. Each device
+/// has to pair again with a new QR code. Returns how many were revoked.
+#[tauri::command]
+pub fn revoke_all_device_tokens(
+    app: AppHandle,
+    state: State<'_, DeviceState>,
+) -> Result<usize, String> {
+    let revoked = revoke_all_tokens(&app, &state)?;
+    println!("Revoked access tokens of {} devices", revoked);
+    Ok(revoked)
+}
+
+fn digest(salt: &str, token: &str) -> Hash {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(salt.as_bytes());
+    hasher.update(token.as_bytes());
+    hasher.finalize()
+}
+
+// `blake3::Hash` compares in constant time.
+fn digests_match(actual: &Hash, expected: &Hash) -> bool {
+    actual == expected
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hashed_tokens_verify_only_the_original() {
+        let token = generate_token();
+        let stored = hash_token(&token);
+
+        assert!(is_hashed(&stored));
+        assert!(verify_token(&stored, &token));
+        assert!(!verify_token(&stored, &generate_token()));
+        assert!(!verify_token(&stored, ""));
+    }
+
+    #[test]
+    fn hashes_are_salted() {
+        let token = generate_token();
+        let first = hash_token(&token);
+        let second = hash_token(&token);
+
+        assert_ne!(first, second);
+        assert!(verify_token(&first, &token));
+        assert!(verify_token(&second, &token));
+    }
+
+    #[test]
+    fn plaintext_and_malformed_values_never_verify() {
+        let token = generate_token();
+        assert!(!is_hashed(&token));
+        assert!(!verify_token(&token, &token));
+        assert!(!verify_token("blake3$salt", &token));
+        assert!(!verify_token("blake3$salt$not-hex", &token));
+        assert!(!verify_token("sha256$salt$00", &token));
+    }
+
+    #[test]
+    fn secrets_match_only_themselves() {
+        let secret = generate_token();
+        assert!(same_secret(&secret, &secret));
+        assert!(!same_secret(&generate_token(), &secret));
+        assert!(!same_secret("", &secret));
+    }
+
+    #[test]
+    fn tokens_expire_after_the_configured_days() {
+        let mut settings = RemoteAccessSettings {
+            token_lifetime_days: None,
+            ..RemoteAccessSettings::default()
+        };
+        assert_eq!(token_expiry(&settings, 1_000), None);
+
+        settings.token_lifetime_days = Some(30);
+        assert_eq!(
+            token_expiry(&settings, 1_000),
+            Some(1_000 + 30 * 24 * 60 * 60)
+        );
+    }
+}
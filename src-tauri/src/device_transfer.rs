@@ -0,0 +1,568 @@
+use std::{collections::HashSet, fs};
+
+use aes_gcm::{
+    Aes256Gcm, Key, Nonce,
+    aead::{Aead, AeadCore, KeyInit, OsRng, Payload, rand_core::RngCore},
+};
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+
+use crate::audit::{self, AuditActor, AuditOutcome};
+use crate::control_server;
+use crate::devices::{
+    self, Device, DeviceState, RemoteAccessSettings, now_ts, resolve_desktop_name,
+};
+use crate::permissions::{self, PermissionRole, PermissionState};
+use crate::tls;
+
+/// Identifies an export file; `version` changes when the payload does.
+const BUNDLE_FORMAT: &str = "lumen-remote-access";
+const BUNDLE_VERSION: u32 = 1;
+const MIN_PASSWORD_CHARS: usize = 8;
+const SALT_BYTES: usize = 16;
+/// Argon2id cost: 19 MiB, two passes, one lane.
+const KDF_MEMORY_KIB: u32 = 19 * 1024;
+const KDF_ITERATIONS: u32 = 2;
+const KDF_PARALLELISM: u32 = 1;
+/// Highest cost accepted from a file, so an edited export cannot make the
+/// import allocate gigabytes or run for minutes before the password check.
+const MAX_KDF_MEMORY_KIB: u32 = 256 * 1024;
+const MAX_KDF_ITERATIONS: u32 = 10;
+const MAX_KDF_PARALLELISM: u32 = 4;
+
+/// The file on disk. Only `ciphertext` is secret; the rest is what is
+/// needed to derive the key and decrypt it, and is authenticated with it.
+#[derive(Serialize, Deserialize)]
+struct BundleFile {
+    format: String,
+    version: u32,
+    kdf: KdfParams,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// The unencrypted fields of a `BundleFile`, passed to AES-GCM as associated
+/// data so they cannot be changed without failing decryption.
+#[derive(Serialize)]
+struct BundleHeader<'a> {
+    format: &'a str,
+    version: u32,
+    kdf: &'a KdfParams,
+}
+
+#[derive(Serialize, Deserialize)]
+struct KdfParams {
+    algorithm: String,
+    salt: String,
+    memory_kib: u32,
+    iterations: u32,
+    parallelism: u32,
+}
+
+/// What the bundle holds once decrypted.
+#[derive(Serialize, Deserialize)]
+struct Bundle {
+    exported_at: u64,
+    desktop_name: String,
+    settings: RemoteAccessSettings,
+    /// Custom roles only; built-in roles exist everywhere.
+    roles: Vec<PermissionRole>,
+    devices: Vec<DeviceRecord>,
+    #[serde(default)]
+    tls_ca: Option<TlsCa>,
+}
+
+/// A paired device with the token hashes `Device` leaves out when
+/// serialized, so the phone keeps its token on the new desktop.
+#[derive(Serialize, Deserialize)]
+struct DeviceRecord {
+    #[serde(flatten)]
+    device: Device,
+    access_token: String,
+    #[serde(default)]
+    previous_token: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct TlsCa {
+    cert_pem: String,
+    key_pem: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportSummary {
+    pub devices: usize,
+    pub roles: usize,
+    pub tls_ca: bool,
+}
+
+/// The contents of an export, checked against this desktop.
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportPreview {
+    pub exported_at: u64,
+    pub desktop_name: String,
+    pub settings: RemoteAccessSettings,
+    pub devices: Vec<ImportDevice>,
+    pub roles: Vec<ImportRole>,
+    pub tls_ca: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportDevice {
+    pub device_id: String,
+    pub device_name: String,
+    pub device_type: String,
+    pub os: String,
+    pub role: Option<String>,
+    pub is_active: bool,
+    pub last_connected_at: Option<u64>,
+    /// Set when a device with the same id is already paired here. Importing
+    /// it replaces that device.
+    pub conflict: Option<DeviceConflict>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceConflict {
+    pub device_name: String,
+    pub last_connected_at: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportRole {
+    pub role_id: String,
+    pub name: String,
+    /// A different role with this id already exists here and is kept.
+    pub conflict: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportSummary {
+    pub devices: usize,
+    pub replaced: usize,
+    pub roles: usize,
+    pub settings: bool,
+    /// Parts of the settings that could not be applied.
+    pub warnings: Vec<String>,
+}
+
+/// Writes every paired device, the custom roles, the remote access settings
+/// and the local CA to `path`, encrypted with `password`.
+#[tauri::command]
+pub fn export_remote_access(
+    app: AppHandle,
+    state: State<'_, DeviceState>,
+    permission_state: State<'_, PermissionState>,
+    path: String,
+    password: String,
+) -> Result<ExportSummary, String> {
+    check_password(&password)?;
+
+    let mut devices = state
+        .devices
+        .lock()
+        .map_err(|e| e.to_string())?
+        .values()
+        .cloned()
+        .map(|device| DeviceRecord {
+            access_token: device.access_token.clone(),
+            previous_token: device.previous_token.clone(),
+            device,
+        })
+        .collect::<Vec<_>>();
+    devices.sort_by(|left, right| left.device.device_id.cmp(&right.device.device_id));
+    let roles = permissions::roles(&permission_state)?
+        .into_values()
+        .filter(|role| !role.built_in)
+        .collect::<Vec<_>>();
+    let settings = state
+        .remote_settings
+        .lock()
+        .map_err(|e| e.to_string())?
+        .clone();
+    let tls_ca = tls::local_ca_pem()?.map(|(cert_pem, key_pem)| TlsCa { cert_pem, key_pem });
+
+    let summary = ExportSummary {
+        devices: devices.len(),
+        roles: roles.len(),
+        tls_ca: tls_ca.is_some(),
+    };
+    let bundle = Bundle {
+        exported_at: now_ts(),
+        desktop_name: resolve_desktop_name(&app),
+        settings,
+        roles,
+        devices,
+        tls_ca,
+    };
+    let plaintext = serde_json::to_vec(&bundle).map_err(|e| e.to_string())?;
+    let file = encrypt(&plaintext, &password)?;
+    let content = serde_json::to_string_pretty(&file).map_err(|e| e.to_string())?;
+    fs::write(&path, content).map_err(|e| e.to_string())?;
+
+    audit::record(
+        &app,
+        AuditActor::desktop(),
+        audit::PERMISSIONS,
+        "export_remote_access",
+        &format!("{} devices to {}", summary.devices, path),
+        AuditOutcome::Ok,
+    );
+    Ok(summary)
+}
+
+/// Decrypts an export and lists what it holds, marking devices and roles
+/// that clash with ones on this desktop. Nothing is changed.
+#[tauri::command]
+pub fn preview_remote_access_import(
+    state: State<'_, DeviceState>,
+    permission_state: State<'_, PermissionState>,
+    path: String,
+    password: String,
+) -> Result<ImportPreview, String> {
+    let bundle = read_bundle(&path, &password)?;
+    let local_roles = permissions::roles(&permission_state)?;
+    let local_devices = state.devices.lock().map_err(|e| e.to_string())?;
+
+    let devices = bundle
+        .devices
+        .iter()
+        .map(|record| {
+            let device = &record.device;
+            ImportDevice {
+                device_id: device.device_id.clone(),
+                device_name: device.device_name.clone(),
+                device_type: device.device_type.clone(),
+                os: device.os.clone(),
+                role: device.permissions.role.clone(),
+                is_active: device.is_active,
+                last_connected_at: device.last_connected_at,
+                conflict: local_devices
+                    .get(&device.device_id)
+                    .map(|existing| DeviceConflict {
+                        device_name: existing.device_name.clone(),
+                        last_connected_at: existing.last_connected_at,
+                    }),
+            }
+        })
+        .collect();
+    let roles = bundle
+        .roles
+        .iter()
+        .map(|role| ImportRole {
+            role_id: role.role_id.clone(),
+            name: role.name.clone(),
+            conflict: local_roles.get(&role.role_id).is_some_and(|existing| {
+                existing.built_in || existing.name != role.name || existing.scopes != role.scopes
+            }),
+        })
+        .collect();
+
+    Ok(ImportPreview {
+        exported_at: bundle.exported_at,
+        desktop_name: bundle.desktop_name,
+        settings: bundle.settings,
+        devices,
+        roles,
+        tls_ca: bundle.tls_ca.is_some(),
+    })
+}
+
+/// Imports the devices in `device_ids` from an export, with the custom
+/// roles this desktop lacks. A device already paired here is replaced.
+/// With `import_settings` the remote access settings and the local CA are
+/// taken over as well, except for the network interface and bind address,
+/// which belong to this machine.
+#[tauri::command]
+pub async fn import_remote_access(
+    app: AppHandle,
+    path: String,
+    password: String,
+    device_ids: Vec<String>,
+    import_settings: bool,
+) -> Result<ImportSummary, String> {
+    let bundle = read_bundle(&path, &password)?;
+    let known = bundle
+        .devices
+        .iter()
+        .map(|record| record.device.device_id.as_str())
+        .collect::<HashSet<_>>();
+    if let Some(missing) = device_ids.iter().find(|id| !known.contains(id.as_str())) {
+        return Err(format!("Device is not in the export: {}", missing));
+    }
+
+    let permission_state = app.state::<PermissionState>();
+    let mut role_count = 0;
+    for role in bundle.roles {
+        if permissions::roles(&permission_state)?.contains_key(&role.role_id) {
+            continue;
+        }
+        permissions::import_role(&permission_state, role)?;
+        role_count += 1;
+    }
+    let roles = permissions::roles(&permission_state)?;
+
+    let selected = device_ids.iter().collect::<HashSet<_>>();
+    let mut device_count = 0;
+    let mut replaced = 0;
+    for record in bundle.devices {
+        if !selected.contains(&record.device.device_id) {
+            continue;
+        }
+        let mut device = Device {
+            access_token: record.access_token,
+            previous_token: record.previous_token,
+            ..record.device
+        };
+        device.permissions.resolve(&roles);
+        let exists = app
+            .state::<DeviceState>()
+            .devices
+            .lock()
+            .map_err(|e| e.to_string())?
+            .contains_key(&device.device_id);
+        devices::import_device(&app, device)?;
+        device_count += 1;
+        if exists {
+            replaced += 1;
+        }
+    }
+
+    let mut warnings = Vec::new();
+    if import_settings {
+        apply_settings(&app, bundle.settings, bundle.tls_ca, &mut warnings).await?;
+    }
+
+    audit::record(
+        &app,
+        AuditActor::desktop(),
+        audit::PERMISSIONS,
+        "import_remote_access",
+        &format!(
+            "{} devices ({} replaced), {} roles from {}",
+            device_count, replaced, role_count, path
+        ),
+        AuditOutcome::Ok,
+    );
+    Ok(ImportSummary {
+        devices: device_count,
+        replaced,
+        roles: role_count,
+        settings: import_settings,
+        warnings,
+    })
+}
+
+/// Takes over imported settings. Settings that need a server restarted go
+/// through the commands that do that; a port that cannot be bound here is
+/// reported and the current one kept.
+async fn apply_settings(
+    app: &AppHandle,
+    imported: RemoteAccessSettings,
+    tls_ca: Option<TlsCa>,
+    warnings: &mut Vec<String>,
+) -> Result<(), String> {
+    if let Some(ca) = tls_ca {
+        tls::replace_local_ca(app, &ca.cert_pem, &ca.key_pem)?;
+    }
+
+    let current = app
+        .state::<DeviceState>()
+        .remote_settings
+        .lock()
+        .map_err(|e| e.to_string())?
+        .clone();
+    let next = RemoteAccessSettings {
+        remote_enabled: current.remote_enabled,
+        transmission_enabled: current.transmission_enabled,
+        preferred_interface: current.preferred_interface.clone(),
+        tls_enabled: current.tls_enabled,
+        control_bind_address: current.control_bind_address.clone(),
+        control_port: current.control_port,
+        control_port_fallback: current.control_port_fallback,
+        ..imported.clone()
+    };
+    devices::replace_remote_access_settings(&app.state::<DeviceState>(), next)?;
+
+    devices::update_remote_access_settings(
+        app.clone(),
+        app.state(),
+        imported.remote_enabled,
+        imported.transmission_enabled,
+    )?;
+    tls::set_remote_tls_enabled(app.clone(), app.state(), app.state(), imported.tls_enabled)
+        .await?;
+    if imported.control_port == current.control_port
+        && imported.control_port_fallback == current.control_port_fallback
+    {
+        return Ok(());
+    }
+    if let Err(error) = control_server::set_control_server(
+        app.clone(),
+        app.state(),
+        current.control_bind_address,
+        imported.control_port,
+        imported.control_port_fallback,
+    )
+    .await
+    {
+        warnings.push(format!(
+            "Control port {} was not applied: {}",
+            imported.control_port, error
+        ));
+    }
+    Ok(())
+}
+
+fn check_password(password: &str) -> Result<(), String> {
+    if password.chars().count() < MIN_PASSWORD_CHARS {
+        return Err(format!(
+            "Password must be at least {} characters",
+            MIN_PASSWORD_CHARS
+        ));
+    }
+    Ok(())
+}
+
+fn read_bundle(path: &str, password: &str) -> Result<Bundle, String> {
+    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let file: BundleFile =
+        serde_json::from_str(&content).map_err(|_| "Not a remote access export".to_string())?;
+    if file.format != BUNDLE_FORMAT {
+        return Err("Not a remote access export".to_string());
+    }
+    if file.version > BUNDLE_VERSION {
+        return Err(format!(
+            "The export was made by a newer version of Lumen (format {})",
+            file.version
+        ));
+    }
+
+    let plaintext = decrypt(&file, password)?;
+    serde_json::from_slice(&plaintext).map_err(|e| e.to_string())
+}
+
+fn encrypt(plaintext: &[u8], password: &str) -> Result<BundleFile, String> {
+    let mut salt = [0u8; SALT_BYTES];
+    OsRng.fill_bytes(&mut salt);
+    let kdf = KdfParams {
+        algorithm: "argon2id".to_string(),
+        salt: STANDARD.encode(salt),
+        memory_kib: KDF_MEMORY_KIB,
+        iterations: KDF_ITERATIONS,
+        parallelism: KDF_PARALLELISM,
+    };
+
+    let cipher = Aes256Gcm::new(&derive_key(&kdf, password)?);
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let aad = associated_data(BUNDLE_FORMAT, BUNDLE_VERSION, &kdf)?;
+    let ciphertext = cipher
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: plaintext,
+                aad: &aad,
+            },
+        )
+        .map_err(|_| "Failed to encrypt the export".to_string())?;
+
+    Ok(BundleFile {
+        format: BUNDLE_FORMAT.to_string(),
+        version: BUNDLE_VERSION,
+        kdf,
+        nonce: STANDARD.encode(nonce),
+        ciphertext: STANDARD.encode(ciphertext),
+    })
+}
+
+fn decrypt(file: &BundleFile, password: &str) -> Result<Vec<u8>, String> {
+    let damaged = || "The export is damaged".to_string();
+    let nonce = STANDARD.decode(&file.nonce).map_err(|_| damaged())?;
+    if nonce.len() != 12 {
+        return Err(damaged());
+    }
+    let ciphertext = STANDARD.decode(&file.ciphertext).map_err(|_| damaged())?;
+
+    let cipher = Aes256Gcm::new(&derive_key(&file.kdf, password)?);
+    let aad = associated_data(&file.format, file.version, &file.kdf)?;
+    cipher
+        .decrypt(
+            Nonce::from_slice(&nonce),
+            Payload {
+                msg: &ciphertext,
+                aad: &aad,
+            },
+        )
+        .map_err(|_| "Wrong password, or the export is damaged".to_string())
+}
+
+fn associated_data(format: &str, version: u32, kdf: &KdfParams) -> Result<Vec<u8>, String> {
+    serde_json::to_vec(&BundleHeader {
+        format,
+        version,
+        kdf,
+    })
+    .map_err(|e| e.to_string())
+}
+
+fn derive_key(kdf: &KdfParams, password: &str) -> Result<Key<Aes256Gcm>, String> {
+    if kdf.algorithm != "argon2id" {
+        return Err(format!("Unsupported key derivation: {}", kdf.algorithm));
+    }
+    if kdf.memory_kib > MAX_KDF_MEMORY_KIB
+        || kdf.iterations > MAX_KDF_ITERATIONS
+        || kdf.parallelism > MAX_KDF_PARALLELISM
+    {
+        return Err("The export asks for more key derivation work than allowed".to_string());
+    }
+    let salt = STANDARD
+        .decode(&kdf.salt)
+        .map_err(|_| "The export is damaged".to_string())?;
+    let params = Params::new(kdf.memory_kib, kdf.iterations, kdf.parallelism, Some(32))
+        .map_err(|e| e.to_string())?;
+
+    let mut key = Key::<Aes256Gcm>::default();
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(password.as_bytes(), &salt, &mut key)
+        .map_err(|e| e.to_string())?;
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encrypted_bundle_round_trips() {
+        let file = encrypt(b"payload", "correct horse").unwrap();
+        assert_eq!(decrypt(&file, "correct horse").unwrap(), b"payload");
+        assert!(decrypt(&file, "wrong horse").is_err());
+    }
+
+    #[test]
+    fn changing_the_header_fails_decryption() {
+        let mut file = encrypt(b"payload", "correct horse").unwrap();
+        file.version = 0;
+        assert!(decrypt(&file, "correct horse").is_err());
+
+        let mut file = encrypt(b"payload", "correct horse").unwrap();
+        file.kdf.iterations += 1;
+        assert!(decrypt(&file, "correct horse").is_err());
+    }
+
+    #[test]
+    fn derive_key_rejects_excessive_cost() {
+        let kdf = |memory_kib, iterations, parallelism| KdfParams {
+            algorithm: "argon2id".to_string(),
+            salt: STANDARD.encode([0u8; SALT_BYTES]),
+            memory_kib,
+            iterations,
+            parallelism,
+        };
+        assert!(derive_key(&kdf(MAX_KDF_MEMORY_KIB + 1, 2, 1), "password").is_err());
+        assert!(derive_key(&kdf(KDF_MEMORY_KIB, u32::MAX, 1), "password").is_err());
+        assert!(derive_key(&kdf(KDF_MEMORY_KIB, 2, 64), "password").is_err());
+        assert!(derive_key(&kdf(KDF_MEMORY_KIB, KDF_ITERATIONS, 1), "password").is_ok());
+    }
+}
@@ -1,20 +1,36 @@
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     fs,
     path::PathBuf,
-    sync::Mutex,
+    sync::{Arc, Mutex},
     time::{SystemTime, UNIX_EPOCH},
 };
 
-use local_ip_address::local_ip;
 use rusqlite::{Connection, params};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use tauri::{AppHandle, Emitter, Manager, State};
-use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::{Notify, mpsc::UnboundedSender};
 use tokio_tungstenite::tungstenite::Message;
 use uuid::Uuid;
 
+use crate::audit::{self, AuditActor, AuditOutcome};
+use crate::control_limits::ControlLimits;
+use crate::control_server::{self, DEFAULT_CONTROL_PORT};
+use crate::device_tokens::{
+    TOKEN_REVOKED_CLOSE_CODE, generate_token, hash_token, is_hashed, token_expiry, verify_token,
+};
+use crate::discovery::announce_control_service;
+use crate::live_state;
+use crate::network::advertised_ip;
+use crate::pairing::{self, PairingState, RegistrationToken};
+use crate::permissions::{self, DevicePermissions, PermissionRole, PermissionState, can_receive};
+use crate::presence::{PresenceReason, emit_presence};
+use crate::protocol::PROTOCOL_VERSION;
+use crate::streaming::{ALL_LANGUAGES, SlideUpdate};
+use crate::tls::tls_fingerprints;
+use crate::topics::{Topic, TopicSubscriptions};
+
 const DEVICES_TABLE_SQL: &str = r#"
 CREATE TABLE IF NOT EXISTS devices (
     device_id TEXT PRIMARY KEY,
@@ -29,31 +45,31 @@ CREATE TABLE IF NOT EXISTS devices (
     permissions_bible INTEGER NOT NULL DEFAULT 1,
     permissions_media INTEGER NOT NULL DEFAULT 1,
     permissions_streaming INTEGER NOT NULL DEFAULT 0,
+    lyrics_language TEXT NOT NULL DEFAULT 'all',
     registered_at INTEGER NOT NULL,
     last_connected_at INTEGER
 )
 "#;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct DevicePermissions {
-    pub player: bool,
-    pub lyrics: bool,
-    pub bible: bool,
-    pub media: bool,
-    pub streaming: bool,
-}
-
-impl Default for DevicePermissions {
-    fn default() -> Self {
-        Self {
-            player: true,
-            lyrics: true,
-            bible: true,
-            media: true,
-            streaming: false,
-        }
-    }
-}
+const DEVICES_COLUMN_MIGRATIONS: &[(&str, &str)] = &[
+    (
+        "permissions_streaming",
+        "ALTER TABLE devices ADD COLUMN permissions_streaming INTEGER NOT NULL DEFAULT 0",
+    ),
+    (
+        "lyrics_language",
+        "ALTER TABLE devices ADD COLUMN lyrics_language TEXT NOT NULL DEFAULT 'all'",
+    ),
+    ("role", "ALTER TABLE devices ADD COLUMN role TEXT"),
+    (
+        "previous_token",
+        "ALTER TABLE devices ADD COLUMN previous_token TEXT",
+    ),
+    (
+        "token_expires_at",
+        "ALTER TABLE devices ADD COLUMN token_expires_at INTEGER",
+    ),
+];
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Device {
@@ -62,25 +78,61 @@ pub struct Device {
     pub device_type: String,
     pub os: String,
     pub version: String,
+    /// Salted hash of the current access token.
+    #[serde(skip)]
     pub access_token: String,
+    /// Hash of the token the device last authenticated with, still accepted
+    /// in case the rotated one never reached it.
+    #[serde(skip)]
+    pub previous_token: Option<String>,
+    #[serde(default)]
+    pub token_expires_at: Option<u64>,
     pub is_active: bool,
     pub permissions: DevicePermissions,
+    #[serde(default = "default_lyrics_language")]
+    pub lyrics_language: String,
     pub registered_at: u64,
     pub last_connected_at: Option<u64>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct RegistrationToken {
-    pub token: String,
-    pub created_at: u64,
-    pub expires_at: u64,
-    pub is_used: bool,
-}
-
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RemoteAccessSettings {
     pub remote_enabled: bool,
     pub transmission_enabled: bool,
+    #[serde(default)]
+    pub preferred_interface: Option<String>,
+    #[serde(default)]
+    pub tls_enabled: bool,
+    /// How often the server pings each remote session.
+    #[serde(default = "default_heartbeat_interval_secs")]
+    pub heartbeat_interval_secs: u64,
+    /// Sessions silent for longer than this are closed with code 4008.
+    #[serde(default = "default_session_timeout_secs")]
+    pub session_timeout_secs: u64,
+    #[serde(default)]
+    pub limits: ControlLimits,
+    /// Days an access token stays valid after it was issued or last used;
+    /// `None` never expires.
+    #[serde(default)]
+    pub token_lifetime_days: Option<u32>,
+    /// Issue a new access token in every `auth_ok`.
+    #[serde(default = "default_rotate_tokens_on_auth")]
+    pub rotate_tokens_on_auth: bool,
+    /// Let devices without a token ask the operator to approve pairing.
+    #[serde(default)]
+    pub pairing_approval_enabled: bool,
+    /// Days audit log entries are kept.
+    #[serde(default = "default_audit_retention_days")]
+    pub audit_retention_days: u32,
+    /// Address the control socket listens on; `None` listens on every
+    /// IPv4 and IPv6 address.
+    #[serde(default)]
+    pub control_bind_address: Option<String>,
+    #[serde(default = "default_control_port")]
+    pub control_port: u16,
+    /// Try the next few ports when `control_port` is taken.
+    #[serde(default = "default_control_port_fallback")]
+    pub control_port_fallback: bool,
 }
 
 impl Default for RemoteAccessSettings {
@@ -88,25 +140,67 @@ impl Default for RemoteAccessSettings {
         Self {
             remote_enabled: true,
             transmission_enabled: true,
+            preferred_interface: None,
+            tls_enabled: false,
+            heartbeat_interval_secs: default_heartbeat_interval_secs(),
+            session_timeout_secs: default_session_timeout_secs(),
+            limits: ControlLimits::default(),
+            token_lifetime_days: None,
+            rotate_tokens_on_auth: default_rotate_tokens_on_auth(),
+            pairing_approval_enabled: false,
+            audit_retention_days: default_audit_retention_days(),
+            control_bind_address: None,
+            control_port: default_control_port(),
+            control_port_fallback: default_control_port_fallback(),
         }
     }
 }
 
+fn default_heartbeat_interval_secs() -> u64 {
+    15
+}
+
+fn default_session_timeout_secs() -> u64 {
+    45
+}
+
+fn default_rotate_tokens_on_auth() -> bool {
+    true
+}
+
+fn default_audit_retention_days() -> u32 {
+    90
+}
+
+fn default_control_port() -> u16 {
+    DEFAULT_CONTROL_PORT
+}
+
+fn default_control_port_fallback() -> bool {
+    true
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WebSocketSession {
     pub session_id: String,
     pub device_id: String,
     pub permissions: DevicePermissions,
+    pub lyrics_language: String,
     pub connected_at: u64,
     pub last_activity_at: u64,
+    #[serde(default)]
+    pub subscriptions: TopicSubscriptions,
     #[serde(skip)]
     pub sender: Option<UnboundedSender<Message>>,
+    /// Wakes the connection task when the server drops the session, so a
+    /// peer that vanished without a TCP close does not keep it alive.
+    #[serde(skip)]
+    pub closer: Arc<Notify>,
 }
 
 pub struct DeviceState {
     pub devices: Mutex<HashMap<String, Device>>,
     pub sessions: Mutex<HashMap<String, WebSocketSession>>,
-    pub pending_token: Mutex<Option<RegistrationToken>>,
     pub remote_settings: Mutex<RemoteAccessSettings>,
 }
 
@@ -114,6 +208,13 @@ pub struct DeviceState {
 pub struct RegistrationTokenPayload {
     pub token: String,
     pub expires_at: u64,
+    pub ip: String,
+    pub port: u16,
+    pub tls: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cert_fingerprint: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ca_fingerprint: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -122,6 +223,7 @@ pub struct AuthOkResponse {
     pub session_id: String,
     pub desktop_name: String,
     pub permissions: DevicePermissions,
+    pub protocol_version: u32,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub access_token: Option<String>,
 }
@@ -173,6 +275,17 @@ struct DeviceDeactivatedResponse {
     device_id: String,
 }
 
+#[derive(Serialize)]
+struct LyricSlideResponse {
+    event: &'static str,
+    #[serde(flatten)]
+    payload: Value,
+}
+
+fn default_lyrics_language() -> String {
+    ALL_LANGUAGES.to_string()
+}
+
 pub fn now_ts() -> u64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -180,7 +293,7 @@ pub fn now_ts() -> u64 {
         .as_secs()
 }
 
-fn resolve_desktop_name(app: &AppHandle) -> String {
+pub fn resolve_desktop_name(app: &AppHandle) -> String {
     for key in ["COMPUTERNAME", "HOSTNAME"] {
         if let Ok(value) = std::env::var(key) {
             let trimmed = value.trim();
@@ -202,7 +315,11 @@ pub fn ensure_remote_access_ready(app: &AppHandle) -> Result<RemoteAccessSetting
     ensure_device_storage()?;
     ensure_devices_table()?;
     let settings = load_remote_access_settings()?;
-    let devices = load_devices_from_db()?;
+    audit::prepare_audit_log(&settings)?;
+    audit::start_audit_writer(app)?;
+    let roles = permissions::load_roles(&open_device_db()?)?;
+    let devices = load_devices_from_db(&roles)?;
+    permissions::set_roles(&app.state::<PermissionState>(), roles)?;
 
     let state = app.state::<DeviceState>();
     {
@@ -213,6 +330,7 @@ pub fn ensure_remote_access_ready(app: &AppHandle) -> Result<RemoteAccessSetting
         let mut remote = state.remote_settings.lock().map_err(|e| e.to_string())?;
         *remote = settings.clone();
     }
+    announce_control_service(app, settings.remote_enabled);
 
     Ok(settings)
 }
@@ -221,37 +339,41 @@ pub fn default_device_state() -> DeviceState {
     DeviceState {
         devices: Mutex::new(HashMap::new()),
         sessions: Mutex::new(HashMap::new()),
-        pending_token: Mutex::new(None),
         remote_settings: Mutex::new(RemoteAccessSettings::default()),
     }
 }
 
 #[tauri::command]
-pub fn get_local_ip() -> Result<String, String> {
-    local_ip()
-        .map(|ip| ip.to_string())
-        .map_err(|error| error.to_string())
+pub fn get_local_ip(app: AppHandle) -> Result<String, String> {
+    advertised_ip(&app).map(|ip| ip.to_string())
 }
 
 #[tauri::command]
-pub fn gen_reg_token(state: State<'_, DeviceState>) -> Result<RegistrationTokenPayload, String> {
-    let created_at = now_ts();
-    let token = RegistrationToken {
-        token: Uuid::new_v4().to_string(),
-        created_at,
-        expires_at: created_at + 900,
-        is_used: false,
-    };
+pub fn gen_reg_token(
+    app: AppHandle,
+    state: State<'_, PairingState>,
+) -> Result<RegistrationTokenPayload, String> {
+    let token = pairing::issue_qr_token(&state)?;
+    registration_payload(&app, &token)
+}
 
-    let payload = RegistrationTokenPayload {
+/// What the desktop shows for a pairing token: the token plus where to
+/// connect.
+pub fn registration_payload(
+    app: &AppHandle,
+    token: &RegistrationToken,
+) -> Result<RegistrationTokenPayload, String> {
+    let fingerprints = tls_fingerprints(app)?;
+    Ok(RegistrationTokenPayload {
         token: token.token.clone(),
         expires_at: token.expires_at,
-    };
-
-    let mut pending = state.pending_token.lock().map_err(|e| e.to_string())?;
-    *pending = Some(token);
-
-    Ok(payload)
+        ip: advertised_ip(app)?.to_string(),
+        port: control_server::active_port(app)
+            .ok_or_else(|| "The control server is not running".to_string())?,
+        tls: fingerprints.is_some(),
+        cert_fingerprint: fingerprints.as_ref().map(|(cert, _)| cert.clone()),
+        ca_fingerprint: fingerprints.map(|(_, ca)| ca),
+    })
 }
 
 #[tauri::command]
@@ -284,16 +406,20 @@ pub fn update_remote_access_settings(
     remote_enabled: bool,
     transmission_enabled: bool,
 ) -> Result<RemoteAccessSettings, String> {
-    let next = RemoteAccessSettings {
-        remote_enabled,
-        transmission_enabled,
-    };
+    let mut next = state
+        .remote_settings
+        .lock()
+        .map_err(|e| e.to_string())?
+        .clone();
+    next.remote_enabled = remote_enabled;
+    next.transmission_enabled = transmission_enabled;
 
     save_remote_access_settings(&next)?;
     {
         let mut settings = state.remote_settings.lock().map_err(|e| e.to_string())?;
         *settings = next.clone();
     }
+    announce_control_service(&app, next.remote_enabled);
 
     if !next.remote_enabled {
         close_all_external_sessions(
@@ -315,8 +441,24 @@ pub fn toggle_device(
     is_active: bool,
 ) -> Result<(), String> {
     let updated = set_device_active_state(&state, &device_id, is_active)?;
+    audit::record(
+        &app,
+        AuditActor {
+            device_id: Some(&device_id),
+            ..AuditActor::desktop()
+        },
+        audit::PERMISSIONS,
+        if is_active {
+            "activate_device"
+        } else {
+            "deactivate_device"
+        },
+        &updated.device_name,
+        AuditOutcome::Ok,
+    );
     if !updated.is_active {
         close_device_sessions(
+            &app,
             &state,
             &device_id,
             Some(auth_fail_message("not_active")?),
@@ -331,22 +473,22 @@ pub fn toggle_device(
 pub fn update_device_permissions(
     app: AppHandle,
     state: State<'_, DeviceState>,
+    permission_state: State<'_, PermissionState>,
     device_id: String,
     permissions: DevicePermissions,
 ) -> Result<(), String> {
+    let permissions = permissions::prepare_permissions(&permission_state, permissions)?;
     let updated = {
         let mut devices = state.devices.lock().map_err(|e| e.to_string())?;
         let device = devices
             .get_mut(&device_id)
             .ok_or_else(|| format!("Device not found: {}", device_id))?;
-        device.permissions = permissions.clone();
+        device.permissions = permissions;
         update_device_in_db(device)?;
         device.clone()
     };
 
-    notify_device_permissions_changed(&state, &device_id, permissions)?;
-    let _ = app.emit("device_updated", updated);
-    Ok(())
+    apply_device_permissions(&app, &state, &updated)
 }
 
 #[tauri::command]
@@ -355,15 +497,27 @@ pub fn remove_device(
     state: State<'_, DeviceState>,
     device_id: String,
 ) -> Result<(), String> {
-    {
+    let removed = {
         let mut devices = state.devices.lock().map_err(|e| e.to_string())?;
         devices
             .remove(&device_id)
-            .ok_or_else(|| format!("Device not found: {}", device_id))?;
-    }
+            .ok_or_else(|| format!("Device not found: {}", device_id))?
+    };
 
     delete_device_from_db(&device_id)?;
+    audit::record(
+        &app,
+        AuditActor {
+            device_id: Some(&device_id),
+            ..AuditActor::desktop()
+        },
+        audit::PERMISSIONS,
+        "remove_device",
+        &removed.device_name,
+        AuditOutcome::Ok,
+    );
     close_device_sessions(
+        &app,
         &state,
         &device_id,
         Some(auth_fail_message("not_registered")?),
@@ -373,12 +527,28 @@ pub fn remove_device(
     Ok(())
 }
 
+#[tauri::command]
+pub fn set_device_lyrics_language(
+    app: AppHandle,
+    state: State<'_, DeviceState>,
+    device_id: String,
+    language: Option<String>,
+) -> Result<(), String> {
+    let updated = set_lyrics_language_for_device(&state, &device_id, language.as_deref())?;
+    let _ = app.emit("device_updated", updated);
+    Ok(())
+}
+
 #[tauri::command]
 pub fn broadcast_remote_event(
+    app: AppHandle,
     state: State<'_, DeviceState>,
     envelope: RemoteSyncEnvelope,
     required_permission: Option<String>,
 ) -> Result<(), String> {
+    if envelope.event == "player_sync" {
+        live_state::apply_player_sync(&app, &envelope.payload);
+    }
     broadcast_remote_event_inner(&state, &envelope, required_permission.as_deref())
 }
 
@@ -389,44 +559,37 @@ pub fn register_device(
     sender: UnboundedSender<Message>,
 ) -> Result<AuthOkResponse, String> {
     let now = now_ts();
-    {
-        let mut pending = state.pending_token.lock().map_err(|e| e.to_string())?;
-        let token = pending
-            .as_mut()
-            .ok_or_else(|| "token_expired".to_string())?;
-
-        if token.expires_at < now {
-            return Err("token_expired".to_string());
-        }
-
-        if token.is_used {
-            return Err("token_used".to_string());
-        }
-
-        if token.token != payload.token {
-            return Err("token_expired".to_string());
-        }
-
-        token.is_used = true;
-    }
+    pairing::redeem_token(app, &payload.token, &payload.device_id)?;
 
     let existing = {
         let devices = state.devices.lock().map_err(|e| e.to_string())?;
         devices.get(&payload.device_id).cloned()
     };
 
+    let access_token = generate_token();
+    let token_expires_at = {
+        let settings = state.remote_settings.lock().map_err(|e| e.to_string())?;
+        token_expiry(&settings, now)
+    };
+    let permissions = existing
+        .as_ref()
+        .map(|d| d.permissions.clone())
+        .unwrap_or_else(|| DevicePermissions::for_new_device(&payload.device_type));
     let device = Device {
         device_id: payload.device_id,
         device_name: payload.device_name,
         device_type: payload.device_type,
         os: payload.os,
         version: payload.version,
-        access_token: Uuid::new_v4().to_string(),
+        access_token: hash_token(&access_token),
+        previous_token: None,
+        token_expires_at,
         is_active: true,
-        permissions: existing
+        permissions,
+        lyrics_language: existing
             .as_ref()
-            .map(|d| d.permissions.clone())
-            .unwrap_or_default(),
+            .map(|d| d.lyrics_language.clone())
+            .unwrap_or_else(default_lyrics_language),
         registered_at: existing.as_ref().map(|d| d.registered_at).unwrap_or(now),
         last_connected_at: Some(now),
     };
@@ -438,12 +601,7 @@ pub fn register_device(
         devices.insert(device.device_id.clone(), device.clone());
     }
 
-    let session_id = create_or_replace_session(
-        state,
-        device.device_id.clone(),
-        device.permissions.clone(),
-        sender,
-    )?;
+    let session_id = create_or_replace_session(state, &device, sender)?;
 
     let event_name = if existing.is_some() {
         "device_updated"
@@ -457,44 +615,94 @@ pub fn register_device(
         event: "auth_ok",
         session_id,
         desktop_name: resolve_desktop_name(app),
+        protocol_version: PROTOCOL_VERSION,
         permissions: device.permissions.clone(),
-        access_token: Some(device.access_token),
+        access_token: Some(access_token),
     })
 }
 
-pub fn authenticate_device(
-    app: &AppHandle,
+/// Checks a device's credentials without opening a session. Returns the
+/// device and the stored token hash that matched, or the `auth_fail` reason.
+pub fn verify_device_credentials(
     state: &State<'_, DeviceState>,
-    payload: AuthPayload,
-    sender: UnboundedSender<Message>,
-) -> Result<AuthOkResponse, String> {
-    let settings = state
+    device_id: &str,
+    access_token: &str,
+) -> Result<(Device, String), String> {
+    let remote_enabled = state
         .remote_settings
         .lock()
         .map_err(|e| e.to_string())?
-        .clone();
-    if !settings.remote_enabled {
+        .remote_enabled;
+    if !remote_enabled {
         return Err("unauthorized".to_string());
     }
 
     let device = {
         let devices = state.devices.lock().map_err(|e| e.to_string())?;
         devices
-            .get(&payload.device_id)
+            .get(device_id)
             .cloned()
             .ok_or_else(|| "not_registered".to_string())?
     };
 
-    if device.access_token != payload.access_token {
-        return Err("invalid_token".to_string());
+    let presented = check_credentials(&device, access_token, now_ts())?;
+    Ok((device, presented))
+}
+
+/// Checks `access_token` against the device's current or previous token and
+/// returns the stored hash it matched. The expiry applies to both.
+fn check_credentials(device: &Device, access_token: &str, now: u64) -> Result<String, String> {
+    let presented = if verify_token(&device.access_token, access_token) {
+        device.access_token.clone()
+    } else {
+        match device
+            .previous_token
+            .as_ref()
+            .filter(|stored| verify_token(stored, access_token))
+        {
+            Some(stored) => stored.clone(),
+            None => return Err("invalid_token".to_string()),
+        }
+    };
+
+    if device
+        .token_expires_at
+        .is_some_and(|expires_at| expires_at < now)
+    {
+        return Err("access_token_expired".to_string());
     }
 
     if !device.is_active {
         return Err("not_active".to_string());
     }
 
+    Ok(presented)
+}
+
+pub fn authenticate_device(
+    app: &AppHandle,
+    state: &State<'_, DeviceState>,
+    payload: AuthPayload,
+    sender: UnboundedSender<Message>,
+) -> Result<AuthOkResponse, String> {
+    let (device, presented) =
+        verify_device_credentials(state, &payload.device_id, &payload.access_token)?;
+    let settings = state
+        .remote_settings
+        .lock()
+        .map_err(|e| e.to_string())?
+        .clone();
+
+    let now = now_ts();
     let mut updated_device = device.clone();
-    updated_device.last_connected_at = Some(now_ts());
+    updated_device.last_connected_at = Some(now);
+    updated_device.token_expires_at = token_expiry(&settings, now);
+    let rotated = settings.rotate_tokens_on_auth.then(|| {
+        let token = generate_token();
+        updated_device.access_token = hash_token(&token);
+        updated_device.previous_token = Some(presented);
+        token
+    });
     update_device_in_db(&updated_device)?;
 
     {
@@ -502,12 +710,7 @@ pub fn authenticate_device(
         devices.insert(updated_device.device_id.clone(), updated_device.clone());
     }
 
-    let session_id = create_or_replace_session(
-        state,
-        updated_device.device_id.clone(),
-        updated_device.permissions.clone(),
-        sender,
-    )?;
+    let session_id = create_or_replace_session(state, &updated_device, sender)?;
 
     let _ = app.emit("device_authenticated", updated_device.clone());
 
@@ -515,8 +718,9 @@ pub fn authenticate_device(
         event: "auth_ok",
         session_id,
         desktop_name: resolve_desktop_name(app),
+        protocol_version: PROTOCOL_VERSION,
         permissions: updated_device.permissions,
-        access_token: None,
+        access_token: rotated,
     })
 }
 
@@ -527,6 +731,7 @@ pub fn deactivate_device_registration(
 ) -> Result<(), String> {
     let updated = set_device_active_state(state, device_id, false)?;
     close_device_sessions(
+        app,
         state,
         device_id,
         Some(auth_fail_message("not_active")?),
@@ -548,10 +753,27 @@ pub fn touch_session(
     Ok(None)
 }
 
-pub fn remove_session(state: &State<'_, DeviceState>, session_id: &str) -> Result<(), String> {
+/// Applies `change` to a session's topic subscriptions and returns the result.
+pub fn update_session_subscriptions(
+    state: &State<'_, DeviceState>,
+    session_id: &str,
+    change: impl FnOnce(&mut TopicSubscriptions) -> Result<(), String>,
+) -> Result<TopicSubscriptions, String> {
     let mut sessions = state.sessions.lock().map_err(|e| e.to_string())?;
-    sessions.remove(session_id);
-    Ok(())
+    let session = sessions
+        .get_mut(session_id)
+        .ok_or_else(|| "Session not found".to_string())?;
+    change(&mut session.subscriptions)?;
+    Ok(session.subscriptions.clone())
+}
+
+/// Removes a session, returning it if it was still registered.
+pub fn remove_session(
+    state: &State<'_, DeviceState>,
+    session_id: &str,
+) -> Result<Option<WebSocketSession>, String> {
+    let mut sessions = state.sessions.lock().map_err(|e| e.to_string())?;
+    Ok(sessions.remove(session_id))
 }
 
 pub fn is_remote_access_enabled(state: &State<'_, DeviceState>) -> Result<bool, String> {
@@ -562,6 +784,14 @@ pub fn is_remote_access_enabled(state: &State<'_, DeviceState>) -> Result<bool,
         .map_err(|e| e.to_string())
 }
 
+pub fn is_pairing_approval_enabled(state: &State<'_, DeviceState>) -> Result<bool, String> {
+    state
+        .remote_settings
+        .lock()
+        .map(|settings| settings.pairing_approval_enabled)
+        .map_err(|e| e.to_string())
+}
+
 pub fn auth_fail_message(reason: &str) -> Result<Message, String> {
     json_message(&AuthFailResponse {
         event: "auth_fail",
@@ -589,15 +819,20 @@ pub fn broadcast_remote_event_inner(
     required_permission: Option<&str>,
 ) -> Result<(), String> {
     let message = json_message(envelope)?;
-    let sessions = state.sessions.lock().map_err(|e| e.to_string())?;
+    let topic = Topic::for_event(&envelope.event, &envelope.payload);
+    let mut sessions = state.sessions.lock().map_err(|e| e.to_string())?;
 
-    for session in sessions.values() {
+    for session in sessions.values_mut() {
         if let Some(permission) = required_permission {
-            if !is_permission_allowed(&session.permissions, permission) {
+            if !can_receive(&session.permissions, permission) {
                 continue;
             }
         }
 
+        if topic.is_some_and(|topic| !session.subscriptions.admit(topic)) {
+            continue;
+        }
+
         if let Some(sender) = &session.sender {
             let _ = sender.send(message.clone());
         }
@@ -606,25 +841,331 @@ pub fn broadcast_remote_event_inner(
     Ok(())
 }
 
-fn notify_device_permissions_changed(
+pub fn set_preferred_interface_setting(
+    state: &DeviceState,
+    preferred_interface: Option<String>,
+) -> Result<RemoteAccessSettings, String> {
+    let mut settings = state.remote_settings.lock().map_err(|e| e.to_string())?;
+    let mut next = settings.clone();
+    next.preferred_interface = preferred_interface;
+    save_remote_access_settings(&next)?;
+    *settings = next.clone();
+    Ok(next)
+}
+
+pub fn set_tls_enabled_setting(
+    state: &DeviceState,
+    tls_enabled: bool,
+) -> Result<RemoteAccessSettings, String> {
+    let mut settings = state.remote_settings.lock().map_err(|e| e.to_string())?;
+    let mut next = settings.clone();
+    next.tls_enabled = tls_enabled;
+    save_remote_access_settings(&next)?;
+    *settings = next.clone();
+    Ok(next)
+}
+
+pub fn set_heartbeat_setting(
+    state: &DeviceState,
+    heartbeat_interval_secs: u64,
+    session_timeout_secs: u64,
+) -> Result<RemoteAccessSettings, String> {
+    let mut settings = state.remote_settings.lock().map_err(|e| e.to_string())?;
+    let mut next = settings.clone();
+    next.heartbeat_interval_secs = heartbeat_interval_secs;
+    next.session_timeout_secs = session_timeout_secs;
+    save_remote_access_settings(&next)?;
+    *settings = next.clone();
+    Ok(next)
+}
+
+pub fn set_token_policy_setting(
+    state: &DeviceState,
+    token_lifetime_days: Option<u32>,
+    rotate_tokens_on_auth: bool,
+) -> Result<RemoteAccessSettings, String> {
+    let mut settings = state.remote_settings.lock().map_err(|e| e.to_string())?;
+    let mut next = settings.clone();
+    next.token_lifetime_days = token_lifetime_days;
+    next.rotate_tokens_on_auth = rotate_tokens_on_auth;
+    save_remote_access_settings(&next)?;
+    *settings = next.clone();
+    Ok(next)
+}
+
+/// Replaces the stored settings wholesale, for settings imported from
+/// another desktop.
+pub fn replace_remote_access_settings(
+    state: &DeviceState,
+    next: RemoteAccessSettings,
+) -> Result<RemoteAccessSettings, String> {
+    let mut settings = state.remote_settings.lock().map_err(|e| e.to_string())?;
+    save_remote_access_settings(&next)?;
+    *settings = next.clone();
+    Ok(next)
+}
+
+/// Adds a device carried over from another desktop, token hashes included,
+/// replacing any device with the same id. Sessions of a replaced device are
+/// closed, since the token they authenticated with is no longer valid.
+pub fn import_device(app: &AppHandle, device: Device) -> Result<(), String> {
+    let state = app.state::<DeviceState>();
+    insert_or_replace_device_in_db(&device)?;
+    let existing = {
+        let mut devices = state.devices.lock().map_err(|e| e.to_string())?;
+        devices.insert(device.device_id.clone(), device.clone())
+    };
+
+    let event_name = if existing.is_some() {
+        close_device_sessions(
+            app,
+            &state,
+            &device.device_id,
+            Some(auth_fail_message("token_revoked")?),
+            Some(TOKEN_REVOKED_CLOSE_CODE),
+        )?;
+        "device_updated"
+    } else {
+        "device_registered"
+    };
+    let _ = app.emit(event_name, device);
+    Ok(())
+}
+
+/// Replaces every device's token with one nobody knows and closes their
+/// sessions. Returns how many devices were affected.
+pub fn revoke_all_tokens(app: &AppHandle, state: &State<'_, DeviceState>) -> Result<usize, String> {
+    let device_ids = {
+        let mut devices = state.devices.lock().map_err(|e| e.to_string())?;
+        for device in devices.values_mut() {
+            device.access_token = hash_token(&generate_token());
+            device.previous_token = None;
+            update_device_in_db(device)?;
+        }
+        devices.keys().cloned().collect::<Vec<_>>()
+    };
+    audit::record(
+        app,
+        AuditActor::desktop(),
+        audit::AUTH,
+        "revoke_all_tokens",
+        &format!("{} devices", device_ids.len()),
+        AuditOutcome::Ok,
+    );
+
+    for device_id in &device_ids {
+        close_device_sessions(
+            app,
+            state,
+            device_id,
+            Some(auth_fail_message("token_revoked")?),
+            Some(TOKEN_REVOKED_CLOSE_CODE),
+        )?;
+    }
+
+    Ok(device_ids.len())
+}
+
+pub fn set_pairing_approval_setting(
+    state: &DeviceState,
+    pairing_approval_enabled: bool,
+) -> Result<RemoteAccessSettings, String> {
+    let mut settings = state.remote_settings.lock().map_err(|e| e.to_string())?;
+    let mut next = settings.clone();
+    next.pairing_approval_enabled = pairing_approval_enabled;
+    save_remote_access_settings(&next)?;
+    *settings = next.clone();
+    Ok(next)
+}
+
+pub fn set_control_server_setting(
+    state: &DeviceState,
+    next: &RemoteAccessSettings,
+) -> Result<RemoteAccessSettings, String> {
+    let mut settings = state.remote_settings.lock().map_err(|e| e.to_string())?;
+    let mut updated = settings.clone();
+    updated.control_bind_address = next.control_bind_address.clone();
+    updated.control_port = next.control_port;
+    updated.control_port_fallback = next.control_port_fallback;
+    save_remote_access_settings(&updated)?;
+    *settings = updated.clone();
+    Ok(updated)
+}
+
+pub fn set_audit_retention_setting(
+    state: &DeviceState,
+    audit_retention_days: u32,
+) -> Result<RemoteAccessSettings, String> {
+    let mut settings = state.remote_settings.lock().map_err(|e| e.to_string())?;
+    let mut next = settings.clone();
+    next.audit_retention_days = audit_retention_days;
+    save_remote_access_settings(&next)?;
+    *settings = next.clone();
+    Ok(next)
+}
+
+pub fn set_control_limits_setting(
+    state: &DeviceState,
+    limits: ControlLimits,
+) -> Result<RemoteAccessSettings, String> {
+    let mut settings = state.remote_settings.lock().map_err(|e| e.to_string())?;
+    let mut next = settings.clone();
+    next.limits = limits;
+    save_remote_access_settings(&next)?;
+    *settings = next.clone();
+    Ok(next)
+}
+
+/// Sends the current lyric slide to every session with lyrics access, rendered
+/// in the language each device picked.
+pub fn broadcast_lyric_slide(
+    state: &State<'_, DeviceState>,
+    update: &SlideUpdate,
+) -> Result<(), String> {
+    let mut sessions = state.sessions.lock().map_err(|e| e.to_string())?;
+    let mut rendered: HashMap<String, Message> = HashMap::new();
+
+    for session in sessions.values_mut() {
+        if !can_receive(&session.permissions, "lyrics")
+            || !session.subscriptions.admit(Topic::Lyrics)
+        {
+            continue;
+        }
+
+        let Some(sender) = &session.sender else {
+            continue;
+        };
+
+        if !rendered.contains_key(&session.lyrics_language) {
+            let message = lyric_slide_message(update, &session.lyrics_language)?;
+            rendered.insert(session.lyrics_language.clone(), message);
+        }
+
+        if let Some(message) = rendered.get(&session.lyrics_language) {
+            let _ = sender.send(message.clone());
+        }
+    }
+
+    Ok(())
+}
+
+pub(crate) fn lyric_slide_message(update: &SlideUpdate, language: &str) -> Result<Message, String> {
+    json_message(&LyricSlideResponse {
+        event: "lyric_slide",
+        payload: update.to_payload(language),
+    })
+}
+
+/// Persists the lyric language a device sees and applies it to its live
+/// sessions. `None` or an empty value selects every language stacked.
+pub fn set_lyrics_language_for_device(
     state: &State<'_, DeviceState>,
     device_id: &str,
-    permissions: DevicePermissions,
+    language: Option<&str>,
+) -> Result<Device, String> {
+    let language = language
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+        .unwrap_or_else(default_lyrics_language);
+
+    let updated = {
+        let mut devices = state.devices.lock().map_err(|e| e.to_string())?;
+        let device = devices
+            .get_mut(device_id)
+            .ok_or_else(|| format!("Device not found: {}", device_id))?;
+        device.lyrics_language = language.clone();
+        update_device_in_db(device)?;
+        device.clone()
+    };
+
+    let mut sessions = state.sessions.lock().map_err(|e| e.to_string())?;
+    for session in sessions.values_mut() {
+        if session.device_id == device_id {
+            session.lyrics_language = language.clone();
+        }
+    }
+
+    Ok(updated)
+}
+
+/// Re-resolves the permissions of every device on `role_id` after the role
+/// changed. Devices whose role was deleted keep only their direct scopes.
+pub(crate) fn refresh_role_permissions(
+    app: &AppHandle,
+    role_id: &str,
+    roles: &BTreeMap<String, PermissionRole>,
+) -> Result<(), String> {
+    let state = app.state::<DeviceState>();
+    let updated = {
+        let mut devices = state.devices.lock().map_err(|e| e.to_string())?;
+        let mut updated = Vec::new();
+        for device in devices.values_mut() {
+            if device.permissions.role.as_deref() != Some(role_id) {
+                continue;
+            }
+            if !roles.contains_key(role_id) {
+                device.permissions.role = None;
+            }
+            device.permissions.resolve(roles);
+            update_device_in_db(device)?;
+            updated.push(device.clone());
+        }
+        updated
+    };
+
+    for device in updated {
+        apply_device_permissions(app, &state, &device)?;
+    }
+    Ok(())
+}
+
+/// Applies a device's new permissions to its live sessions, tells them and
+/// the desktop UI.
+fn apply_device_permissions(
+    app: &AppHandle,
+    state: &State<'_, DeviceState>,
+    device: &Device,
 ) -> Result<(), String> {
-    let sessions = state.sessions.lock().map_err(|e| e.to_string())?;
     let message = json_message(&PermissionsUpdatedResponse {
         event: "permissions_updated",
-        permissions,
+        permissions: device.permissions.clone(),
     })?;
+    audit::record(
+        app,
+        AuditActor {
+            device_id: Some(&device.device_id),
+            ..AuditActor::desktop()
+        },
+        audit::PERMISSIONS,
+        "update_permissions",
+        &format!(
+            "role={} scopes={}",
+            device.permissions.role.as_deref().unwrap_or("custom"),
+            device
+                .permissions
+                .scopes
+                .iter()
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(" ")
+        ),
+        AuditOutcome::Ok,
+    );
 
-    for session in sessions.values() {
-        if session.device_id == device_id {
-            if let Some(sender) = &session.sender {
-                let _ = sender.send(message.clone());
+    {
+        let mut sessions = state.sessions.lock().map_err(|e| e.to_string())?;
+        for session in sessions.values_mut() {
+            if session.device_id == device.device_id {
+                session.permissions = device.permissions.clone();
+                if let Some(sender) = &session.sender {
+                    let _ = sender.send(message.clone());
+                }
             }
         }
     }
 
+    let _ = app.emit("device_updated", device.clone());
     Ok(())
 }
 
@@ -658,6 +1199,7 @@ fn close_all_external_sessions(
 
     for device_id in device_ids {
         close_device_sessions(
+            app,
             state,
             &device_id,
             auth_fail_reason
@@ -673,6 +1215,7 @@ fn close_all_external_sessions(
 }
 
 fn close_device_sessions(
+    app: &AppHandle,
     state: &State<'_, DeviceState>,
     device_id: &str,
     auth_fail_message: Option<Message>,
@@ -692,18 +1235,25 @@ fn close_device_sessions(
             .collect::<Vec<_>>()
     };
 
-    let mut sessions = state.sessions.lock().map_err(|e| e.to_string())?;
-    for session_id in session_ids {
-        if let Some(session) = sessions.remove(&session_id) {
-            if let Some(sender) = session.sender {
-                if let Some(message) = auth_fail_message.clone() {
-                    let _ = sender.send(message);
-                }
-                if let Some(code) = close_code {
-                    let _ = sender.send(close_message(code));
-                }
+    let closed = {
+        let mut sessions = state.sessions.lock().map_err(|e| e.to_string())?;
+        session_ids
+            .iter()
+            .filter_map(|session_id| sessions.remove(session_id))
+            .collect::<Vec<_>>()
+    };
+
+    for session in closed {
+        if let Some(sender) = &session.sender {
+            if let Some(message) = auth_fail_message.clone() {
+                let _ = sender.send(message);
+            }
+            if let Some(code) = close_code {
+                let _ = sender.send(close_message(code));
             }
         }
+        session.closer.notify_one();
+        emit_presence(app, &session, PresenceReason::Disconnected);
     }
 
     Ok(())
@@ -711,18 +1261,20 @@ fn close_device_sessions(
 
 fn create_or_replace_session(
     state: &State<'_, DeviceState>,
-    device_id: String,
-    permissions: DevicePermissions,
+    device: &Device,
     sender: UnboundedSender<Message>,
 ) -> Result<String, String> {
     let session_id = Uuid::new_v4().to_string();
     let session = WebSocketSession {
         session_id: session_id.clone(),
-        device_id,
-        permissions,
+        device_id: device.device_id.clone(),
+        permissions: device.permissions.clone(),
+        lyrics_language: device.lyrics_language.clone(),
         connected_at: now_ts(),
         last_activity_at: now_ts(),
+        subscriptions: TopicSubscriptions::default(),
         sender: Some(sender),
+        closer: Arc::new(Notify::new()),
     };
 
     let mut sessions = state.sessions.lock().map_err(|e| e.to_string())?;
@@ -742,6 +1294,7 @@ fn ensure_devices_table() -> Result<(), String> {
         .execute(DEVICES_TABLE_SQL, [])
         .map_err(|e| e.to_string())?;
     ensure_devices_schema(&connection)?;
+    permissions::ensure_permission_tables(&connection)?;
     Ok(())
 }
 
@@ -753,33 +1306,28 @@ fn ensure_devices_schema(connection: &Connection) -> Result<(), String> {
         .query_map([], |row| row.get::<_, String>(1))
         .map_err(|e| e.to_string())?;
 
-    let mut has_permissions_streaming = false;
+    let mut existing = Vec::new();
     for column in columns {
-        let column = column.map_err(|e| e.to_string())?;
-        if column == "permissions_streaming" {
-            has_permissions_streaming = true;
-            break;
-        }
+        existing.push(column.map_err(|e| e.to_string())?);
     }
 
-    if !has_permissions_streaming {
-        connection
-            .execute(
-                "ALTER TABLE devices ADD COLUMN permissions_streaming INTEGER NOT NULL DEFAULT 0",
-                [],
-            )
-            .map_err(|e| e.to_string())?;
+    for (column, sql) in DEVICES_COLUMN_MIGRATIONS {
+        if !existing.iter().any(|name| name == column) {
+            connection.execute(sql, []).map_err(|e| e.to_string())?;
+        }
     }
 
     Ok(())
 }
 
-fn open_device_db() -> Result<Connection, String> {
+pub(crate) fn open_device_db() -> Result<Connection, String> {
     let path = device_db_path()?;
     Connection::open(path).map_err(|e| e.to_string())
 }
 
-fn app_base_dir() -> Result<PathBuf, String> {
+/// `lumen` next to the executable, where config, the database and files
+/// live.
+pub fn app_base_dir() -> Result<PathBuf, String> {
     let exe = std::env::current_exe().map_err(|e| e.to_string())?;
     let parent = exe
         .parent()
@@ -813,8 +1361,11 @@ fn save_remote_access_settings(settings: &RemoteAccessSettings) -> Result<(), St
     fs::write(path, content).map_err(|e| e.to_string())
 }
 
-fn load_devices_from_db() -> Result<HashMap<String, Device>, String> {
+fn load_devices_from_db(
+    roles: &BTreeMap<String, PermissionRole>,
+) -> Result<HashMap<String, Device>, String> {
     let connection = open_device_db()?;
+    let mut scopes = permissions::load_device_scopes(&connection)?;
     let mut statement = connection
         .prepare(
             r#"
@@ -825,12 +1376,11 @@ fn load_devices_from_db() -> Result<HashMap<String, Device>, String> {
                 os,
                 version,
                 access_token,
+                previous_token,
+                token_expires_at,
                 is_active,
-                permissions_player,
-                permissions_lyrics,
-                permissions_bible,
-                permissions_media,
-                permissions_streaming,
+                role,
+                lyrics_language,
                 registered_at,
                 last_connected_at
             FROM devices
@@ -847,26 +1397,37 @@ fn load_devices_from_db() -> Result<HashMap<String, Device>, String> {
                 os: row.get(3)?,
                 version: row.get(4)?,
                 access_token: row.get(5)?,
-                is_active: row.get::<_, i64>(6)? == 1,
-                permissions: DevicePermissions {
-                    player: row.get::<_, i64>(7)? == 1,
-                    lyrics: row.get::<_, i64>(8)? == 1,
-                    bible: row.get::<_, i64>(9)? == 1,
-                    media: row.get::<_, i64>(10)? == 1,
-                    streaming: row.get::<_, i64>(11)? == 1,
-                },
-                registered_at: row.get::<_, u64>(12)?,
-                last_connected_at: row.get(13)?,
+                previous_token: row.get(6)?,
+                token_expires_at: row.get(7)?,
+                is_active: row.get::<_, i64>(8)? == 1,
+                permissions: DevicePermissions::new(row.get(9)?, Default::default()),
+                lyrics_language: row.get(10)?,
+                registered_at: row.get::<_, u64>(11)?,
+                last_connected_at: row.get(12)?,
             })
         })
         .map_err(|e| e.to_string())?;
 
     let mut devices = HashMap::new();
+    let mut plaintext = Vec::new();
     for row in rows {
-        let device = row.map_err(|e| e.to_string())?;
+        let mut device = row.map_err(|e| e.to_string())?;
+        device.permissions.scopes = scopes.remove(&device.device_id).unwrap_or_default();
+        device.permissions.resolve(roles);
+        if !is_hashed(&device.access_token) {
+            device.access_token = hash_token(&device.access_token);
+            plaintext.push(device.device_id.clone());
+        }
         devices.insert(device.device_id.clone(), device);
     }
 
+    // Tokens stored before hashing are hashed in place; devices keep working.
+    for device_id in plaintext {
+        if let Some(device) = devices.get(&device_id) {
+            insert_or_replace_device_in_db(device)?;
+        }
+    }
+
     Ok(devices)
 }
 
@@ -882,15 +1443,21 @@ fn insert_or_replace_device_in_db(device: &Device) -> Result<(), String> {
                 os,
                 version,
                 access_token,
+                previous_token,
+                token_expires_at,
                 is_active,
                 permissions_player,
                 permissions_lyrics,
                 permissions_bible,
                 permissions_media,
                 permissions_streaming,
+                role,
+                lyrics_language,
                 registered_at,
                 last_connected_at
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)
+            ) VALUES (
+                ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18
+            )
             "#,
             params![
                 device.device_id,
@@ -899,18 +1466,22 @@ fn insert_or_replace_device_in_db(device: &Device) -> Result<(), String> {
                 device.os,
                 device.version,
                 device.access_token,
+                device.previous_token,
+                device.token_expires_at,
                 i64::from(device.is_active),
                 i64::from(device.permissions.player),
                 i64::from(device.permissions.lyrics),
                 i64::from(device.permissions.bible),
                 i64::from(device.permissions.media),
                 i64::from(device.permissions.streaming),
+                device.permissions.role,
+                device.lyrics_language,
                 device.registered_at,
                 device.last_connected_at,
             ],
         )
         .map_err(|e| e.to_string())?;
-    Ok(())
+    permissions::save_device_scopes(&connection, &device.device_id, &device.permissions.scopes)
 }
 
 fn update_device_in_db(device: &Device) -> Result<(), String> {
@@ -922,7 +1493,7 @@ fn delete_device_from_db(device_id: &str) -> Result<(), String> {
     connection
         .execute("DELETE FROM devices WHERE device_id = ?1", [device_id])
         .map_err(|e| e.to_string())?;
-    Ok(())
+    permissions::delete_device_scopes(&connection, device_id)
 }
 
 fn json_message<T: Serialize>(payload: &T) -> Result<Message, String> {
@@ -931,34 +1502,92 @@ fn json_message<T: Serialize>(payload: &T) -> Result<Message, String> {
         .map_err(|e| e.to_string())
 }
 
-fn close_message(code: u16) -> Message {
+pub(crate) fn close_message(code: u16) -> Message {
     Message::Close(Some(tokio_tungstenite::tungstenite::protocol::CloseFrame {
-        code: tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode::Library(code),
+        code: tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode::from(code),
         reason: "".into(),
     }))
 }
 
-pub fn map_event_permission(event: &str) -> Option<&'static str> {
-    match event {
-        "play_pause" | "stop" | "next" | "previous" | "mute" | "set_volume" | "seek"
-        | "set_loop" | "load_url" | "metadata" | "progress" => Some("player"),
-        "load_lyric" => Some("lyrics"),
-        "subscribe_stream"
-        | "unsubscribe_stream"
-        | "webrtc_answer"
-        | "webrtc_ice_candidate"
-        | "mobile_offer" => Some("streaming"),
-        _ => None,
-    }
-}
-
-pub fn is_permission_allowed(permissions: &DevicePermissions, permission: &str) -> bool {
-    match permission {
-        "player" => permissions.player,
-        "lyrics" => permissions.lyrics,
-        "bible" => permissions.bible,
-        "media" => permissions.media,
-        "streaming" => permissions.streaming,
-        _ => false,
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::device_tokens::hash_token;
+
+    fn device(token: &str, previous: Option<&str>, expires_at: Option<u64>) -> Device {
+        Device {
+            device_id: "phone".to_string(),
+            device_name: "Phone".to_string(),
+            device_type: "mobile".to_string(),
+            os: "ios".to_string(),
+            version: "1.0".to_string(),
+            access_token: hash_token(token),
+            previous_token: previous.map(hash_token),
+            token_expires_at: expires_at,
+            is_active: true,
+            permissions: DevicePermissions::default(),
+            lyrics_language: default_lyrics_language(),
+            registered_at: 0,
+            last_connected_at: None,
+        }
+    }
+
+    #[test]
+    fn credentials_accept_the_current_token() {
+        let device = device("current", None, None);
+        assert_eq!(
+            check_credentials(&device, "current", 100),
+            Ok(device.access_token.clone())
+        );
+        assert_eq!(
+            check_credentials(&device, "other", 100),
+            Err("invalid_token".to_string())
+        );
+    }
+
+    #[test]
+    fn credentials_accept_the_previous_token_until_it_rotates_again() {
+        let device = device("current", Some("previous"), None);
+        assert_eq!(
+            check_credentials(&device, "previous", 100),
+            device.previous_token.clone().ok_or_else(String::new)
+        );
+
+        let rotated = Device {
+            previous_token: Some(device.access_token.clone()),
+            access_token: hash_token("next"),
+            ..device
+        };
+        assert_eq!(
+            check_credentials(&rotated, "previous", 100),
+            Err("invalid_token".to_string())
+        );
+        assert!(check_credentials(&rotated, "current", 100).is_ok());
+    }
+
+    #[test]
+    fn credentials_expire_for_current_and_previous_tokens() {
+        let device = device("current", Some("previous"), Some(100));
+        assert!(check_credentials(&device, "current", 100).is_ok());
+        assert_eq!(
+            check_credentials(&device, "current", 101),
+            Err("access_token_expired".to_string())
+        );
+        assert_eq!(
+            check_credentials(&device, "previous", 101),
+            Err("access_token_expired".to_string())
+        );
+    }
+
+    #[test]
+    fn credentials_reject_inactive_devices() {
+        let device = Device {
+            is_active: false,
+            ..device("current", None, None)
+        };
+        assert_eq!(
+            check_credentials(&device, "current", 100),
+            Err("not_active".to_string())
+        );
     }
 }
@@ -0,0 +1,159 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use mdns_sd::{ServiceDaemon, ServiceInfo};
+use tauri::{AppHandle, Manager};
+
+use crate::control_server::active_port;
+use crate::devices::resolve_desktop_name;
+use crate::network::preferred_ip;
+use crate::protocol::PROTOCOL_VERSION;
+use crate::tls::is_tls_enabled;
+
+const CONTROL_SERVICE_TYPE: &str = "_lumen-control._tcp.local.";
+const HTTP_SERVICE_TYPE: &str = "_http._tcp.local.";
+
+/// Multicast DNS announcements for the control socket and the HTML server so
+/// phones and stage displays can find the desktop without typing an address.
+pub struct DiscoveryState {
+    daemon: Mutex<Option<ServiceDaemon>>,
+    control_service: Mutex<Option<String>>,
+    html_service: Mutex<Option<String>>,
+}
+
+pub fn default_discovery_state() -> DiscoveryState {
+    DiscoveryState {
+        daemon: Mutex::new(None),
+        control_service: Mutex::new(None),
+        html_service: Mutex::new(None),
+    }
+}
+
+/// Announces or withdraws `_lumen-control._tcp`, following the remote access
+/// toggle and the port the control server is listening on.
+pub fn announce_control_service(app: &AppHandle, enabled: bool) {
+    let state = app.state::<DiscoveryState>();
+    let properties = txt_properties(app, "control,lyrics,player,streaming");
+
+    if let Err(error) = update_service(
+        app,
+        &state,
+        &state.control_service,
+        active_port(app).filter(|_| enabled),
+        CONTROL_SERVICE_TYPE,
+        properties,
+    ) {
+        eprintln!("Failed to update control service announcement: {}", error);
+    }
+}
+
+/// Announces `_http._tcp` on `port` while the HTML server runs, or withdraws
+/// it when `port` is `None`.
+pub fn announce_html_service(app: &AppHandle, port: Option<u16>) {
+    let state = app.state::<DiscoveryState>();
+    let mut properties = txt_properties(app, "slides");
+    properties.insert("path".to_string(), "/".to_string());
+
+    if let Err(error) = update_service(
+        app,
+        &state,
+        &state.html_service,
+        port,
+        HTTP_SERVICE_TYPE,
+        properties,
+    ) {
+        eprintln!("Failed to update HTML service announcement: {}", error);
+    }
+}
+
+fn update_service(
+    app: &AppHandle,
+    state: &DiscoveryState,
+    slot: &Mutex<Option<String>>,
+    port: Option<u16>,
+    service_type: &str,
+    properties: HashMap<String, String>,
+) -> Result<(), String> {
+    let daemon = ensure_daemon(state)?;
+    let mut current = slot.lock().map_err(|e| e.to_string())?;
+
+    if let Some(fullname) = current.take() {
+        daemon.unregister(&fullname).map_err(|e| e.to_string())?;
+    }
+
+    let Some(port) = port else {
+        return Ok(());
+    };
+
+    let desktop_name = resolve_desktop_name(app);
+    let host_name = format!("{}.local.", hostname_label(&desktop_name));
+    // With a preferred interface, only its address is announced so clients
+    // do not pick up a VPN or virtual adapter the operator ruled out.
+    let info = match preferred_ip(app)? {
+        Some(ip) => ServiceInfo::new(
+            service_type,
+            &desktop_name,
+            &host_name,
+            ip,
+            port,
+            properties,
+        ),
+        None => ServiceInfo::new(
+            service_type,
+            &desktop_name,
+            &host_name,
+            "",
+            port,
+            properties,
+        )
+        .map(ServiceInfo::enable_addr_auto),
+    }
+    .map_err(|e| e.to_string())?;
+
+    let fullname = info.get_fullname().to_string();
+    daemon.register(info).map_err(|e| e.to_string())?;
+    *current = Some(fullname);
+    Ok(())
+}
+
+fn ensure_daemon(state: &DiscoveryState) -> Result<ServiceDaemon, String> {
+    let mut daemon = state.daemon.lock().map_err(|e| e.to_string())?;
+    if let Some(existing) = daemon.as_ref() {
+        return Ok(existing.clone());
+    }
+
+    let created = ServiceDaemon::new().map_err(|e| e.to_string())?;
+    *daemon = Some(created.clone());
+    Ok(created)
+}
+
+fn txt_properties(app: &AppHandle, capabilities: &str) -> HashMap<String, String> {
+    let tls = is_tls_enabled(app).unwrap_or(false);
+    HashMap::from([
+        (
+            "version".to_string(),
+            app.package_info().version.to_string(),
+        ),
+        ("protocol".to_string(), PROTOCOL_VERSION.to_string()),
+        ("caps".to_string(), capabilities.to_string()),
+        ("tls".to_string(), if tls { "1" } else { "0" }.to_string()),
+    ])
+}
+
+/// Reduces a display name such as "Booth PC (Main)" to a valid DNS label.
+fn hostname_label(name: &str) -> String {
+    let mut label = String::with_capacity(name.len());
+    for ch in name.chars() {
+        if ch.is_ascii_alphanumeric() {
+            label.push(ch.to_ascii_lowercase());
+        } else if !label.ends_with('-') {
+            label.push('-');
+        }
+    }
+
+    let label = label.trim_matches('-');
+    if label.is_empty() {
+        "lumen".to_string()
+    } else {
+        label.chars().take(63).collect()
+    }
+}
@@ -0,0 +1,918 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use futures_util::{SinkExt, StreamExt};
+use rustls::{
+    ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme,
+    client::{
+        danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+        verify_server_cert_signed_by_trust_anchor, verify_server_name,
+    },
+    crypto::{CryptoProvider, verify_tls12_signature, verify_tls13_signature},
+    pki_types::{CertificateDer, ServerName, UnixTime},
+    server::ParsedCertificate,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use tauri::{AppHandle, Emitter, Manager, State, async_runtime};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    net::TcpStream,
+};
+use tokio_rustls::TlsConnector;
+use tokio_tungstenite::{WebSocketStream, client_async, tungstenite::Message};
+use uuid::Uuid;
+
+use crate::devices::{app_base_dir, now_ts, resolve_desktop_name};
+use crate::live_state::{self, LiveSnapshot};
+use crate::protocol::{ClientMessage, PROTOCOL_VERSION};
+use crate::tls::{sha256_fingerprint, write_private_file};
+use crate::websocket::run_control_message;
+
+/// Device type a follower registers with. The leader gives it the
+/// view-only `follower` role when it first pairs.
+pub const FOLLOWER_DEVICE_TYPE: &str = "lumen_follower";
+/// How often the follower sends `heartbeat` to the leader.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const RECONNECT_DELAY: Duration = Duration::from_secs(3);
+/// Playback drift tolerated before the follower seeks to the leader's position.
+const SEEK_DRIFT_SECS: f64 = 3.0;
+/// Gives the player time to land on a seek before drift is checked again.
+const SEEK_COOLDOWN: Duration = Duration::from_secs(5);
+/// Source recorded for the commands the follower applies locally.
+const MIRROR_SOURCE: &str = "follower";
+
+/// The leader this desktop follows and how to reach it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeaderLink {
+    pub host: String,
+    pub port: u16,
+    #[serde(default)]
+    pub tls: bool,
+    /// SHA-256 fingerprint of the leader's local CA, from its pairing code.
+    /// Pinning the CA keeps the link working when the leader issues a new
+    /// server certificate.
+    #[serde(default)]
+    pub ca_fingerprint: Option<String>,
+    pub device_id: String,
+    /// One-time pairing token, used until the first `auth_ok`.
+    #[serde(default)]
+    pub pairing_token: Option<String>,
+    #[serde(default)]
+    pub access_token: Option<String>,
+    #[serde(default)]
+    pub desktop_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FollowerConfig {
+    pub enabled: bool,
+    pub leader: Option<LeaderLink>,
+    /// Heartbeats the leader may miss before takeover is offered.
+    pub missed_heartbeats: u32,
+}
+
+impl Default for FollowerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            leader: None,
+            missed_heartbeats: 3,
+        }
+    }
+}
+
+/// What the QR code and the "Copy pairing code" button of a leader carry.
+#[derive(Debug, Deserialize)]
+struct PairingCode {
+    ip: String,
+    port: u16,
+    token: String,
+    #[serde(default)]
+    tls: bool,
+    #[serde(default)]
+    ca_fingerprint: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FollowerPhase {
+    Disabled,
+    Connecting,
+    Following,
+    /// The leader stopped answering; the operator may take over.
+    LeaderLost,
+    TakenOver,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FollowerStatus {
+    pub phase: FollowerPhase,
+    pub paired: bool,
+    /// `host:port` of the leader.
+    pub leader: Option<String>,
+    pub desktop_name: Option<String>,
+    pub last_seen_at: Option<u64>,
+    pub revision: Option<u64>,
+    pub missed_heartbeats: u32,
+    pub error: Option<String>,
+}
+
+impl Default for FollowerStatus {
+    fn default() -> Self {
+        Self {
+            phase: FollowerPhase::Disabled,
+            paired: false,
+            leader: None,
+            desktop_name: None,
+            last_seen_at: None,
+            revision: None,
+            missed_heartbeats: FollowerConfig::default().missed_heartbeats,
+            error: None,
+        }
+    }
+}
+
+pub struct FollowerState {
+    config: Mutex<FollowerConfig>,
+    status: Mutex<FollowerStatus>,
+    task: Mutex<Option<async_runtime::JoinHandle<()>>>,
+}
+
+pub fn default_follower_state() -> FollowerState {
+    FollowerState {
+        config: Mutex::new(FollowerConfig::default()),
+        status: Mutex::new(FollowerStatus::default()),
+        task: Mutex::new(None),
+    }
+}
+
+/// The leader's state as last received, and what was last replayed from it
+/// so a command the UI has not caught up with is not sent again on every
+/// patch.
+#[derive(Default)]
+struct Mirror {
+    state: Option<Value>,
+    revision: u64,
+    applied: Option<LiveSnapshot>,
+    last_seek: Option<Instant>,
+}
+
+/// Why a connection to the leader ended.
+enum LinkError {
+    /// Worth retrying, such as the leader being unreachable.
+    Retry(String),
+    /// The leader refused this follower; it has to pair again.
+    Rejected(String),
+}
+
+/// Loads `config/follower.json` and starts following when enabled.
+pub fn start(app: &AppHandle) {
+    let config = match load_follower_config() {
+        Ok(config) => config,
+        Err(error) => {
+            eprintln!("Failed to load follower config: {}", error);
+            FollowerConfig::default()
+        }
+    };
+    if let Ok(mut current) = app.state::<FollowerState>().config.lock() {
+        *current = config.clone();
+    }
+    restart(app, &config);
+}
+
+#[tauri::command]
+pub fn get_follower_status(state: State<'_, FollowerState>) -> Result<FollowerStatus, String> {
+    state
+        .status
+        .lock()
+        .map(|status| status.clone())
+        .map_err(|e| e.to_string())
+}
+
+/// Pairs with a leader from its pairing code and starts following it.
+/// Pairing again with another leader replaces the previous one.
+#[tauri::command]
+pub async fn pair_follower(
+    app: AppHandle,
+    state: State<'_, FollowerState>,
+    code: String,
+) -> Result<FollowerStatus, String> {
+    let code: PairingCode = serde_json::from_str(code.trim())
+        .map_err(|_| "The pairing code is not valid".to_string())?;
+    if code.tls && code.ca_fingerprint.is_none() {
+        return Err("The pairing code has no CA fingerprint".to_string());
+    }
+
+    let previous = state.config.lock().map_err(|e| e.to_string())?.clone();
+    let device_id = previous
+        .leader
+        .as_ref()
+        .map(|leader| leader.device_id.clone())
+        .unwrap_or_else(|| format!("lumen-follower-{}", Uuid::new_v4().simple()));
+    let next = FollowerConfig {
+        enabled: true,
+        leader: Some(LeaderLink {
+            host: code.ip,
+            port: code.port,
+            tls: code.tls,
+            ca_fingerprint: code.ca_fingerprint.map(|value| value.to_uppercase()),
+            device_id,
+            pairing_token: Some(code.token),
+            access_token: None,
+            desktop_name: None,
+        }),
+        ..previous
+    };
+
+    stop(&app).await?;
+    store_config(&app, &next)?;
+    restart(&app, &next);
+    get_follower_status(state)
+}
+
+/// Pauses or resumes following the paired leader.
+#[tauri::command]
+pub async fn set_follower_enabled(
+    app: AppHandle,
+    state: State<'_, FollowerState>,
+    enabled: bool,
+) -> Result<FollowerStatus, String> {
+    let mut next = state.config.lock().map_err(|e| e.to_string())?.clone();
+    if enabled && next.leader.is_none() {
+        return Err("Pair with a leader first".to_string());
+    }
+    next.enabled = enabled;
+
+    stop(&app).await?;
+    store_config(&app, &next)?;
+    restart(&app, &next);
+    get_follower_status(state)
+}
+
+/// Sets how many heartbeats the leader may miss before takeover is offered.
+#[tauri::command]
+pub fn set_follower_missed_heartbeats(
+    app: AppHandle,
+    state: State<'_, FollowerState>,
+    missed_heartbeats: u32,
+) -> Result<FollowerStatus, String> {
+    if !(2..=60).contains(&missed_heartbeats) {
+        return Err("Missed heartbeats must be between 2 and 60".to_string());
+    }
+    let mut next = state.config.lock().map_err(|e| e.to_string())?.clone();
+    next.missed_heartbeats = missed_heartbeats;
+    store_config(&app, &next)?;
+    update_status(&app, |status| status.missed_heartbeats = missed_heartbeats);
+    get_follower_status(state)
+}
+
+/// Stops following and forgets the leader.
+#[tauri::command]
+pub async fn unpair_follower(
+    app: AppHandle,
+    state: State<'_, FollowerState>,
+) -> Result<FollowerStatus, String> {
+    let mut next = state.config.lock().map_err(|e| e.to_string())?.clone();
+    next.enabled = false;
+    next.leader = None;
+
+    stop(&app).await?;
+    store_config(&app, &next)?;
+    restart(&app, &next);
+    get_follower_status(state)
+}
+
+/// Stops mirroring so this desktop runs the service from where the leader
+/// left off. Following stays off until it is turned on again, also across
+/// restarts.
+#[tauri::command]
+pub async fn take_over_from_leader(
+    app: AppHandle,
+    state: State<'_, FollowerState>,
+) -> Result<FollowerStatus, String> {
+    let mut next = state.config.lock().map_err(|e| e.to_string())?.clone();
+    next.enabled = false;
+
+    stop(&app).await?;
+    store_config(&app, &next)?;
+    update_status(&app, |status| {
+        status.phase = FollowerPhase::TakenOver;
+        status.error = None;
+    });
+    println!("Took over from the leader");
+    let _ = app.emit("follower_takeover", live_state::current_snapshot(&app));
+    get_follower_status(state)
+}
+
+async fn stop(app: &AppHandle) -> Result<(), String> {
+    let task = app
+        .state::<FollowerState>()
+        .task
+        .lock()
+        .map_err(|e| e.to_string())?
+        .take();
+    if let Some(task) = task {
+        task.abort();
+        let _ = task.await;
+    }
+    Ok(())
+}
+
+/// Resets the status for `config` and starts the link task when following
+/// is enabled.
+fn restart(app: &AppHandle, config: &FollowerConfig) {
+    let follow = config.enabled && config.leader.is_some();
+    update_status(app, |status| {
+        *status = FollowerStatus {
+            phase: if follow {
+                FollowerPhase::Connecting
+            } else {
+                FollowerPhase::Disabled
+            },
+            paired: config.leader.is_some(),
+            leader: config
+                .leader
+                .as_ref()
+                .map(|leader| format!("{}:{}", leader.host, leader.port)),
+            desktop_name: config
+                .leader
+                .as_ref()
+                .and_then(|leader| leader.desktop_name.clone()),
+            missed_heartbeats: config.missed_heartbeats,
+            ..FollowerStatus::default()
+        };
+    });
+    if !follow {
+        return;
+    }
+
+    let task = async_runtime::spawn(run(app.clone()));
+    if let Ok(mut current) = app.state::<FollowerState>().task.lock() {
+        *current = Some(task);
+    }
+}
+
+/// Connects to the leader and reconnects until stopped or rejected.
+async fn run(app: AppHandle) {
+    loop {
+        let result = connect(&app).await;
+        match result {
+            Ok(()) => {}
+            Err(LinkError::Retry(error)) => {
+                update_status(&app, |status| status.error = Some(error));
+            }
+            Err(LinkError::Rejected(reason)) => {
+                eprintln!("Leader rejected the follower: {}", reason);
+                reject(&app, reason);
+                return;
+            }
+        }
+        check_leader_lost(&app);
+        tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+}
+
+/// Drops the credentials the leader refused and stops following.
+fn reject(app: &AppHandle, reason: String) {
+    let state = app.state::<FollowerState>();
+    let next = match state.config.lock() {
+        Ok(config) => {
+            let mut next = config.clone();
+            next.enabled = false;
+            if let Some(leader) = next.leader.as_mut() {
+                leader.pairing_token = None;
+                leader.access_token = None;
+            }
+            next
+        }
+        Err(_) => return,
+    };
+    if let Err(error) = store_config(app, &next) {
+        eprintln!("Failed to save follower config: {}", error);
+    }
+    update_status(app, |status| {
+        status.phase = FollowerPhase::Disabled;
+        status.error = Some(format!(
+            "The leader refused this computer ({}). Pair again.",
+            reason
+        ));
+    });
+}
+
+async fn connect(app: &AppHandle) -> Result<(), LinkError> {
+    let Some(leader) = config(app).leader else {
+        return Err(LinkError::Rejected("not_registered".to_string()));
+    };
+
+    let address = (leader.host.as_str(), leader.port);
+    let tcp = tokio::time::timeout(CONNECT_TIMEOUT, TcpStream::connect(address))
+        .await
+        .map_err(|_| LinkError::Retry("The leader did not answer".to_string()))?
+        .map_err(|e| LinkError::Retry(e.to_string()))?;
+    let host = if leader.host.contains(':') {
+        format!("[{}]", leader.host)
+    } else {
+        leader.host.clone()
+    };
+
+    if !leader.tls {
+        let url = format!("ws://{}:{}", host, leader.port);
+        let (socket, _) = client_async(url, tcp)
+            .await
+            .map_err(|e| LinkError::Retry(e.to_string()))?;
+        return follow(app, socket, &leader).await;
+    }
+
+    let ca_fingerprint = leader
+        .ca_fingerprint
+        .clone()
+        .ok_or_else(|| LinkError::Rejected("missing_fingerprint".to_string()))?;
+    let server_name =
+        ServerName::try_from(leader.host.clone()).map_err(|e| LinkError::Retry(e.to_string()))?;
+    let stream =
+        TlsConnector::from(pinned_client_config(ca_fingerprint).map_err(LinkError::Retry)?)
+            .connect(server_name, tcp)
+            .await
+            .map_err(|e| LinkError::Retry(e.to_string()))?;
+    let url = format!("wss://{}:{}", host, leader.port);
+    let (socket, _) = client_async(url, stream)
+        .await
+        .map_err(|e| LinkError::Retry(e.to_string()))?;
+    follow(app, socket, &leader).await
+}
+
+/// Authenticates, then mirrors the leader's live state until the
+/// connection ends.
+async fn follow<S>(
+    app: &AppHandle,
+    mut socket: WebSocketStream<S>,
+    leader: &LeaderLink,
+) -> Result<(), LinkError>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let hello = match (&leader.access_token, &leader.pairing_token) {
+        (Some(access_token), _) => json!({
+            "event": "auth",
+            "device_id": leader.device_id,
+            "access_token": access_token,
+            "protocol_version": PROTOCOL_VERSION,
+        }),
+        (None, Some(token)) => json!({
+            "event": "register",
+            "token": token,
+            "device_id": leader.device_id,
+            "device_name": resolve_desktop_name(app),
+            "device_type": FOLLOWER_DEVICE_TYPE,
+            "os": std::env::consts::OS,
+            "version": app.package_info().version.to_string(),
+            "protocol_version": PROTOCOL_VERSION,
+        }),
+        (None, None) => return Err(LinkError::Rejected("not_registered".to_string())),
+    };
+    send(&mut socket, &hello).await?;
+
+    let mut mirror = Mirror::default();
+    let mut heartbeat = tokio::time::interval(HEARTBEAT_INTERVAL);
+    heartbeat.tick().await;
+
+    loop {
+        tokio::select! {
+            _ = heartbeat.tick() => {
+                send(&mut socket, &json!({ "event": "heartbeat" })).await?;
+                check_leader_lost(app);
+            }
+            frame = socket.next() => {
+                let message = match frame {
+                    Some(Ok(message)) => message,
+                    Some(Err(error)) => return Err(LinkError::Retry(error.to_string())),
+                    None => return Err(LinkError::Retry("The leader closed the connection".to_string())),
+                };
+                seen(app);
+
+                let text = match message {
+                    Message::Text(text) => text,
+                    Message::Close(_) => {
+                        return Err(LinkError::Retry("The leader closed the connection".to_string()));
+                    }
+                    _ => continue,
+                };
+                let Ok(payload) = serde_json::from_str::<Value>(&text) else {
+                    continue;
+                };
+
+                match payload.get("event").and_then(Value::as_str) {
+                    Some("auth_ok") => authenticated(app, &payload)?,
+                    Some("auth_fail") => {
+                        let reason = payload
+                            .get("reason")
+                            .and_then(Value::as_str)
+                            .unwrap_or("unauthorized")
+                            .to_string();
+                        // Remote access being switched off on the leader is
+                        // temporary; anything else needs a new pairing.
+                        return Err(if reason == "unauthorized" {
+                            LinkError::Retry(reason)
+                        } else {
+                            LinkError::Rejected(reason)
+                        });
+                    }
+                    Some("state_snapshot") => {
+                        mirror.revision = payload.get("revision").and_then(Value::as_u64).unwrap_or(0);
+                        mirror.state = payload.get("state").cloned();
+                    }
+                    // Revisions count every change on the leader, including
+                    // ones to sections this follower cannot see, so they may
+                    // skip; throttled changes still arrive merged. Only
+                    // stale patches are dropped.
+                    Some("state_patch") => {
+                        let next = payload.get("revision").and_then(Value::as_u64).unwrap_or(0);
+                        let (Some(state), Some(patch)) = (mirror.state.as_mut(), payload.get("patch")) else {
+                            continue;
+                        };
+                        if next <= mirror.revision {
+                            continue;
+                        }
+                        mirror.revision = next;
+                        live_state::apply_merge_patch(state, patch);
+                    }
+                    _ => continue,
+                }
+
+                let revision = mirror.revision;
+                update_status(app, |status| status.revision = Some(revision));
+                apply_mirror(app, &mut mirror);
+            }
+        }
+    }
+}
+
+async fn send<S>(socket: &mut WebSocketStream<S>, payload: &Value) -> Result<(), LinkError>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    socket
+        .send(Message::Text(payload.to_string()))
+        .await
+        .map_err(|e| LinkError::Retry(e.to_string()))
+}
+
+/// Stores the token from `auth_ok`; with rotation on, each one replaces the
+/// last.
+fn authenticated(app: &AppHandle, payload: &Value) -> Result<(), LinkError> {
+    let state = app.state::<FollowerState>();
+    let mut next = state
+        .config
+        .lock()
+        .map_err(|e| LinkError::Retry(e.to_string()))?
+        .clone();
+    let desktop_name = payload
+        .get("desktop_name")
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    if let Some(leader) = next.leader.as_mut() {
+        if let Some(token) = payload.get("access_token").and_then(Value::as_str) {
+            leader.access_token = Some(token.to_string());
+            leader.pairing_token = None;
+        }
+        leader.desktop_name = desktop_name.clone();
+    }
+    store_config(app, &next).map_err(LinkError::Retry)?;
+
+    println!(
+        "Following {}",
+        desktop_name.as_deref().unwrap_or("the leader")
+    );
+    update_status(app, |status| {
+        status.phase = FollowerPhase::Following;
+        status.desktop_name = desktop_name;
+        status.error = None;
+    });
+    Ok(())
+}
+
+/// Replays what differs between the leader's state and this desktop as the
+/// same commands a remote device would send, so the UI, outputs and this
+/// desktop's own remote clients all follow. After the first snapshot only
+/// fields the leader changed are replayed.
+fn apply_mirror(app: &AppHandle, mirror: &mut Mirror) {
+    let Some(leader) = mirror
+        .state
+        .as_ref()
+        .and_then(|state| serde_json::from_value::<LiveSnapshot>(state.clone()).ok())
+    else {
+        return;
+    };
+    let local = live_state::current_snapshot(app).unwrap_or_default();
+    let first = mirror.applied.is_none();
+    let previous = mirror.applied.take().unwrap_or_default();
+
+    let mut commands = Vec::new();
+    let leader_url = leader.media.url.as_deref().map(localize_path);
+    let media_changed = first || leader.media.url != previous.media.url;
+    if media_changed && leader_url.is_some() && leader_url != local.media.url {
+        commands.push(ClientMessage::LoadUrl {
+            url: leader_url.unwrap_or_default(),
+            value: Some(leader.playback.position),
+        });
+    } else {
+        if (first || leader.playback.is_playing != previous.playback.is_playing)
+            && leader.playback.is_playing != local.playback.is_playing
+        {
+            commands.push(ClientMessage::PlayPause);
+        }
+        let settled = mirror
+            .last_seek
+            .is_none_or(|at| at.elapsed() >= SEEK_COOLDOWN);
+        if settled && (leader.playback.position - local.playback.position).abs() > SEEK_DRIFT_SECS {
+            mirror.last_seek = Some(Instant::now());
+            commands.push(ClientMessage::Seek {
+                value: leader.playback.position,
+            });
+        }
+    }
+    if (first || leader.audio.volume != previous.audio.volume)
+        && leader.audio.volume != local.audio.volume
+    {
+        commands.push(ClientMessage::SetVolume {
+            value: f64::from(leader.audio.volume),
+        });
+    }
+    if (first || leader.audio.is_muted != previous.audio.is_muted)
+        && leader.audio.is_muted != local.audio.is_muted
+    {
+        commands.push(ClientMessage::Mute);
+    }
+    if (first || leader.audio.is_loop != previous.audio.is_loop)
+        && leader.audio.is_loop != local.audio.is_loop
+    {
+        commands.push(ClientMessage::SetLoop {
+            value: Some(if leader.audio.is_loop { 1.0 } else { 0.0 }),
+        });
+    }
+
+    let lyric_url = leader.lyric.url.as_deref().map(localize_path);
+    let lyric_changed = (first || leader.lyric.url != previous.lyric.url)
+        && leader.lyric.active
+        && lyric_url.is_some()
+        && lyric_url != local.lyric.url;
+    if lyric_changed {
+        commands.push(ClientMessage::LoadLyric {
+            url: lyric_url.unwrap_or_default(),
+        });
+    }
+    let slide_changed = first
+        || lyric_changed
+        || leader.lyric.slide_index != previous.lyric.slide_index
+        || leader.lyric.active != previous.lyric.active;
+    if leader.lyric.active && slide_changed {
+        commands.push(ClientMessage::LyricsSlide {
+            index: leader.lyric.slide_index,
+        });
+    }
+
+    for command in &commands {
+        if let Err(error) = run_control_message(app, MIRROR_SOURCE, command) {
+            eprintln!(
+                "Failed to mirror {}: {}",
+                command.event_name(),
+                error.message
+            );
+        }
+    }
+
+    if first || leader.queue.paths != previous.queue.paths {
+        let paths = leader
+            .queue
+            .paths
+            .iter()
+            .map(|path| localize_path(path))
+            .collect::<Vec<_>>();
+        let _ = app.emit(
+            "follower-queue",
+            json!({ "paths": paths, "position": leader.queue.position }),
+        );
+    }
+
+    mirror.applied = Some(leader);
+}
+
+/// Maps a path on the leader to the same file on this desktop. Files kept
+/// under the leader's `lumen/` folder are looked up under this one's.
+fn localize_path(path: &str) -> String {
+    if Path::new(path).exists() {
+        return path.to_string();
+    }
+    let normalized = path.replace('\\', "/");
+    let Some((_, relative)) = normalized.split_once("/lumen/") else {
+        return path.to_string();
+    };
+    match app_base_dir() {
+        Ok(base) => {
+            let local = base.join(relative);
+            if local.exists() {
+                local.to_string_lossy().into_owned()
+            } else {
+                path.to_string()
+            }
+        }
+        Err(_) => path.to_string(),
+    }
+}
+
+fn seen(app: &AppHandle) {
+    update_status(app, |status| {
+        status.last_seen_at = Some(now_ts());
+        if status.phase == FollowerPhase::LeaderLost {
+            status.phase = FollowerPhase::Following;
+        }
+    });
+}
+
+/// Marks the leader lost once nothing arrived for `missed_heartbeats`
+/// heartbeat intervals.
+fn check_leader_lost(app: &AppHandle) {
+    let Ok(status) = app
+        .state::<FollowerState>()
+        .status
+        .lock()
+        .map(|s| s.clone())
+    else {
+        return;
+    };
+    let Some(last_seen_at) = status.last_seen_at else {
+        return;
+    };
+    let limit = HEARTBEAT_INTERVAL.as_secs() * u64::from(status.missed_heartbeats);
+    let lost = now_ts().saturating_sub(last_seen_at) > limit;
+    if lost
+        && matches!(
+            status.phase,
+            FollowerPhase::Following | FollowerPhase::Connecting
+        )
+    {
+        eprintln!("Leader missed {} heartbeats", status.missed_heartbeats);
+        update_status(app, |status| status.phase = FollowerPhase::LeaderLost);
+    }
+}
+
+fn config(app: &AppHandle) -> FollowerConfig {
+    app.state::<FollowerState>()
+        .config
+        .lock()
+        .map(|config| config.clone())
+        .unwrap_or_default()
+}
+
+fn store_config(app: &AppHandle, config: &FollowerConfig) -> Result<(), String> {
+    save_follower_config(config)?;
+    *app.state::<FollowerState>()
+        .config
+        .lock()
+        .map_err(|e| e.to_string())? = config.clone();
+    Ok(())
+}
+
+fn update_status(app: &AppHandle, apply: impl FnOnce(&mut FollowerStatus)) {
+    let status = {
+        let state = app.state::<FollowerState>();
+        let Ok(mut status) = state.status.lock() else {
+            return;
+        };
+        let before = status.clone();
+        apply(&mut status);
+        // `last_seen_at` changes with every frame; only tell the UI about
+        // changes it shows.
+        if status.phase == before.phase
+            && status.error == before.error
+            && status.desktop_name == before.desktop_name
+            && status.missed_heartbeats == before.missed_heartbeats
+            && status.paired == before.paired
+        {
+            return;
+        }
+        status.clone()
+    };
+    let _ = app.emit("follower_status", status);
+}
+
+/// Accepts only server certificates issued by the leader CA pinned at
+/// pairing. The leader sends its CA after the server certificate.
+#[derive(Debug)]
+struct PinnedCertVerifier {
+    ca_fingerprint: String,
+    provider: Arc<CryptoProvider>,
+}
+
+impl ServerCertVerifier for PinnedCertVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let ca = intermediates
+            .iter()
+            .find(|cert| sha256_fingerprint(cert.as_ref()) == self.ca_fingerprint)
+            .ok_or_else(|| {
+                rustls::Error::General(
+                    "The leader's certificate authority does not match the pairing code"
+                        .to_string(),
+                )
+            })?;
+        let mut roots = RootCertStore::empty();
+        roots.add(ca.clone())?;
+
+        let cert = ParsedCertificate::try_from(end_entity)?;
+        verify_server_cert_signed_by_trust_anchor(
+            &cert,
+            &roots,
+            &[],
+            now,
+            self.provider.signature_verification_algorithms.all,
+        )?;
+        verify_server_name(&cert, server_name)?;
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider
+            .signature_verification_algorithms
+            .supported_schemes()
+    }
+}
+
+fn pinned_client_config(ca_fingerprint: String) -> Result<Arc<ClientConfig>, String> {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let config = ClientConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .map_err(|e| e.to_string())?
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(PinnedCertVerifier {
+            ca_fingerprint,
+            provider,
+        }))
+        .with_no_client_auth();
+    Ok(Arc::new(config))
+}
+
+fn follower_config_path() -> Result<PathBuf, String> {
+    Ok(app_base_dir()?.join("config").join("follower.json"))
+}
+
+fn load_follower_config() -> Result<FollowerConfig, String> {
+    let path = follower_config_path()?;
+    if !path.exists() {
+        return Ok(FollowerConfig::default());
+    }
+
+    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&content).map_err(|e| e.to_string())
+}
+
+fn save_follower_config(config: &FollowerConfig) -> Result<(), String> {
+    let path = follower_config_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    // The file holds the device's access token on the leader.
+    let content = serde_json::to_string_pretty(config).map_err(|e| e.to_string())?;
+    write_private_file(&path, &content)
+}
@@ -0,0 +1,52 @@
+use tauri::{AppHandle, Manager, State, WebviewWindow};
+use tokio_tungstenite::tungstenite::http::Uri;
+
+use crate::device_tokens::{generate_token, same_secret};
+
+/// Query parameter the desktop's own windows add to the control socket URL.
+const SECRET_PARAM: &str = "internal";
+
+/// Windows that load Lumen's own pages. Module overlay windows pick their
+/// own labels and never get the secret.
+const INTERNAL_WINDOWS: &[&str] = &["main", crate::MEDIA_WINDOW_LABEL];
+
+/// Proves a loopback connection comes from one of Lumen's own windows.
+/// The secret is made fresh on every launch, never written to disk, and only
+/// handed out through `internal_socket_secret`.
+pub struct InternalAuthState {
+    secret: String,
+}
+
+pub fn default_internal_auth_state() -> InternalAuthState {
+    InternalAuthState {
+        secret: generate_token(),
+    }
+}
+
+/// Hands the secret to the main and media windows so they can connect to
+/// the control socket as internal clients. Any other window is refused.
+#[tauri::command]
+pub fn internal_socket_secret(
+    window: WebviewWindow,
+    state: State<'_, InternalAuthState>,
+) -> Result<String, String> {
+    if !INTERNAL_WINDOWS.contains(&window.label()) {
+        return Err(format!("Window {} is not a Lumen window", window.label()));
+    }
+    Ok(state.secret.clone())
+}
+
+/// The secret a client put in the handshake URL, if any.
+pub fn presented_secret(uri: &Uri) -> Option<String> {
+    let query = uri.query()?;
+    url::form_urlencoded::parse(query.as_bytes())
+        .find(|(key, _)| key == SECRET_PARAM)
+        .map(|(_, value)| value.into_owned())
+}
+
+pub fn is_internal(app: &AppHandle, presented: Option<&str>) -> bool {
+    let Some(presented) = presented else {
+        return false;
+    };
+    same_secret(presented, &app.state::<InternalAuthState>().secret)
+}
@@ -0,0 +1,655 @@
+use std::{sync::Mutex, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+use tauri::{AppHandle, Manager, async_runtime};
+use tokio::sync::mpsc::UnboundedSender;
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::devices::{DeviceState, lyric_slide_message, now_ts};
+use crate::osc;
+use crate::permissions::{DevicePermissions, can_receive};
+use crate::protocol::ClientMessage;
+use crate::streaming::{SlideUpdate, StreamingStatus};
+use crate::topics::Topic;
+
+/// What a remote device needs to render the desktop without waiting for the
+/// next change: current media, playback, audio settings, lyric position,
+/// queue and live streams.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LiveSnapshot {
+    pub media: MediaState,
+    pub playback: PlaybackState,
+    pub audio: AudioState,
+    pub lyric: LyricState,
+    pub queue: QueueState,
+    pub streams: StreamingStatus,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MediaState {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub artist: Option<String>,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub media_type: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PlaybackState {
+    pub is_playing: bool,
+    pub position: f64,
+    pub duration: f64,
+    /// Unix seconds when `position` was sampled, so clients can extrapolate.
+    pub updated_at: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudioState {
+    pub volume: u8,
+    pub is_muted: bool,
+    pub is_loop: bool,
+}
+
+impl Default for AudioState {
+    fn default() -> Self {
+        Self {
+            volume: 100,
+            is_muted: false,
+            is_loop: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LyricState {
+    pub active: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    pub slide_index: usize,
+    pub total_slides: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QueueState {
+    /// Paths of the queued items, in play order.
+    pub paths: Vec<String>,
+    /// Index in `paths` of the item playing now.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub position: Option<usize>,
+}
+
+pub struct LiveState {
+    inner: Mutex<LiveStateInner>,
+}
+
+struct LiveStateInner {
+    snapshot: LiveSnapshot,
+    revision: u64,
+    /// Last slide pushed to outputs, replayed to devices that connect
+    /// mid-song in their own lyric language.
+    latest_slide: Option<SlideUpdate>,
+}
+
+pub fn default_live_state() -> LiveState {
+    LiveState {
+        inner: Mutex::new(LiveStateInner {
+            snapshot: LiveSnapshot::default(),
+            revision: 0,
+            latest_slide: None,
+        }),
+    }
+}
+
+/// Shape of the `player_sync` envelope the desktop broadcasts.
+#[derive(Debug, Default, Deserialize)]
+struct PlayerSync {
+    #[serde(default)]
+    media: Option<MediaState>,
+    #[serde(default)]
+    playback: Option<PlayerSyncPlayback>,
+    #[serde(default)]
+    state: Option<AudioState>,
+    #[serde(default)]
+    lyric: Option<PlayerSyncLyric>,
+    #[serde(default)]
+    queue: Option<QueueState>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PlayerSyncPlayback {
+    is_playing: bool,
+    position: f64,
+    duration: f64,
+}
+
+#[derive(Debug, Deserialize)]
+struct PlayerSyncLyric {
+    active: bool,
+    #[serde(default)]
+    url: Option<String>,
+    #[serde(default)]
+    slide_index: Option<usize>,
+    #[serde(default)]
+    total_slides: Option<usize>,
+}
+
+/// Applies a `player_sync` broadcast from the desktop UI.
+pub fn apply_player_sync(app: &AppHandle, payload: &Value) {
+    let Ok(sync) = serde_json::from_value::<PlayerSync>(payload.clone()) else {
+        return;
+    };
+
+    update(app, |snapshot| {
+        if let Some(media) = sync.media {
+            snapshot.media = media;
+        }
+        if let Some(playback) = sync.playback {
+            snapshot.playback = PlaybackState {
+                is_playing: playback.is_playing,
+                position: playback.position,
+                duration: playback.duration,
+                updated_at: now_ts(),
+            };
+        }
+        if let Some(audio) = sync.state {
+            snapshot.audio = audio;
+        }
+        if let Some(lyric) = sync.lyric {
+            snapshot.lyric.active = lyric.active;
+            snapshot.lyric.url = lyric.url;
+            if let Some(index) = lyric.slide_index {
+                snapshot.lyric.slide_index = index;
+            }
+            if let Some(total) = lyric.total_slides {
+                snapshot.lyric.total_slides = total;
+            }
+        }
+        if let Some(queue) = sync.queue {
+            snapshot.queue = queue;
+        }
+    });
+}
+
+/// Applies a control command as it is emitted to the desktop UI.
+pub fn apply_control_message(app: &AppHandle, message: &ClientMessage) {
+    update(app, |snapshot| match message {
+        ClientMessage::SetVolume { value } => {
+            snapshot.audio.volume = value.clamp(0.0, 100.0) as u8;
+        }
+        ClientMessage::Seek { value } => {
+            snapshot.playback.position = *value;
+            snapshot.playback.updated_at = now_ts();
+        }
+        ClientMessage::Progress { value, duration } => {
+            snapshot.playback.position = *value;
+            if let Some(duration) = duration {
+                snapshot.playback.duration = *duration;
+            }
+            snapshot.playback.updated_at = now_ts();
+        }
+        ClientMessage::Metadata { title, url, artist } => {
+            snapshot.media.title = title.clone();
+            snapshot.media.artist = artist.clone();
+            if url.is_some() {
+                snapshot.media.url = url.clone();
+            }
+        }
+        ClientMessage::Mute => snapshot.audio.is_muted = !snapshot.audio.is_muted,
+        ClientMessage::PlayPause => snapshot.playback.is_playing = !snapshot.playback.is_playing,
+        ClientMessage::Stop => {
+            snapshot.playback.is_playing = false;
+            snapshot.playback.position = 0.0;
+            snapshot.playback.updated_at = now_ts();
+        }
+        ClientMessage::LoadUrl { url, value } => {
+            snapshot.media = MediaState {
+                url: Some(url.clone()),
+                ..MediaState::default()
+            };
+            snapshot.playback = PlaybackState {
+                is_playing: true,
+                position: value.unwrap_or(0.0),
+                duration: 0.0,
+                updated_at: now_ts(),
+            };
+            snapshot.lyric.active = false;
+        }
+        ClientMessage::LoadLyric { url } => {
+            snapshot.lyric = LyricState {
+                active: true,
+                url: Some(url.clone()),
+                slide_index: 0,
+                total_slides: 0,
+            };
+        }
+        ClientMessage::LyricsSlide { index } => {
+            snapshot.lyric.slide_index = match snapshot.lyric.total_slides {
+                0 => *index,
+                total => (*index).min(total - 1),
+            };
+        }
+        ClientMessage::SetLoop { value } => {
+            snapshot.audio.is_loop = value.map(|value| value != 0.0).unwrap_or(false);
+        }
+        _ => {}
+    });
+}
+
+/// Records the slide just pushed to outputs.
+pub fn apply_slide(app: &AppHandle, slide: &SlideUpdate) {
+    if let Ok(mut inner) = app.state::<LiveState>().inner.lock() {
+        inner.latest_slide = slide.active.then(|| slide.clone());
+    }
+
+    update(app, |snapshot| {
+        snapshot.lyric.active = slide.active;
+        snapshot.lyric.slide_index = slide.slide_index;
+        snapshot.lyric.total_slides = slide.total_slides;
+    });
+}
+
+pub fn apply_blank(app: &AppHandle) {
+    if let Ok(mut inner) = app.state::<LiveState>().inner.lock() {
+        inner.latest_slide = None;
+    }
+
+    update(app, |snapshot| snapshot.lyric.active = false);
+}
+
+pub fn apply_streams(app: &AppHandle, streams: StreamingStatus) {
+    update(app, |snapshot| snapshot.streams = streams);
+}
+
+pub fn current_snapshot(app: &AppHandle) -> Option<LiveSnapshot> {
+    let state = app.try_state::<LiveState>()?;
+    let inner = state.inner.lock().ok()?;
+    Some(inner.snapshot.clone())
+}
+
+/// Sends `state_snapshot` to one session, filtered by its permissions,
+/// followed by the current lyric slide when one is showing.
+pub fn send_snapshot(
+    app: &AppHandle,
+    sender: &UnboundedSender<Message>,
+    permissions: &DevicePermissions,
+    lyrics_language: &str,
+) -> Result<u64, String> {
+    let state = app.state::<LiveState>();
+    // Held while sending, so no patch with a later revision overtakes it.
+    let inner = state.inner.lock().map_err(|e| e.to_string())?;
+    let (revision, state, slide) = filter_inner(&inner, permissions)?;
+
+    let message = json!({
+        "event": "state_snapshot",
+        "revision": revision,
+        "state": state,
+    });
+    let _ = sender.send(Message::Text(message.to_string()));
+
+    if let Some(slide) = slide {
+        let _ = sender.send(lyric_slide_message(&slide, lyrics_language)?);
+    }
+
+    Ok(revision)
+}
+
+/// The revision and state a device with `permissions` may see, plus the
+/// current lyric slide when it may receive lyrics.
+pub fn filtered_snapshot(
+    app: &AppHandle,
+    permissions: &DevicePermissions,
+) -> Result<(u64, Value, Option<SlideUpdate>), String> {
+    let state = app.state::<LiveState>();
+    let inner = state.inner.lock().map_err(|e| e.to_string())?;
+    filter_inner(&inner, permissions)
+}
+
+fn filter_inner(
+    inner: &LiveStateInner,
+    permissions: &DevicePermissions,
+) -> Result<(u64, Value, Option<SlideUpdate>), String> {
+    let snapshot = serde_json::to_value(&inner.snapshot).map_err(|e| e.to_string())?;
+    Ok((
+        inner.revision,
+        filter_sections(&snapshot, permissions),
+        inner
+            .latest_slide
+            .clone()
+            .filter(|_| can_receive(permissions, "lyrics")),
+    ))
+}
+
+fn update(app: &AppHandle, apply: impl FnOnce(&mut LiveSnapshot)) {
+    let Some(state) = app.try_state::<LiveState>() else {
+        return;
+    };
+
+    let after = {
+        let Ok(mut inner) = state.inner.lock() else {
+            return;
+        };
+        let before = serde_json::to_value(&inner.snapshot).unwrap_or_default();
+        apply(&mut inner.snapshot);
+        let after = serde_json::to_value(&inner.snapshot).unwrap_or_default();
+
+        let Some(patch) = merge_diff(&before, &after) else {
+            return;
+        };
+        inner.revision += 1;
+        // Sent before the lock is released, so concurrent updates reach every
+        // session in revision order.
+        broadcast_patch(app, &patch, inner.revision);
+        after
+    };
+
+    osc::send_feedback(app, &after);
+}
+
+fn broadcast_patch(app: &AppHandle, patch: &Value, revision: u64) {
+    let devices = app.state::<DeviceState>();
+    let Ok(mut sessions) = devices.sessions.lock() else {
+        return;
+    };
+
+    for session in sessions.values_mut() {
+        let Some(sender) = session.sender.clone() else {
+            continue;
+        };
+        let Value::Object(sections) = filter_sections(patch, &session.permissions) else {
+            continue;
+        };
+
+        let mut admitted = Map::new();
+        for (section, value) in sections {
+            let topic = section_topic(&section, &value);
+            if session.subscriptions.admit(topic) {
+                admitted.insert(section, value);
+            } else if session.subscriptions.is_subscribed(topic) {
+                let delay = session
+                    .subscriptions
+                    .hold(topic, &section, &value, revision);
+                if let Some(delay) = delay {
+                    schedule_held_patch(app, &session.session_id, delay);
+                }
+            }
+        }
+        if admitted.is_empty() {
+            continue;
+        }
+
+        let admitted = session.subscriptions.release_into(admitted);
+        send_patch(&sender, admitted, revision);
+    }
+}
+
+/// Sends a session's throttled patch sections once its throttle ends,
+/// unless a later patch already carried them.
+fn schedule_held_patch(app: &AppHandle, session_id: &str, delay: Duration) {
+    let app = app.clone();
+    let session_id = session_id.to_string();
+    async_runtime::spawn(async move {
+        tokio::time::sleep(delay).await;
+
+        let devices = app.state::<DeviceState>();
+        let Ok(mut sessions) = devices.sessions.lock() else {
+            return;
+        };
+        let Some(session) = sessions.get_mut(&session_id) else {
+            return;
+        };
+        let Some(sender) = session.sender.clone() else {
+            return;
+        };
+        if let Some((sections, revision)) = session.subscriptions.take_held() {
+            send_patch(&sender, sections, revision);
+        }
+    });
+}
+
+fn send_patch(sender: &UnboundedSender<Message>, sections: Map<String, Value>, revision: u64) {
+    let message = json!({
+        "event": "state_patch",
+        "revision": revision,
+        "patch": sections,
+    });
+    let _ = sender.send(Message::Text(message.to_string()));
+}
+
+/// Drops the top-level sections a device is not allowed to see.
+fn filter_sections(state: &Value, permissions: &DevicePermissions) -> Value {
+    let Some(object) = state.as_object() else {
+        return state.clone();
+    };
+
+    let filtered = object
+        .iter()
+        .filter(|(section, _)| {
+            let area = match section.as_str() {
+                "media" | "playback" | "audio" => "player",
+                "lyric" => "lyrics",
+                "queue" => "media",
+                "streams" => "streaming",
+                _ => return true,
+            };
+            can_receive(permissions, area)
+        })
+        .map(|(section, value)| (section.clone(), value.clone()))
+        .collect::<Map<_, _>>();
+
+    Value::Object(filtered)
+}
+
+/// Topic a patched section is delivered under. A playback change that only
+/// moves the position counts as a progress tick.
+fn section_topic(section: &str, value: &Value) -> Topic {
+    match section {
+        "playback" => {
+            let only_position = value.as_object().is_some_and(|fields| {
+                fields
+                    .keys()
+                    .all(|key| key == "position" || key == "updated_at")
+            });
+            if only_position {
+                Topic::PlayerProgress
+            } else {
+                Topic::Player
+            }
+        }
+        "lyric" => Topic::Lyrics,
+        "queue" => Topic::Queue,
+        "streams" => Topic::Streaming,
+        _ => Topic::Player,
+    }
+}
+
+/// Applies a JSON Merge Patch (RFC 7396) to `target`, as a client holding a
+/// `state_snapshot` does with each `state_patch`.
+pub fn apply_merge_patch(target: &mut Value, patch: &Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    let Value::Object(fields) = target else {
+        return;
+    };
+    for (key, value) in patch {
+        if value.is_null() {
+            fields.remove(key);
+        } else {
+            apply_merge_patch(fields.entry(key.clone()).or_insert(Value::Null), value);
+        }
+    }
+}
+
+/// Folds the merge patch `next` into `patch`, so applying the result equals
+/// applying `patch` and then `next`. Unlike `apply_merge_patch`, `null`
+/// removals are kept.
+pub fn compose_patches(patch: &mut Value, next: &Value) {
+    let (Value::Object(fields), Value::Object(next_fields)) = (&mut *patch, next) else {
+        *patch = next.clone();
+        return;
+    };
+    for (key, value) in next_fields {
+        match fields.get_mut(key) {
+            Some(existing) if existing.is_object() && value.is_object() => {
+                compose_patches(existing, value);
+            }
+            _ => {
+                fields.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+/// JSON Merge Patch (RFC 7396) turning `before` into `after`, or `None` when
+/// nothing changed. Removed keys become `null`.
+fn merge_diff(before: &Value, after: &Value) -> Option<Value> {
+    match (before, after) {
+        (Value::Object(before), Value::Object(after)) => {
+            let mut patch = Map::new();
+            for (key, value) in after {
+                match before.get(key) {
+                    Some(previous) => {
+                        if let Some(diff) = merge_diff(previous, value) {
+                            patch.insert(key.clone(), diff);
+                        }
+                    }
+                    None => {
+                        patch.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+            for key in before.keys() {
+                if !after.contains_key(key) {
+                    patch.insert(key.clone(), Value::Null);
+                }
+            }
+            (!patch.is_empty()).then_some(Value::Object(patch))
+        }
+        _ if before == after => None,
+        _ => Some(after.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_diff_reports_changed_added_and_removed_keys() {
+        let before = json!({
+            "audio": { "volume": 80, "is_muted": false },
+            "media": { "url": "a.mp4", "title": "A" },
+            "queue": { "paths": ["a.mp4"] },
+        });
+        let after = json!({
+            "audio": { "volume": 50, "is_muted": false },
+            "media": { "url": "b.mp4" },
+            "queue": { "paths": ["a.mp4"] },
+            "lyric": { "active": true },
+        });
+
+        assert_eq!(
+            merge_diff(&before, &after),
+            Some(json!({
+                "audio": { "volume": 50 },
+                "media": { "url": "b.mp4", "title": null },
+                "lyric": { "active": true },
+            }))
+        );
+    }
+
+    #[test]
+    fn merge_diff_is_none_when_nothing_changed() {
+        let state = json!({ "audio": { "volume": 80 }, "queue": { "paths": [] } });
+        assert_eq!(merge_diff(&state, &state.clone()), None);
+    }
+
+    #[test]
+    fn merge_diff_replaces_arrays_and_scalars_whole() {
+        let before = json!({ "queue": { "paths": ["a", "b"] }, "revision": 1 });
+        let after = json!({ "queue": { "paths": ["b"] }, "revision": "2" });
+
+        assert_eq!(
+            merge_diff(&before, &after),
+            Some(json!({ "queue": { "paths": ["b"] }, "revision": "2" }))
+        );
+    }
+
+    #[test]
+    fn apply_merge_patch_follows_rfc_7396() {
+        let mut target = json!({ "a": "b", "c": { "d": "e", "f": "g" } });
+        apply_merge_patch(&mut target, &json!({ "a": "z", "c": { "f": null } }));
+        assert_eq!(target, json!({ "a": "z", "c": { "d": "e" } }));
+
+        let mut target = json!({ "a": ["b"] });
+        apply_merge_patch(&mut target, &json!({ "a": { "b": "c" } }));
+        assert_eq!(target, json!({ "a": { "b": "c" } }));
+
+        let mut target = json!(["a", "b"]);
+        apply_merge_patch(&mut target, &json!({ "a": "b", "c": null }));
+        assert_eq!(target, json!({ "a": "b" }));
+
+        let mut target = json!({ "a": "foo" });
+        apply_merge_patch(&mut target, &json!("bar"));
+        assert_eq!(target, json!("bar"));
+    }
+
+    #[test]
+    fn composed_patches_apply_like_the_patches_in_turn() {
+        let state = json!({
+            "media": { "url": "a.mp4", "title": "A" },
+            "playback": { "position": 1.0, "updated_at": 10 },
+        });
+        let first = json!({ "playback": { "position": 2.0, "updated_at": 11 } });
+        let second = json!({
+            "media": { "title": null },
+            "playback": { "position": 3.0 },
+        });
+
+        let mut in_turn = state.clone();
+        apply_merge_patch(&mut in_turn, &first);
+        apply_merge_patch(&mut in_turn, &second);
+
+        let mut composed = first.clone();
+        compose_patches(&mut composed, &second);
+        assert_eq!(
+            composed,
+            json!({
+                "media": { "title": null },
+                "playback": { "position": 3.0, "updated_at": 11 },
+            })
+        );
+
+        let mut at_once = state;
+        apply_merge_patch(&mut at_once, &composed);
+        assert_eq!(at_once, in_turn);
+    }
+
+    #[test]
+    fn applying_the_diff_reproduces_the_new_state() {
+        let before = json!({
+            "media": { "url": "a.mp4", "title": "A", "artist": "X" },
+            "playback": { "is_playing": false, "position": 12.5 },
+            "queue": { "paths": ["a.mp4", "b.mp4"], "position": 0 },
+        });
+        let after = json!({
+            "media": { "url": "b.mp4" },
+            "playback": { "is_playing": true, "position": 0.0 },
+            "queue": { "paths": ["a.mp4", "b.mp4"], "position": 1 },
+            "lyric": { "active": false, "slide_index": 0 },
+        });
+
+        let patch = merge_diff(&before, &after).unwrap();
+        let mut state = before.clone();
+        apply_merge_patch(&mut state, &patch);
+        assert_eq!(state, after);
+    }
+}
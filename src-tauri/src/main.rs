@@ -1,10 +1,34 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod audience;
+mod audit;
+mod bible;
+mod control_limits;
+mod control_lock;
+mod control_server;
+mod device_tokens;
+mod device_transfer;
 mod devices;
+mod discovery;
+mod follower;
+mod internal_auth;
+mod live_state;
+mod media_library;
+mod media_upload;
 mod module_runtime;
+mod network;
+mod osc;
+mod pairing;
+mod permissions;
+mod presence;
 mod presentation;
+mod protocol;
+mod remote_commands;
+mod rest_api;
 mod streaming;
 mod thumbnail;
+mod tls;
+mod topics;
 mod websocket;
 
 use module_runtime::{ModuleRuntime, dev_server::start_dev_server, protocol::handle_module_request};
@@ -16,7 +40,6 @@ use tauri::menu::{MenuBuilder, MenuItemBuilder};
 use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
 use tauri_plugin_dialog::{DialogExt, MessageDialogButtons};
 use tauri_plugin_window_state::{AppHandleExt, StateFlags};
-use tokio::net::TcpListener;
 
 struct WindowState {
     positions: Mutex<HashMap<String, (i32, i32)>>,
@@ -308,7 +331,22 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .manage(WindowState {
             positions: Mutex::new(HashMap::new()),
         })
+        .manage(audit::default_audit_state())
         .manage(devices::default_device_state())
+        .manage(discovery::default_discovery_state())
+        .manage(tls::default_tls_state())
+        .manage(remote_commands::default_remote_command_state())
+        .manage(live_state::default_live_state())
+        .manage(control_limits::default_connection_limit_state())
+        .manage(control_lock::default_control_lock_state())
+        .manage(control_server::default_control_server_state())
+        .manage(permissions::default_permission_state())
+        .manage(bible::default_bible_state())
+        .manage(pairing::default_pairing_state())
+        .manage(osc::default_osc_state())
+        .manage(media_upload::default_media_upload_state())
+        .manage(follower::default_follower_state())
+        .manage(internal_auth::default_internal_auth_state())
         .plugin(tauri_plugin_single_instance::init(|app, args, cwd| {
             println!("Single instance callback:");
             println!("  args: {:?}", args);
@@ -347,24 +385,14 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             });
 
             devices::ensure_remote_access_ready(&app.handle()).map_err(|e| e.to_string())?;
+            audience::prepare_audience_storage()?;
             let streaming_state = streaming::initialize_streaming_state(&app.handle())?;
             app.manage(streaming_state);
-            let app_handle = app.handle();
-            let app_handle_clone = app_handle.clone();
-            async_runtime::spawn(async move {
-                let listener = TcpListener::bind("0.0.0.0:8080").await.unwrap();
-                while let Ok((stream, _)) = listener.accept().await {
-                    let peer = stream
-                        .peer_addr()
-                        .expect("connected streams should have a peer address");
-
-                    async_runtime::spawn(websocket::accept_connection(
-                        peer,
-                        stream,
-                        app_handle_clone.clone(),
-                    ));
-                }
-            });
+            presence::start_session_reaper(app.handle().clone());
+            control_server::start(app.handle());
+            tls::start_address_watch(app.handle());
+            osc::start(app.handle());
+            follower::start(app.handle());
 
             let show = MenuItemBuilder::with_id("show", "Show Lumen").build(app)?;
             let quit = MenuItemBuilder::with_id("quit", "Quit").build(app)?;
@@ -459,6 +487,60 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             get_system_fonts,
             get_system_info,
             devices::get_local_ip,
+            control_limits::set_control_limits,
+            control_lock::get_control_locks,
+            control_lock::take_control_lock,
+            control_lock::release_control_lock,
+            control_server::get_control_server_status,
+            control_server::set_control_server,
+            device_tokens::set_token_policy,
+            device_tokens::revoke_all_device_tokens,
+            device_transfer::export_remote_access,
+            device_transfer::preview_remote_access_import,
+            device_transfer::import_remote_access,
+            follower::get_follower_status,
+            follower::pair_follower,
+            follower::set_follower_enabled,
+            follower::set_follower_missed_heartbeats,
+            follower::unpair_follower,
+            follower::take_over_from_leader,
+            media_upload::get_upload_config,
+            media_upload::set_upload_config,
+            network::list_network_interfaces,
+            network::set_preferred_interface,
+            osc::get_osc_config,
+            osc::get_osc_status,
+            osc::set_osc_config,
+            pairing::generate_pairing_pin,
+            pairing::get_pairing_tokens,
+            pairing::cancel_pairing_token,
+            pairing::set_pairing_approval,
+            pairing::get_pairing_requests,
+            pairing::approve_pairing_request,
+            pairing::reject_pairing_request,
+            permissions::get_permission_scopes,
+            permissions::register_permission_scope,
+            permissions::get_permission_roles,
+            permissions::save_permission_role,
+            permissions::delete_permission_role,
+            presence::set_session_heartbeat,
+            internal_auth::internal_socket_secret,
+            protocol::get_remote_protocol_schema,
+            remote_commands::complete_remote_command,
+            tls::get_tls_info,
+            tls::set_remote_tls_enabled,
+            tls::regenerate_tls_certificate,
+            audience::get_audience_polls,
+            audience::open_audience_poll,
+            audience::close_audience_poll,
+            audience::get_audience_questions,
+            audience::moderate_audience_question,
+            audience::push_audience_poll_results,
+            audience::push_audience_question,
+            audit::get_audit_log,
+            audit::export_audit_log,
+            audit::set_audit_retention,
+            bible::get_bible_translations,
             devices::gen_reg_token,
             devices::get_devices,
             devices::get_remote_access_settings,
@@ -466,6 +548,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             devices::toggle_device,
             devices::update_device_permissions,
             devices::remove_device,
+            devices::set_device_lyrics_language,
             devices::broadcast_remote_event,
             streaming::manager::get_streaming_config,
             streaming::manager::update_streaming_config,
@@ -474,6 +557,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             streaming::manager::set_mobile_preview_device,
             streaming::manager::push_stream_slide,
             streaming::manager::push_stream_blank,
+            streaming::manager::get_html_outputs,
+            streaming::manager::set_html_output_language,
             set_stream_overlay,
             thumbnail::get_thumbnail,
             module_runtime::module_list_installed,
@@ -0,0 +1,219 @@
+use rusqlite::{Connection, OptionalExtension, params, params_from_iter};
+use serde::Serialize;
+
+use crate::devices::open_device_db;
+
+const DEFAULT_PAGE_SIZE: u32 = 50;
+const MAX_PAGE_SIZE: u32 = 200;
+
+/// Library types a remote device can browse, queue and show.
+const REMOTE_MEDIA_TYPES: &[&str] = &["audio", "video", "image", "lyrics", "presentation"];
+/// Types `thumbnail::get_thumbnail` can render.
+pub const THUMBNAIL_TYPES: &[&str] = &["video", "image"];
+
+/// A `media_files` row as shown to remote devices. The local path stays on
+/// the desktop; devices refer to items by `id`.
+#[derive(Debug, Clone, Serialize)]
+pub struct MediaItem {
+    pub id: i64,
+    pub name: String,
+    pub media_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub artist: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration: Option<f64>,
+    /// Path of the thumbnail on the HTML server, for types that have one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail_url: Option<String>,
+    #[serde(skip)]
+    pub path: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MediaPage {
+    pub items: Vec<MediaItem>,
+    pub total: u64,
+    pub offset: u32,
+    pub limit: u32,
+}
+
+/// Lists library items by name, optionally narrowed to one type and to
+/// names or artists containing every word of `query`.
+pub fn browse(
+    media_type: Option<&str>,
+    query: Option<&str>,
+    offset: Option<u32>,
+    limit: Option<u32>,
+) -> Result<MediaPage, String> {
+    let offset = offset.unwrap_or(0);
+    let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+
+    let types = match media_type {
+        Some(media_type) if REMOTE_MEDIA_TYPES.contains(&media_type) => vec![media_type],
+        Some(media_type) => return Err(format!("Unknown media type: {}", media_type)),
+        None => REMOTE_MEDIA_TYPES.to_vec(),
+    };
+
+    let connection = open_device_db()?;
+    if !has_media_table(&connection)? {
+        return Ok(MediaPage {
+            items: Vec::new(),
+            total: 0,
+            offset,
+            limit,
+        });
+    }
+
+    let mut values = types
+        .iter()
+        .map(|value| value.to_string())
+        .collect::<Vec<_>>();
+    let mut filter = format!("media_type IN ({})", vec!["?"; types.len()].join(", "));
+    for term in query.unwrap_or_default().split_whitespace() {
+        let pattern = format!("%{}%", escape_like(term));
+        filter.push_str(" AND (name LIKE ? ESCAPE '#' OR COALESCE(artist, '') LIKE ? ESCAPE '#')");
+        values.push(pattern.clone());
+        values.push(pattern);
+    }
+
+    let total = connection
+        .query_row(
+            &format!("SELECT COUNT(*) FROM media_files WHERE {}", filter),
+            params_from_iter(values.iter()),
+            |row| row.get::<_, i64>(0),
+        )
+        .map_err(|e| e.to_string())?;
+
+    let mut statement = connection
+        .prepare(&format!(
+            "SELECT id, name, media_type, artist, duration, path FROM media_files \
+             WHERE {} ORDER BY name COLLATE NOCASE LIMIT {} OFFSET {}",
+            filter, limit, offset
+        ))
+        .map_err(|e| e.to_string())?;
+    let rows = statement
+        .query_map(params_from_iter(values.iter()), media_item)
+        .map_err(|e| e.to_string())?;
+
+    let mut items = Vec::new();
+    for row in rows {
+        items.push(row.map_err(|e| e.to_string())?);
+    }
+
+    Ok(MediaPage {
+        items,
+        total: total as u64,
+        offset,
+        limit,
+    })
+}
+
+pub fn find(media_id: i64) -> Result<MediaItem, String> {
+    let connection = open_device_db()?;
+    let item = if has_media_table(&connection)? {
+        connection
+            .query_row(
+                "SELECT id, name, media_type, artist, duration, path FROM media_files WHERE id = ?1",
+                [media_id],
+                media_item,
+            )
+            .optional()
+            .map_err(|e| e.to_string())?
+    } else {
+        None
+    };
+
+    item.filter(|item| REMOTE_MEDIA_TYPES.contains(&item.media_type.as_str()))
+        .ok_or_else(|| format!("Media item not found: {}", media_id))
+}
+
+/// Adds a file that was just stored in the media directory. Returns `None`
+/// when the UI has not created the library yet; it picks the file up on its
+/// next scan.
+pub fn insert_file(
+    name: &str,
+    path: &str,
+    size: u64,
+    extension: &str,
+    media_type: &str,
+) -> Result<Option<MediaItem>, String> {
+    let connection = open_device_db()?;
+    if !has_media_table(&connection)? {
+        return Ok(None);
+    }
+
+    let modified_at = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as i64)
+        .unwrap_or_default();
+    connection
+        .execute(
+            "INSERT OR IGNORE INTO media_files (name, path, size, modified_at, extension, media_type) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![name, path, size as i64, modified_at, extension, media_type],
+        )
+        .map_err(|e| e.to_string())?;
+
+    connection
+        .query_row(
+            "SELECT id, name, media_type, artist, duration, path FROM media_files WHERE path = ?1",
+            [path],
+            media_item,
+        )
+        .optional()
+        .map_err(|e| e.to_string())
+}
+
+fn media_item(row: &rusqlite::Row<'_>) -> rusqlite::Result<MediaItem> {
+    let id = row.get(0)?;
+    let media_type: String = row.get(2)?;
+    Ok(MediaItem {
+        id,
+        name: row.get(1)?,
+        thumbnail_url: THUMBNAIL_TYPES
+            .contains(&media_type.as_str())
+            .then(|| format!("/api/v1/media/{}/thumbnail", id)),
+        media_type,
+        artist: row.get(3)?,
+        duration: row.get(4)?,
+        path: row.get(5)?,
+    })
+}
+
+/// The UI creates `media_files` the first time it opens the library.
+fn has_media_table(connection: &Connection) -> Result<bool, String> {
+    connection
+        .query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'media_files'",
+            [],
+            |row| row.get::<_, i64>(0),
+        )
+        .map(|count| count > 0)
+        .map_err(|e| e.to_string())
+}
+
+/// Escapes LIKE wildcards with `#`, matching the UI's library search; use
+/// with `ESCAPE '#'`.
+pub fn escape_like(term: &str) -> String {
+    let mut escaped = String::with_capacity(term.len());
+    for c in term.chars() {
+        if matches!(c, '%' | '_' | '#') {
+            escaped.push('#');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_like_escapes_wildcards_and_the_escape_character() {
+        assert_eq!(escape_like("amazing grace"), "amazing grace");
+        assert_eq!(escape_like("100%"), "100#%");
+        assert_eq!(escape_like("my_song"), "my#_song");
+        assert_eq!(escape_like("#1"), "##1");
+    }
+}
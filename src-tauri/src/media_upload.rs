@@ -0,0 +1,755 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::{Read, SeekFrom},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use rusqlite::{Connection, params};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+use uuid::Uuid;
+
+use crate::devices::{app_base_dir, now_ts, open_device_db};
+use crate::media_library::{self, MediaItem};
+use crate::protocol::{ErrorCode, ErrorReply};
+use crate::thumbnail;
+
+/// Largest chunk accepted in one request; the HTTP body limit is 2 MiB.
+pub const CHUNK_BYTES: u64 = 1024 * 1024;
+const MAX_UPLOADS_PER_DEVICE: usize = 4;
+/// Uploads with no chunk for this long are dropped.
+const STALE_UPLOAD_SECS: u64 = 600;
+const MAX_FILE_NAME_CHARS: usize = 120;
+/// Bytes read from the start of a finished upload to check its type.
+const SIGNATURE_BYTES: usize = 16;
+
+/// Extensions a device may upload and the library type each one lands in.
+const UPLOAD_TYPES: &[(&str, &str)] = &[
+    ("jpg", "image"),
+    ("jpeg", "image"),
+    ("png", "image"),
+    ("gif", "image"),
+    ("webp", "image"),
+    ("mp4", "video"),
+    ("mov", "video"),
+    ("m4v", "video"),
+    ("mkv", "video"),
+    ("webm", "video"),
+    ("mp3", "audio"),
+    ("wav", "audio"),
+    ("ogg", "audio"),
+    ("flac", "audio"),
+    ("m4a", "audio"),
+];
+
+const UPLOAD_TABLE_SQL: &str = r#"
+CREATE TABLE IF NOT EXISTS media_uploads (
+    path TEXT PRIMARY KEY,
+    device_id TEXT NOT NULL,
+    size INTEGER NOT NULL,
+    uploaded_at INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_media_uploads_device ON media_uploads (device_id);
+"#;
+
+/// Limits for files sent from paired devices.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UploadConfig {
+    pub enabled: bool,
+    pub max_file_bytes: u64,
+    /// Total size of the files one device has uploaded that are still in
+    /// the library.
+    pub device_quota_bytes: u64,
+}
+
+impl Default for UploadConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_file_bytes: 2 * 1024 * 1024 * 1024,
+            device_quota_bytes: 10 * 1024 * 1024 * 1024,
+        }
+    }
+}
+
+/// Returned when an upload starts.
+#[derive(Debug, Clone, Serialize)]
+pub struct UploadTicket {
+    pub upload_id: String,
+    pub media_type: String,
+    pub chunk_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UploadProgress {
+    pub upload_id: String,
+    pub received: u64,
+    pub size: u64,
+}
+
+/// Payload of `remote_media_uploaded`, emitted to the UI once a file is in
+/// the library.
+#[derive(Debug, Clone, Serialize)]
+pub struct MediaUploaded {
+    pub media_id: Option<i64>,
+    pub name: String,
+    pub path: String,
+    pub extension: String,
+    pub media_type: String,
+    pub size: u64,
+    pub thumbnail_path: Option<String>,
+    pub device_id: String,
+    pub device_name: String,
+}
+
+struct Upload {
+    device_id: String,
+    file_name: String,
+    extension: String,
+    media_type: String,
+    size: u64,
+    received: u64,
+    temp_path: PathBuf,
+    updated_at: u64,
+    /// Set while a chunk is being written, so chunks cannot interleave.
+    writing: bool,
+}
+
+pub struct MediaUploadState {
+    config: Mutex<UploadConfig>,
+    uploads: Mutex<HashMap<String, Upload>>,
+}
+
+pub fn default_media_upload_state() -> MediaUploadState {
+    let config = load_upload_config().unwrap_or_else(|error| {
+        eprintln!("Failed to load upload config, using defaults: {}", error);
+        UploadConfig::default()
+    });
+    if let Err(error) = remove_partial_uploads() {
+        eprintln!("Failed to clear unfinished uploads: {}", error);
+    }
+    MediaUploadState {
+        config: Mutex::new(config),
+        uploads: Mutex::new(HashMap::new()),
+    }
+}
+
+#[tauri::command]
+pub fn get_upload_config(state: State<'_, MediaUploadState>) -> Result<UploadConfig, String> {
+    state
+        .config
+        .lock()
+        .map(|config| config.clone())
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn set_upload_config(
+    state: State<'_, MediaUploadState>,
+    config: UploadConfig,
+) -> Result<UploadConfig, String> {
+    if config.max_file_bytes < CHUNK_BYTES {
+        return Err("Maximum file size must be at least 1 MiB".to_string());
+    }
+    if config.device_quota_bytes < config.max_file_bytes {
+        return Err("The device quota must be at least the maximum file size".to_string());
+    }
+
+    save_upload_config(&config)?;
+    *state.config.lock().map_err(|e| e.to_string())? = config.clone();
+    Ok(config)
+}
+
+/// Starts an upload of `size` bytes after checking the file type, size and
+/// the device's quota.
+pub fn begin(
+    app: &AppHandle,
+    device_id: &str,
+    file_name: &str,
+    size: u64,
+) -> Result<UploadTicket, ErrorReply> {
+    let state = app.state::<MediaUploadState>();
+    let config = state.config.lock().map_err(internal)?.clone();
+    if !config.enabled {
+        return Err(reply(
+            ErrorCode::PermissionDenied,
+            "Uploads are turned off on the desktop",
+        ));
+    }
+
+    let file_name = sanitize_file_name(file_name)
+        .ok_or_else(|| reply(ErrorCode::InvalidPayload, "Invalid file name"))?;
+    let extension = Path::new(&file_name)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_lowercase)
+        .unwrap_or_default();
+    let media_type = UPLOAD_TYPES
+        .iter()
+        .find(|(allowed, _)| *allowed == extension)
+        .map(|(_, media_type)| media_type.to_string())
+        .ok_or_else(|| {
+            reply(
+                ErrorCode::InvalidPayload,
+                format!("`.{}` files cannot be uploaded", extension),
+            )
+        })?;
+
+    if size == 0 || size > config.max_file_bytes {
+        return Err(reply(
+            ErrorCode::InvalidPayload,
+            format!(
+                "`size` must be between 1 and {} bytes",
+                config.max_file_bytes
+            ),
+        ));
+    }
+
+    prune_stale(app);
+    let used = uploaded_bytes(device_id).map_err(internal)?;
+    let temp_dir = upload_dir().map_err(internal)?;
+    let upload_id = Uuid::new_v4().simple().to_string();
+
+    let mut uploads = state.uploads.lock().map_err(internal)?;
+    let pending = uploads
+        .values()
+        .filter(|upload| upload.device_id == device_id)
+        .collect::<Vec<_>>();
+    if pending.len() >= MAX_UPLOADS_PER_DEVICE {
+        return Err(reply(
+            ErrorCode::RateLimited,
+            format!(
+                "At most {} uploads per device can run at once",
+                MAX_UPLOADS_PER_DEVICE
+            ),
+        ));
+    }
+    let reserved = pending.iter().map(|upload| upload.size).sum::<u64>();
+    if used + reserved + size > config.device_quota_bytes {
+        return Err(reply(
+            ErrorCode::InvalidPayload,
+            format!(
+                "Upload quota exceeded: {} of {} bytes in use",
+                used + reserved,
+                config.device_quota_bytes
+            ),
+        ));
+    }
+
+    let temp_path = temp_dir.join(format!("{}.part", upload_id));
+    fs::File::create(&temp_path).map_err(internal)?;
+    uploads.insert(
+        upload_id.clone(),
+        Upload {
+            device_id: device_id.to_string(),
+            file_name,
+            extension,
+            media_type: media_type.clone(),
+            size,
+            received: 0,
+            temp_path,
+            updated_at: now_ts(),
+            writing: false,
+        },
+    );
+
+    Ok(UploadTicket {
+        upload_id,
+        media_type,
+        chunk_bytes: CHUNK_BYTES,
+    })
+}
+
+/// Appends one chunk. `offset` must equal the bytes received so far, so a
+/// client that lost a reply can ask for the progress and resume.
+pub async fn write_chunk(
+    app: &AppHandle,
+    device_id: &str,
+    upload_id: &str,
+    offset: u64,
+    chunk: &[u8],
+) -> Result<UploadProgress, ErrorReply> {
+    let length = chunk.len() as u64;
+    let temp_path = {
+        let state = app.state::<MediaUploadState>();
+        let mut uploads = state.uploads.lock().map_err(internal)?;
+        find_upload(&uploads, device_id, upload_id)?;
+        let Some(upload) = uploads.get_mut(upload_id) else {
+            return Err(reply(ErrorCode::InvalidPayload, "Unknown upload"));
+        };
+        if upload.writing {
+            return Err(reply(
+                ErrorCode::RateLimited,
+                "Wait for the previous chunk to finish",
+            ));
+        }
+        if offset != upload.received {
+            return Err(reply(
+                ErrorCode::InvalidPayload,
+                format!("Expected a chunk at offset {}", upload.received),
+            ));
+        }
+        if length == 0 || length > CHUNK_BYTES || upload.received + length > upload.size {
+            return Err(reply(
+                ErrorCode::InvalidPayload,
+                format!(
+                    "Chunks must hold 1 to {} bytes and stay within the declared size",
+                    CHUNK_BYTES
+                ),
+            ));
+        }
+        upload.writing = true;
+        upload.temp_path.clone()
+    };
+
+    let guard = WritingGuard { app, upload_id };
+    let written = append_chunk(&temp_path, offset, chunk).await;
+    drop(guard);
+
+    let state = app.state::<MediaUploadState>();
+    let mut uploads = state.uploads.lock().map_err(internal)?;
+    let upload = uploads
+        .get_mut(upload_id)
+        .ok_or_else(|| reply(ErrorCode::InvalidPayload, "Upload was cancelled"))?;
+    written.map_err(internal)?;
+    upload.received += length;
+    upload.updated_at = now_ts();
+    Ok(UploadProgress {
+        upload_id: upload_id.to_string(),
+        received: upload.received,
+        size: upload.size,
+    })
+}
+
+/// Clears `writing` when a chunk write ends, including when the request is
+/// dropped mid-write, so the upload can take the chunk again.
+struct WritingGuard<'a> {
+    app: &'a AppHandle,
+    upload_id: &'a str,
+}
+
+impl Drop for WritingGuard<'_> {
+    fn drop(&mut self) {
+        let state = self.app.state::<MediaUploadState>();
+        let Ok(mut uploads) = state.uploads.lock() else {
+            return;
+        };
+        if let Some(upload) = uploads.get_mut(self.upload_id) {
+            upload.writing = false;
+        }
+    }
+}
+
+/// Writes `chunk` at `offset`, cutting off anything a failed earlier write
+/// left behind.
+async fn append_chunk(path: &Path, offset: u64, chunk: &[u8]) -> std::io::Result<()> {
+    let mut file = tokio::fs::OpenOptions::new().write(true).open(path).await?;
+    file.set_len(offset).await?;
+    file.seek(SeekFrom::Start(offset)).await?;
+    file.write_all(chunk).await?;
+    file.flush().await
+}
+
+pub fn progress(
+    app: &AppHandle,
+    device_id: &str,
+    upload_id: &str,
+) -> Result<UploadProgress, ErrorReply> {
+    let state = app.state::<MediaUploadState>();
+    let uploads = state.uploads.lock().map_err(internal)?;
+    let upload = find_upload(&uploads, device_id, upload_id)?;
+    Ok(UploadProgress {
+        upload_id: upload_id.to_string(),
+        received: upload.received,
+        size: upload.size,
+    })
+}
+
+/// Moves a finished upload into the media directory, adds it to the library
+/// with a thumbnail and tells the desktop UI.
+pub async fn complete(
+    app: &AppHandle,
+    device_id: &str,
+    device_name: &str,
+    upload_id: &str,
+) -> Result<MediaUploaded, ErrorReply> {
+    let upload = {
+        let state = app.state::<MediaUploadState>();
+        let mut uploads = state.uploads.lock().map_err(internal)?;
+        let upload = find_upload(&uploads, device_id, upload_id)?;
+        if upload.received != upload.size {
+            return Err(reply(
+                ErrorCode::InvalidPayload,
+                format!(
+                    "Upload is incomplete: {} of {} bytes received",
+                    upload.received, upload.size
+                ),
+            ));
+        }
+        uploads
+            .remove(upload_id)
+            .ok_or_else(|| reply(ErrorCode::InvalidPayload, "Unknown upload"))?
+    };
+
+    match has_expected_signature(&upload.temp_path, &upload.extension) {
+        Ok(true) => {}
+        Ok(false) => {
+            let _ = fs::remove_file(&upload.temp_path);
+            return Err(reply(
+                ErrorCode::InvalidPayload,
+                format!("The file is not a valid `.{}` file", upload.extension),
+            ));
+        }
+        Err(error) => {
+            let _ = fs::remove_file(&upload.temp_path);
+            return Err(internal(error));
+        }
+    }
+
+    let destination = unique_path(
+        &media_dir(&upload.media_type).map_err(internal)?,
+        &upload.file_name,
+    );
+    if let Err(error) = fs::rename(&upload.temp_path, &destination) {
+        let _ = fs::remove_file(&upload.temp_path);
+        return Err(internal(error));
+    }
+
+    let path = destination.to_string_lossy().to_string();
+    let name = destination
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| upload.file_name.clone());
+    record_upload(device_id, &path, upload.size).map_err(internal)?;
+
+    // Audio has no thumbnail; the library shows its usual icon.
+    let thumbnail_path = if media_library::THUMBNAIL_TYPES.contains(&upload.media_type.as_str()) {
+        thumbnail::get_thumbnail(app.clone(), path.clone(), None)
+            .await
+            .map_err(|error| eprintln!("No thumbnail for uploaded {}: {}", path, error))
+            .ok()
+    } else {
+        None
+    };
+
+    let item: Option<MediaItem> = media_library::insert_file(
+        &name,
+        &path,
+        upload.size,
+        &upload.extension,
+        &upload.media_type,
+    )
+    .map_err(internal)?;
+
+    let uploaded = MediaUploaded {
+        media_id: item.map(|item| item.id),
+        name,
+        path,
+        extension: upload.extension,
+        media_type: upload.media_type,
+        size: upload.size,
+        thumbnail_path,
+        device_id: device_id.to_string(),
+        device_name: device_name.to_string(),
+    };
+    let _ = app.emit("remote_media_uploaded", uploaded.clone());
+    println!(
+        "Stored {} ({} bytes) uploaded by {}",
+        uploaded.path, uploaded.size, device_name
+    );
+    Ok(uploaded)
+}
+
+pub fn cancel(app: &AppHandle, device_id: &str, upload_id: &str) -> Result<(), ErrorReply> {
+    let state = app.state::<MediaUploadState>();
+    let mut uploads = state.uploads.lock().map_err(internal)?;
+    find_upload(&uploads, device_id, upload_id)?;
+    if let Some(upload) = uploads.remove(upload_id) {
+        let _ = fs::remove_file(upload.temp_path);
+    }
+    Ok(())
+}
+
+/// An upload belongs to the device that started it; others see it as
+/// unknown.
+fn find_upload<'a>(
+    uploads: &'a HashMap<String, Upload>,
+    device_id: &str,
+    upload_id: &str,
+) -> Result<&'a Upload, ErrorReply> {
+    uploads
+        .get(upload_id)
+        .filter(|upload| upload.device_id == device_id)
+        .ok_or_else(|| reply(ErrorCode::InvalidPayload, "Unknown upload"))
+}
+
+fn prune_stale(app: &AppHandle) {
+    let state = app.state::<MediaUploadState>();
+    let Ok(mut uploads) = state.uploads.lock() else {
+        return;
+    };
+    let now = now_ts();
+    uploads.retain(|upload_id, upload| {
+        let fresh = now.saturating_sub(upload.updated_at) < STALE_UPLOAD_SECS;
+        if !fresh {
+            println!("Dropping stale upload {}", upload_id);
+            let _ = fs::remove_file(&upload.temp_path);
+        }
+        fresh
+    });
+}
+
+/// Bytes the device has uploaded that are still on disk. Rows for files
+/// deleted from the library are dropped so they stop counting.
+fn uploaded_bytes(device_id: &str) -> Result<u64, String> {
+    let connection = open_upload_db()?;
+    let rows = {
+        let mut statement = connection
+            .prepare("SELECT path, size FROM media_uploads WHERE device_id = ?1")
+            .map_err(|e| e.to_string())?;
+        let rows = statement
+            .query_map([device_id], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+            })
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?
+    };
+
+    let mut used = 0;
+    for (path, size) in rows {
+        if Path::new(&path).exists() {
+            used += size.max(0) as u64;
+        } else {
+            connection
+                .execute("DELETE FROM media_uploads WHERE path = ?1", [&path])
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(used)
+}
+
+fn record_upload(device_id: &str, path: &str, size: u64) -> Result<(), String> {
+    let connection = open_upload_db()?;
+    connection
+        .execute(
+            "INSERT OR REPLACE INTO media_uploads (path, device_id, size, uploaded_at) VALUES (?1, ?2, ?3, ?4)",
+            params![path, device_id, size as i64, now_ts() as i64],
+        )
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn open_upload_db() -> Result<Connection, String> {
+    let connection = open_device_db()?;
+    connection
+        .execute_batch(UPLOAD_TABLE_SQL)
+        .map_err(|e| e.to_string())?;
+    Ok(connection)
+}
+
+/// Keeps the last path component, drops control and reserved characters and
+/// shortens long names while keeping the extension.
+fn sanitize_file_name(file_name: &str) -> Option<String> {
+    let base = file_name.rsplit(['/', '\\']).next().unwrap_or_default();
+    let cleaned = base
+        .chars()
+        .filter(|c| !c.is_control() && !matches!(c, '<' | '>' | ':' | '"' | '|' | '?' | '*'))
+        .collect::<String>();
+    let cleaned = cleaned.trim().trim_start_matches('.').trim_end_matches('.');
+    if cleaned.is_empty() {
+        return None;
+    }
+
+    let path = Path::new(cleaned);
+    let stem = path.file_stem()?.to_string_lossy();
+    let extension = path.extension()?.to_string_lossy();
+    let stem = stem.chars().take(MAX_FILE_NAME_CHARS).collect::<String>();
+    Some(format!("{}.{}", stem.trim(), extension))
+}
+
+fn has_expected_signature(path: &Path, extension: &str) -> Result<bool, String> {
+    let mut header = Vec::with_capacity(SIGNATURE_BYTES);
+    fs::File::open(path)
+        .and_then(|file| file.take(SIGNATURE_BYTES as u64).read_to_end(&mut header))
+        .map_err(|e| e.to_string())?;
+    Ok(matches_signature(extension, &header))
+}
+
+/// Whether the first bytes of a file fit its extension, so a device cannot
+/// put, say, an executable into the library under an image name.
+fn matches_signature(extension: &str, header: &[u8]) -> bool {
+    let at = |offset: usize, magic: &[u8]| header.get(offset..offset + magic.len()) == Some(magic);
+    match extension {
+        "jpg" | "jpeg" => at(0, &[0xFF, 0xD8, 0xFF]),
+        "png" => at(0, b"\x89PNG\r\n\x1a\n"),
+        "gif" => at(0, b"GIF87a") || at(0, b"GIF89a"),
+        "webp" => at(0, b"RIFF") && at(8, b"WEBP"),
+        "wav" => at(0, b"RIFF") && at(8, b"WAVE"),
+        "mp4" | "m4v" | "m4a" => at(4, b"ftyp"),
+        // Older QuickTime files can start with any top-level atom.
+        "mov" => [b"ftyp", b"moov", b"mdat", b"wide", b"free", b"skip"]
+            .iter()
+            .any(|atom| at(4, *atom)),
+        "mkv" | "webm" => at(0, &[0x1A, 0x45, 0xDF, 0xA3]),
+        "mp3" => {
+            at(0, b"ID3") || (header.len() >= 2 && header[0] == 0xFF && header[1] & 0xE0 == 0xE0)
+        }
+        "ogg" => at(0, b"OggS"),
+        "flac" => at(0, b"fLaC"),
+        _ => false,
+    }
+}
+
+/// `name`, or `name (2)`, `name (3)` and so on when it is taken, like files
+/// added on the desktop.
+fn unique_path(dir: &Path, file_name: &str) -> PathBuf {
+    let candidate = dir.join(file_name);
+    if !candidate.exists() {
+        return candidate;
+    }
+
+    let path = Path::new(file_name);
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_string())
+        .unwrap_or_default();
+    (2..)
+        .map(|n| dir.join(format!("{} ({}).{}", stem, n, extension)))
+        .find(|candidate| !candidate.exists())
+        .unwrap_or(candidate)
+}
+
+fn reply(code: ErrorCode, message: impl Into<String>) -> ErrorReply {
+    ErrorReply::new(code, message, None)
+}
+
+fn internal(error: impl ToString) -> ErrorReply {
+    reply(ErrorCode::CommandFailed, error.to_string())
+}
+
+fn media_dir(media_type: &str) -> Result<PathBuf, String> {
+    let dir = app_base_dir()?.join("files").join("media").join(media_type);
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir)
+}
+
+fn upload_dir() -> Result<PathBuf, String> {
+    let dir = app_base_dir()?.join("cache").join("uploads");
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir)
+}
+
+/// Deletes `.part` files left by uploads that were running when the app
+/// last quit; no upload survives a restart.
+fn remove_partial_uploads() -> Result<(), String> {
+    for entry in fs::read_dir(upload_dir()?).map_err(|e| e.to_string())? {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        if path
+            .extension()
+            .is_some_and(|extension| extension == "part")
+        {
+            fs::remove_file(&path).map_err(|e| e.to_string())?;
+            println!("Removed unfinished upload {}", path.display());
+        }
+    }
+    Ok(())
+}
+
+fn upload_config_path() -> Result<PathBuf, String> {
+    Ok(app_base_dir()?.join("config").join("uploads.json"))
+}
+
+fn load_upload_config() -> Result<UploadConfig, String> {
+    let path = upload_config_path()?;
+    if !path.exists() {
+        return Ok(UploadConfig::default());
+    }
+
+    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&content).map_err(|e| e.to_string())
+}
+
+fn save_upload_config(config: &UploadConfig) -> Result<(), String> {
+    let path = upload_config_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let content = serde_json::to_string_pretty(config).map_err(|e| e.to_string())?;
+    fs::write(path, content).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sanitize_file_name_keeps_only_the_last_component() {
+        assert_eq!(
+            sanitize_file_name("../../etc/photo.jpg").as_deref(),
+            Some("photo.jpg")
+        );
+        assert_eq!(
+            sanitize_file_name("C:\\Users\\me\\song.mp3").as_deref(),
+            Some("song.mp3")
+        );
+    }
+
+    #[test]
+    fn sanitize_file_name_drops_reserved_and_control_characters() {
+        assert_eq!(
+            sanitize_file_name("a<b>c:d\"e|f?g*h\u{0}.png").as_deref(),
+            Some("abcdefgh.png")
+        );
+        assert_eq!(
+            sanitize_file_name("  ..hidden.mp4.  ").as_deref(),
+            Some("hidden.mp4")
+        );
+    }
+
+    #[test]
+    fn sanitize_file_name_rejects_names_without_a_stem_or_extension() {
+        assert_eq!(sanitize_file_name(""), None);
+        assert_eq!(sanitize_file_name("..."), None);
+        assert_eq!(sanitize_file_name("folder/"), None);
+        assert_eq!(sanitize_file_name("README"), None);
+    }
+
+    #[test]
+    fn sanitize_file_name_shortens_long_stems() {
+        let long = format!("{}.webm", "x".repeat(500));
+        let name = sanitize_file_name(&long).unwrap();
+        assert_eq!(name, format!("{}.webm", "x".repeat(MAX_FILE_NAME_CHARS)));
+    }
+
+    #[test]
+    fn signatures_match_their_extensions() {
+        assert!(matches_signature("jpg", &[0xFF, 0xD8, 0xFF, 0xE0]));
+        assert!(matches_signature("png", b"\x89PNG\r\n\x1a\n\0\0"));
+        assert!(matches_signature("webp", b"RIFF\0\0\0\0WEBPVP8 "));
+        assert!(matches_signature("wav", b"RIFF\0\0\0\0WAVEfmt "));
+        assert!(matches_signature("mp4", b"\0\0\0\x20ftypisom"));
+        assert!(matches_signature("mov", b"\0\0\0\x08wide"));
+        assert!(matches_signature("mp3", b"ID3\x04"));
+        assert!(matches_signature("mp3", &[0xFF, 0xFB, 0x90]));
+        assert!(matches_signature("flac", b"fLaC"));
+    }
+
+    #[test]
+    fn signatures_reject_mismatched_or_short_files() {
+        assert!(!matches_signature("png", &[0xFF, 0xD8, 0xFF]));
+        assert!(!matches_signature("webp", b"RIFF\0\0\0\0WAVE"));
+        assert!(!matches_signature("mp4", b"MZ\x90\0"));
+        assert!(!matches_signature("jpg", &[0xFF]));
+        assert!(!matches_signature("mp3", &[0xFF]));
+        assert!(!matches_signature("exe", b"MZ"));
+    }
+}
@@ -0,0 +1,186 @@
+use std::net::IpAddr;
+
+use local_ip_address::{list_afinet_netifas, local_ip};
+use serde::Serialize;
+use tauri::{AppHandle, Manager, State};
+
+use crate::devices::{DeviceState, set_preferred_interface_setting};
+use crate::discovery::{announce_control_service, announce_html_service};
+use crate::streaming::manager::StreamingState;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NetworkInterfaceKind {
+    Loopback,
+    Ethernet,
+    Wifi,
+    Vpn,
+    Virtual,
+    Other,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NetworkInterfaceInfo {
+    pub name: String,
+    pub addresses: Vec<String>,
+    pub kind: NetworkInterfaceKind,
+    pub is_preferred: bool,
+}
+
+#[tauri::command]
+pub fn list_network_interfaces(
+    state: State<'_, DeviceState>,
+) -> Result<Vec<NetworkInterfaceInfo>, String> {
+    let preferred = preferred_interface(&state)?;
+    let mut interfaces: Vec<NetworkInterfaceInfo> = Vec::new();
+
+    for (name, ip) in list_afinet_netifas().map_err(|e| e.to_string())? {
+        let kind = classify_interface(&name, ip);
+        match interfaces.iter_mut().find(|entry| entry.name == name) {
+            Some(entry) => entry.addresses.push(ip.to_string()),
+            None => interfaces.push(NetworkInterfaceInfo {
+                is_preferred: preferred.as_deref() == Some(name.as_str()),
+                addresses: vec![ip.to_string()],
+                kind,
+                name,
+            }),
+        }
+    }
+
+    interfaces.sort_by_key(|entry| (entry.kind as u8, entry.name.to_lowercase()));
+    Ok(interfaces)
+}
+
+/// Persists the interface used for every advertised address. `None` returns
+/// to automatic selection. mDNS announcements and the advertised HTML URL
+/// follow the new address right away.
+#[tauri::command]
+pub async fn set_preferred_interface(
+    app: AppHandle,
+    state: State<'_, DeviceState>,
+    streaming: State<'_, StreamingState>,
+    name: Option<String>,
+) -> Result<Option<String>, String> {
+    let name = name
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+
+    if let Some(name) = &name {
+        let known = list_afinet_netifas()
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .any(|(candidate, _)| &candidate == name);
+        if !known {
+            return Err(format!("Network interface not found: {}", name));
+        }
+    }
+
+    let settings = set_preferred_interface_setting(&state, name.clone())?;
+    announce_control_service(&app, settings.remote_enabled);
+
+    let manager = streaming.manager.lock().await;
+    if manager.html_server.is_active() {
+        announce_html_service(&app, Some(manager.config.html_server_port));
+    }
+    manager.emit_status();
+
+    Ok(name)
+}
+
+/// The address phones and browsers should use to reach this desktop: the
+/// first IPv4 address of the preferred interface, or the OS default route
+/// when no interface is chosen or it has gone away.
+pub fn advertised_ip(app: &AppHandle) -> Result<IpAddr, String> {
+    match preferred_ip(app)? {
+        Some(ip) => Ok(ip),
+        None => local_ip().map_err(|e| e.to_string()),
+    }
+}
+
+/// The first IPv4 address of the preferred interface, or `None` when no
+/// interface is chosen or it currently has no address.
+pub fn preferred_ip(app: &AppHandle) -> Result<Option<IpAddr>, String> {
+    let Some(name) = preferred_interface(&app.state::<DeviceState>())? else {
+        return Ok(None);
+    };
+
+    let addresses = list_afinet_netifas()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|(candidate, _)| *candidate == name)
+        .map(|(_, ip)| ip)
+        .collect::<Vec<_>>();
+
+    Ok(addresses
+        .iter()
+        .find(|ip| ip.is_ipv4())
+        .or_else(|| addresses.first())
+        .copied())
+}
+
+/// Whether ICE may gather host candidates on `name`.
+pub fn is_ice_interface_allowed(app: &AppHandle, name: &str) -> bool {
+    match preferred_interface(&app.state::<DeviceState>()) {
+        Ok(Some(preferred)) => preferred == name,
+        _ => true,
+    }
+}
+
+fn preferred_interface(state: &DeviceState) -> Result<Option<String>, String> {
+    state
+        .remote_settings
+        .lock()
+        .map(|settings| settings.preferred_interface.clone())
+        .map_err(|e| e.to_string())
+}
+
+fn classify_interface(name: &str, ip: IpAddr) -> NetworkInterfaceKind {
+    if ip.is_loopback() {
+        return NetworkInterfaceKind::Loopback;
+    }
+
+    let lower = name.to_lowercase();
+    let matches_any = |prefixes: &[&str]| prefixes.iter().any(|prefix| lower.starts_with(prefix));
+
+    if matches_any(&[
+        "tun",
+        "tap",
+        "wg",
+        "utun",
+        "ppp",
+        "vpn",
+        "tailscale",
+        "zerotier",
+        "zt",
+        "ipsec",
+        "nordlynx",
+    ]) {
+        NetworkInterfaceKind::Vpn
+    } else if matches_any(&[
+        "docker",
+        "br-",
+        "veth",
+        "vmnet",
+        "vbox",
+        "virbr",
+        "vethernet",
+        "hyper-v",
+        "bridge",
+        "lxc",
+        "lxd",
+        "awdl",
+        "llw",
+    ]) {
+        NetworkInterfaceKind::Virtual
+    } else if matches_any(&["wlan", "wl", "wi-fi", "wifi", "wireless", "airport"]) {
+        NetworkInterfaceKind::Wifi
+    } else if matches_any(&["eth", "ethernet", "local area connection"]) {
+        NetworkInterfaceKind::Ethernet
+    } else if lower.starts_with("en") && !cfg!(target_os = "macos") {
+        // Linux names wired ports `enp3s0`/`eno1`; macOS uses `en0` for
+        // Wi-Fi and Ethernet alike, so there it stays unclassified.
+        NetworkInterfaceKind::Ethernet
+    } else {
+        NetworkInterfaceKind::Other
+    }
+}
@@ -0,0 +1,601 @@
+use std::{
+    collections::HashMap,
+    fs,
+    net::{IpAddr, SocketAddr},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use rosc::{OscMessage, OscPacket, OscType, decoder, encoder};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, Manager, State, async_runtime};
+use tokio::net::UdpSocket;
+
+use crate::audit::{self, AuditActor, AuditOutcome};
+use crate::control_lock;
+use crate::devices::app_base_dir;
+use crate::live_state;
+use crate::protocol::ClientMessage;
+use crate::websocket::run_control_message;
+
+const DEFAULT_OSC_PORT: u16 = 9000;
+/// Largest UDP payload.
+const MAX_PACKET_BYTES: usize = 65_507;
+
+/// What an incoming OSC address does. Each maps onto the same command a
+/// remote device would send.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OscAction {
+    Next,
+    Previous,
+    PlayPause,
+    Stop,
+    Mute,
+    /// Volume from 0 to 100, after `scale`.
+    Volume,
+    /// Position in seconds, after `scale`.
+    Seek,
+    /// Any non-zero value enables looping.
+    Loop,
+    /// Jumps to a lyric slide, counting from 0.
+    LyricsSlide,
+}
+
+impl OscAction {
+    /// Actions that fire once per message rather than taking a value.
+    fn is_trigger(self) -> bool {
+        matches!(
+            self,
+            Self::Next | Self::Previous | Self::PlayPause | Self::Stop | Self::Mute
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OscMapping {
+    pub address: String,
+    pub action: OscAction,
+    /// Multiplies the first argument, e.g. `100` for a 0–1 fader on volume.
+    #[serde(default = "default_scale")]
+    pub scale: f64,
+}
+
+/// A snapshot field mirrored to the feedback targets.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OscFeedback {
+    pub address: String,
+    /// Dot path into the live state, e.g. `playback.is_playing`.
+    pub field: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OscConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// `None` listens on every IPv4 address.
+    #[serde(default)]
+    pub bind_address: Option<String>,
+    #[serde(default = "default_osc_port")]
+    pub port: u16,
+    /// Addresses allowed to send commands. Empty allows only this computer.
+    #[serde(default)]
+    pub allowed_sources: Vec<String>,
+    /// `host:port` pairs that receive state feedback.
+    #[serde(default)]
+    pub feedback_targets: Vec<String>,
+    #[serde(default = "default_mappings")]
+    pub mappings: Vec<OscMapping>,
+    #[serde(default = "default_feedback")]
+    pub feedback: Vec<OscFeedback>,
+}
+
+impl Default for OscConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind_address: None,
+            port: DEFAULT_OSC_PORT,
+            allowed_sources: Vec::new(),
+            feedback_targets: Vec::new(),
+            mappings: default_mappings(),
+            feedback: default_feedback(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct OscStatus {
+    pub running: bool,
+    pub address: Option<String>,
+    /// Why the last start failed, while not running.
+    pub error: Option<String>,
+}
+
+pub struct OscState {
+    config: Mutex<OscConfig>,
+    status: Mutex<OscStatus>,
+    socket: Mutex<Option<Arc<UdpSocket>>>,
+    task: Mutex<Option<async_runtime::JoinHandle<()>>>,
+    /// Last value sent per feedback address, so only changes go out.
+    sent: Mutex<HashMap<String, OscType>>,
+}
+
+pub fn default_osc_state() -> OscState {
+    OscState {
+        config: Mutex::new(OscConfig::default()),
+        status: Mutex::new(OscStatus::default()),
+        socket: Mutex::new(None),
+        task: Mutex::new(None),
+        sent: Mutex::new(HashMap::new()),
+    }
+}
+
+/// Loads `config/osc.json` and starts listening when OSC is enabled. A
+/// failure is logged and kept in the status.
+pub fn start(app: &AppHandle) {
+    let config = match load_osc_config() {
+        Ok(config) => config,
+        Err(error) => {
+            eprintln!("Failed to load OSC config: {}", error);
+            OscConfig::default()
+        }
+    };
+    if let Ok(mut current) = app.state::<OscState>().config.lock() {
+        *current = config.clone();
+    }
+
+    if let Err(error) = listen_with(app, &config) {
+        report_failure(app, error);
+    }
+}
+
+/// Mirrors changed live-state fields to the feedback targets.
+pub fn send_feedback(app: &AppHandle, snapshot: &Value) {
+    let Some(state) = app.try_state::<OscState>() else {
+        return;
+    };
+    let Some(socket) = state.socket.lock().ok().and_then(|socket| socket.clone()) else {
+        return;
+    };
+    let (targets, feedback) = match state.config.lock() {
+        Ok(config) => (
+            parse_targets(&config.feedback_targets),
+            config.feedback.clone(),
+        ),
+        Err(_) => return,
+    };
+    if targets.is_empty() {
+        return;
+    }
+
+    let Ok(mut sent) = state.sent.lock() else {
+        return;
+    };
+    for item in feedback {
+        let value = osc_value(lookup(snapshot, &item.field));
+        if sent.get(&item.address) == Some(&value) {
+            continue;
+        }
+
+        let packet = OscPacket::Message(OscMessage {
+            addr: item.address.clone(),
+            args: vec![value.clone()],
+        });
+        let Ok(bytes) = encoder::encode(&packet) else {
+            continue;
+        };
+        for target in &targets {
+            if let Err(error) = socket.try_send_to(&bytes, *target) {
+                eprintln!("Failed to send OSC feedback to {}: {}", target, error);
+            }
+        }
+        sent.insert(item.address, value);
+    }
+}
+
+#[tauri::command]
+pub fn get_osc_config(state: State<'_, OscState>) -> Result<OscConfig, String> {
+    state
+        .config
+        .lock()
+        .map(|config| config.clone())
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_osc_status(state: State<'_, OscState>) -> Result<OscStatus, String> {
+    state
+        .status
+        .lock()
+        .map(|status| status.clone())
+        .map_err(|e| e.to_string())
+}
+
+/// Saves the OSC config and restarts the listener with it. When the new
+/// address cannot be bound the previous config is restored.
+#[tauri::command]
+pub async fn set_osc_config(
+    app: AppHandle,
+    state: State<'_, OscState>,
+    config: OscConfig,
+) -> Result<OscStatus, String> {
+    validate_config(&config)?;
+    let previous = state.config.lock().map_err(|e| e.to_string())?.clone();
+
+    stop(&app).await?;
+    *state.config.lock().map_err(|e| e.to_string())? = config.clone();
+    match listen_with(&app, &config) {
+        Ok(status) => {
+            save_osc_config(&config)?;
+            Ok(status)
+        }
+        Err(error) => {
+            *state.config.lock().map_err(|e| e.to_string())? = previous.clone();
+            if let Err(restore_error) = listen_with(&app, &previous) {
+                report_failure(&app, restore_error);
+            }
+            Err(error)
+        }
+    }
+}
+
+async fn stop(app: &AppHandle) -> Result<(), String> {
+    let state = app.state::<OscState>();
+    let task = state.task.lock().map_err(|e| e.to_string())?.take();
+    state.socket.lock().map_err(|e| e.to_string())?.take();
+    if let Some(task) = task {
+        task.abort();
+        let _ = task.await;
+    }
+    Ok(())
+}
+
+fn listen_with(app: &AppHandle, config: &OscConfig) -> Result<OscStatus, String> {
+    if !config.enabled {
+        let status = OscStatus::default();
+        set_status(app, status.clone());
+        return Ok(status);
+    }
+
+    let ip = match config.bind_address.as_deref() {
+        Some(address) => address
+            .parse::<IpAddr>()
+            .map_err(|_| format!("Invalid bind address: {}", address))?,
+        None => IpAddr::from([0, 0, 0, 0]),
+    };
+    let address = SocketAddr::new(ip, config.port);
+    let socket = std::net::UdpSocket::bind(address)
+        .map_err(|e| format!("Cannot listen for OSC on {}: {}", address, e))?;
+    socket.set_nonblocking(true).map_err(|e| e.to_string())?;
+    let socket = Arc::new(UdpSocket::from_std(socket).map_err(|e| e.to_string())?);
+
+    let state = app.state::<OscState>();
+    *state.socket.lock().map_err(|e| e.to_string())? = Some(socket.clone());
+    *state.task.lock().map_err(|e| e.to_string())? =
+        Some(async_runtime::spawn(receive_loop(socket, app.clone())));
+    state.sent.lock().map_err(|e| e.to_string())?.clear();
+    println!("OSC listening on {}", address);
+
+    let status = OscStatus {
+        running: true,
+        address: Some(address.to_string()),
+        error: None,
+    };
+    set_status(app, status.clone());
+
+    // Bring new feedback targets up to date right away.
+    if let Some(snapshot) =
+        live_state::current_snapshot(app).and_then(|snapshot| serde_json::to_value(snapshot).ok())
+    {
+        send_feedback(app, &snapshot);
+    }
+    Ok(status)
+}
+
+fn report_failure(app: &AppHandle, error: String) {
+    eprintln!("OSC not started: {}", error);
+    set_status(
+        app,
+        OscStatus {
+            error: Some(error),
+            ..OscStatus::default()
+        },
+    );
+}
+
+fn set_status(app: &AppHandle, status: OscStatus) {
+    if let Ok(mut current) = app.state::<OscState>().status.lock() {
+        *current = status.clone();
+    }
+    let _ = app.emit("osc_status", status);
+}
+
+async fn receive_loop(socket: Arc<UdpSocket>, app: AppHandle) {
+    let mut buffer = vec![0u8; MAX_PACKET_BYTES];
+    loop {
+        let (length, source) = match socket.recv_from(&mut buffer).await {
+            Ok(received) => received,
+            Err(error) => {
+                // Windows reports ICMP port unreachable from earlier
+                // feedback sends here; the socket is still usable.
+                eprintln!("OSC receive error: {}", error);
+                continue;
+            }
+        };
+
+        if !is_allowed_source(&app, source.ip()) {
+            eprintln!("Dropped OSC packet from {}: source not allowed", source);
+            continue;
+        }
+
+        match decoder::decode_udp(&buffer[..length]) {
+            Ok((_, packet)) => handle_packet(&app, packet, source),
+            Err(error) => eprintln!("Invalid OSC packet from {}: {:?}", source, error),
+        }
+    }
+}
+
+fn handle_packet(app: &AppHandle, packet: OscPacket, source: SocketAddr) {
+    match packet {
+        OscPacket::Message(message) => handle_message(app, &message, source),
+        OscPacket::Bundle(bundle) => {
+            for packet in bundle.content {
+                handle_packet(app, packet, source);
+            }
+        }
+    }
+}
+
+fn handle_message(app: &AppHandle, message: &OscMessage, source: SocketAddr) {
+    let mapping = app
+        .state::<OscState>()
+        .config
+        .lock()
+        .ok()
+        .and_then(|config| {
+            config
+                .mappings
+                .iter()
+                .find(|mapping| mapping.address == message.addr)
+                .cloned()
+        });
+    let Some(mapping) = mapping else {
+        return;
+    };
+
+    let value = message
+        .args
+        .first()
+        .and_then(number)
+        .map(|value| value * mapping.scale);
+    // Consoles send 1 on button press and 0 on release; only the press
+    // should fire.
+    if mapping.action.is_trigger() && value == Some(0.0) {
+        return;
+    }
+
+    let source_address = source.to_string();
+    let result = run_action(app, mapping.action, value, &source_address)
+        .map_err(|reason| format!("{}: {}", message.addr, reason));
+    if let Err(reason) = &result {
+        eprintln!("OSC command from {} failed: {}", source, reason);
+    }
+    audit::record(
+        app,
+        AuditActor {
+            peer: Some(&source_address),
+            ..AuditActor::default()
+        },
+        audit::COMMAND,
+        &format!("osc {}", message.addr),
+        &format!("{:?}", message.args),
+        AuditOutcome::of(&result),
+    );
+}
+
+fn run_action(
+    app: &AppHandle,
+    action: OscAction,
+    value: Option<f64>,
+    source: &str,
+) -> Result<(), String> {
+    let required = || value.ok_or_else(|| "a numeric argument is required".to_string());
+    let message = match action {
+        OscAction::Next => ClientMessage::Next,
+        OscAction::Previous => ClientMessage::Previous,
+        OscAction::PlayPause => ClientMessage::PlayPause,
+        OscAction::Stop => ClientMessage::Stop,
+        OscAction::Mute => ClientMessage::Mute,
+        OscAction::Volume => ClientMessage::SetVolume {
+            value: required()?.clamp(0.0, 100.0),
+        },
+        OscAction::Seek => ClientMessage::Seek {
+            value: required()?.max(0.0),
+        },
+        OscAction::Loop => ClientMessage::SetLoop {
+            value: Some(required()?),
+        },
+        OscAction::LyricsSlide => ClientMessage::LyricsSlide {
+            index: required()?.max(0.0) as usize,
+        },
+    };
+    check_unlocked(app, control_lock::lock_scopes(&message))?;
+    run_control_message(app, source, &message).map_err(|error| error.message)
+}
+
+/// OSC senders are not devices and cannot take a control lock, so a lock
+/// held by anyone on `scopes` turns their commands away, as it does for
+/// other devices.
+fn check_unlocked(app: &AppHandle, scopes: &[&str]) -> Result<(), String> {
+    match control_lock::blocking_any_lock(app, scopes) {
+        Some(lock) => Err(format!(
+            "`{}` is locked by {}",
+            lock.scope, lock.device_name
+        )),
+        None => Ok(()),
+    }
+}
+
+fn is_allowed_source(app: &AppHandle, ip: IpAddr) -> bool {
+    let ip = ip.to_canonical();
+    let state = app.state::<OscState>();
+    let Ok(config) = state.config.lock() else {
+        return false;
+    };
+    if config.allowed_sources.is_empty() {
+        return ip.is_loopback();
+    }
+    config
+        .allowed_sources
+        .iter()
+        .filter_map(|source| source.parse::<IpAddr>().ok())
+        .any(|allowed| allowed == ip)
+}
+
+fn validate_config(config: &OscConfig) -> Result<(), String> {
+    if config.port == 0 {
+        return Err("Port must be between 1 and 65535".to_string());
+    }
+    if let Some(address) = &config.bind_address {
+        address
+            .parse::<IpAddr>()
+            .map_err(|_| format!("Invalid bind address: {}", address))?;
+    }
+    for source in &config.allowed_sources {
+        source
+            .parse::<IpAddr>()
+            .map_err(|_| format!("Invalid allowed source: {}", source))?;
+    }
+    for target in &config.feedback_targets {
+        target
+            .parse::<SocketAddr>()
+            .map_err(|_| format!("Invalid feedback target `{}`; expected ip:port", target))?;
+    }
+    for address in config
+        .mappings
+        .iter()
+        .map(|mapping| &mapping.address)
+        .chain(config.feedback.iter().map(|feedback| &feedback.address))
+    {
+        if !address.starts_with('/') {
+            return Err(format!("OSC address must start with `/`: {}", address));
+        }
+    }
+    Ok(())
+}
+
+fn parse_targets(targets: &[String]) -> Vec<SocketAddr> {
+    targets
+        .iter()
+        .filter_map(|target| target.parse().ok())
+        .collect()
+}
+
+fn number(arg: &OscType) -> Option<f64> {
+    match arg {
+        OscType::Int(value) => Some(f64::from(*value)),
+        OscType::Long(value) => Some(*value as f64),
+        OscType::Float(value) => Some(f64::from(*value)),
+        OscType::Double(value) => Some(*value),
+        OscType::Bool(value) => Some(if *value { 1.0 } else { 0.0 }),
+        OscType::String(value) => value.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn lookup<'a>(snapshot: &'a Value, field: &str) -> Option<&'a Value> {
+    field
+        .split('.')
+        .try_fold(snapshot, |value, key| value.get(key))
+}
+
+/// Booleans become 0/1 integers since many consoles have no boolean type.
+/// Missing fields are sent as an empty string.
+fn osc_value(value: Option<&Value>) -> OscType {
+    match value {
+        Some(Value::Bool(value)) => OscType::Int(i32::from(*value)),
+        Some(Value::Number(number)) => match number.as_i64() {
+            Some(value) => OscType::Int(value as i32),
+            None => OscType::Float(number.as_f64().unwrap_or_default() as f32),
+        },
+        Some(Value::String(value)) => OscType::String(value.clone()),
+        _ => OscType::String(String::new()),
+    }
+}
+
+fn default_osc_port() -> u16 {
+    DEFAULT_OSC_PORT
+}
+
+fn default_scale() -> f64 {
+    1.0
+}
+
+fn default_mappings() -> Vec<OscMapping> {
+    [
+        ("/lumen/next", OscAction::Next),
+        ("/lumen/previous", OscAction::Previous),
+        ("/lumen/play_pause", OscAction::PlayPause),
+        ("/lumen/stop", OscAction::Stop),
+        ("/lumen/mute", OscAction::Mute),
+        ("/lumen/volume", OscAction::Volume),
+        ("/lumen/seek", OscAction::Seek),
+        ("/lumen/loop", OscAction::Loop),
+        ("/lumen/lyrics/slide", OscAction::LyricsSlide),
+    ]
+    .into_iter()
+    .map(|(address, action)| OscMapping {
+        address: address.to_string(),
+        action,
+        scale: default_scale(),
+    })
+    .collect()
+}
+
+fn default_feedback() -> Vec<OscFeedback> {
+    [
+        ("/lumen/playing", "playback.is_playing"),
+        ("/lumen/position", "playback.position"),
+        ("/lumen/duration", "playback.duration"),
+        ("/lumen/volume", "audio.volume"),
+        ("/lumen/muted", "audio.is_muted"),
+        ("/lumen/loop", "audio.is_loop"),
+        ("/lumen/title", "media.title"),
+        ("/lumen/lyrics/active", "lyric.active"),
+        ("/lumen/lyrics/slide", "lyric.slide_index"),
+        ("/lumen/lyrics/total", "lyric.total_slides"),
+    ]
+    .into_iter()
+    .map(|(address, field)| OscFeedback {
+        address: address.to_string(),
+        field: field.to_string(),
+    })
+    .collect()
+}
+
+fn osc_config_path() -> Result<PathBuf, String> {
+    Ok(app_base_dir()?.join("config").join("osc.json"))
+}
+
+fn load_osc_config() -> Result<OscConfig, String> {
+    let path = osc_config_path()?;
+    if !path.exists() {
+        return Ok(OscConfig::default());
+    }
+
+    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let config: OscConfig = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+    validate_config(&config)?;
+    Ok(config)
+}
+
+fn save_osc_config(config: &OscConfig) -> Result<(), String> {
+    let path = osc_config_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let content = serde_json::to_string_pretty(config).map_err(|e| e.to_string())?;
+    fs::write(path, content).map_err(|e| e.to_string())
+}
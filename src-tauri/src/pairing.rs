@@ -0,0 +1,550 @@
+use std::{collections::HashMap, sync::Mutex, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_notification::NotificationExt;
+use tokio::sync::mpsc::UnboundedSender;
+use tokio_tungstenite::tungstenite::Message;
+use uuid::Uuid;
+
+use crate::audit::{self, AuditActor, AuditOutcome};
+use crate::control_limits::TokenBucket;
+use crate::devices::{
+    DeviceState, RegisterPayload, RegistrationTokenPayload, RemoteAccessSettings,
+    auth_fail_message, now_ts, registration_payload, set_pairing_approval_setting,
+};
+
+const QR_TOKEN_LIFETIME_SECS: u64 = 900;
+const PIN_LIFETIME_SECS: u64 = 300;
+const PIN_DIGITS: usize = 6;
+/// Wrong PINs tolerated before every outstanding PIN is invalidated.
+const MAX_FAILED_PIN_ATTEMPTS: u32 = 5;
+/// How long an approved device has to come back with its token.
+const APPROVAL_TOKEN_LIFETIME_SECS: u64 = 120;
+const APPROVAL_REQUEST_LIFETIME_SECS: u64 = 300;
+const MAX_PAIRING_TOKENS: usize = 16;
+const MAX_PAIRING_REQUESTS: usize = 16;
+/// Approval requests one IP may make at once, each of which notifies the
+/// operator, and how often it may make another after that.
+const PAIRING_REQUEST_BURST: u32 = 3;
+const PAIRING_REQUEST_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Close code for a device whose pairing request the operator rejected.
+pub const PAIRING_REJECTED_CLOSE_CODE: u16 = 4007;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PairingKind {
+    Qr,
+    Pin,
+    /// Issued when the operator approves a request; only the requesting
+    /// device can redeem it.
+    Approval,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegistrationToken {
+    pub token: String,
+    pub kind: PairingKind,
+    pub created_at: u64,
+    pub expires_at: u64,
+    pub is_used: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device_id: Option<String>,
+}
+
+/// A device waiting for the operator to let it pair.
+#[derive(Debug, Clone, Serialize)]
+pub struct PairingRequest {
+    pub request_id: String,
+    pub device_id: String,
+    pub device_name: String,
+    pub device_type: String,
+    pub os: String,
+    pub version: String,
+    pub ip: String,
+    pub requested_at: u64,
+    pub expires_at: u64,
+}
+
+struct PendingPairing {
+    request: PairingRequest,
+    sender: UnboundedSender<Message>,
+}
+
+pub struct PairingState {
+    tokens: Mutex<Vec<RegistrationToken>>,
+    failed_pin_attempts: Mutex<u32>,
+    requests: Mutex<HashMap<String, PendingPairing>>,
+    /// Approval requests per IP.
+    request_buckets: Mutex<HashMap<String, TokenBucket>>,
+}
+
+pub fn default_pairing_state() -> PairingState {
+    PairingState {
+        tokens: Mutex::new(Vec::new()),
+        failed_pin_attempts: Mutex::new(0),
+        requests: Mutex::new(HashMap::new()),
+        request_buckets: Mutex::new(HashMap::new()),
+    }
+}
+
+#[derive(Serialize)]
+struct PairingPendingResponse<'a> {
+    event: &'static str,
+    request_id: &'a str,
+    expires_at: u64,
+}
+
+#[derive(Serialize)]
+struct PairingApprovedResponse<'a> {
+    event: &'static str,
+    request_id: &'a str,
+    token: &'a str,
+    expires_at: u64,
+}
+
+#[derive(Clone, Serialize)]
+struct PairingResolvedEvent {
+    request_id: String,
+    device_id: String,
+    outcome: &'static str,
+}
+
+/// Adds a QR pairing token. Earlier tokens stay valid until they expire or
+/// are used.
+pub fn issue_qr_token(state: &PairingState) -> Result<RegistrationToken, String> {
+    issue_token(
+        state,
+        Uuid::new_v4().to_string(),
+        PairingKind::Qr,
+        QR_TOKEN_LIFETIME_SECS,
+        None,
+    )
+}
+
+/// Creates a short numeric PIN for devices that cannot scan a QR code. The
+/// device sends it as the `register` token.
+#[tauri::command]
+pub fn generate_pairing_pin(
+    app: AppHandle,
+    state: State<'_, PairingState>,
+) -> Result<RegistrationTokenPayload, String> {
+    let pin = {
+        let tokens = state.tokens.lock().map_err(|e| e.to_string())?;
+        loop {
+            let pin = random_pin();
+            if !tokens.iter().any(|token| token.token == pin) {
+                break pin;
+            }
+        }
+    };
+    let token = issue_token(&state, pin, PairingKind::Pin, PIN_LIFETIME_SECS, None)?;
+    registration_payload(&app, &token)
+}
+
+/// Unused pairing tokens and PINs that have not expired.
+#[tauri::command]
+pub fn get_pairing_tokens(
+    state: State<'_, PairingState>,
+) -> Result<Vec<RegistrationToken>, String> {
+    let mut tokens = state.tokens.lock().map_err(|e| e.to_string())?;
+    prune_tokens(&mut tokens, now_ts());
+    Ok(tokens.clone())
+}
+
+#[tauri::command]
+pub fn cancel_pairing_token(state: State<'_, PairingState>, token: String) -> Result<(), String> {
+    let mut tokens = state.tokens.lock().map_err(|e| e.to_string())?;
+    tokens.retain(|pending| pending.token != token);
+    Ok(())
+}
+
+/// Turns "approve on desktop" pairing on or off. While on, a `register`
+/// without a token waits in the pairing request list.
+#[tauri::command]
+pub fn set_pairing_approval(
+    app: AppHandle,
+    state: State<'_, DeviceState>,
+    enabled: bool,
+) -> Result<RemoteAccessSettings, String> {
+    let settings = set_pairing_approval_setting(&state, enabled)?;
+    if !enabled {
+        let request_ids = {
+            let pairing = app.state::<PairingState>();
+            let requests = pairing.requests.lock().map_err(|e| e.to_string())?;
+            requests.keys().cloned().collect::<Vec<_>>()
+        };
+        for request_id in request_ids {
+            reject(&app, &request_id)?;
+        }
+    }
+    Ok(settings)
+}
+
+#[tauri::command]
+pub fn get_pairing_requests(
+    app: AppHandle,
+    state: State<'_, PairingState>,
+) -> Result<Vec<PairingRequest>, String> {
+    expire_requests(&app, &state)?;
+    let requests = state.requests.lock().map_err(|e| e.to_string())?;
+    let mut values = requests
+        .values()
+        .map(|pending| pending.request.clone())
+        .collect::<Vec<_>>();
+    values.sort_by_key(|request| request.requested_at);
+    Ok(values)
+}
+
+/// Lets a waiting device pair. It receives a single-use token bound to its
+/// device id and completes pairing with a second `register`.
+#[tauri::command]
+pub fn approve_pairing_request(
+    app: AppHandle,
+    state: State<'_, PairingState>,
+    request_id: String,
+) -> Result<(), String> {
+    expire_requests(&app, &state)?;
+    let pending = state
+        .requests
+        .lock()
+        .map_err(|e| e.to_string())?
+        .remove(&request_id)
+        .ok_or_else(|| format!("Pairing request not found: {}", request_id))?;
+
+    let token = issue_token(
+        &state,
+        Uuid::new_v4().to_string(),
+        PairingKind::Approval,
+        APPROVAL_TOKEN_LIFETIME_SECS,
+        Some(pending.request.device_id.clone()),
+    )?;
+    let _ = pending.sender.send(json_message(&PairingApprovedResponse {
+        event: "pairing_approved",
+        request_id: &request_id,
+        token: &token.token,
+        expires_at: token.expires_at,
+    })?);
+
+    println!(
+        "Approved pairing request from {} ({})",
+        pending.request.device_name, pending.request.ip
+    );
+    audit_request(&app, &pending.request, "approve_pairing");
+    emit_resolved(&app, &pending.request, "approved");
+    Ok(())
+}
+
+#[tauri::command]
+pub fn reject_pairing_request(app: AppHandle, request_id: String) -> Result<(), String> {
+    if !reject(&app, &request_id)? {
+        return Err(format!("Pairing request not found: {}", request_id));
+    }
+    Ok(())
+}
+
+/// Consumes the token a device sent with `register`.
+pub fn redeem_token(app: &AppHandle, token: &str, device_id: &str) -> Result<(), String> {
+    let state = app.state::<PairingState>();
+    let now = now_ts();
+    let mut tokens = state.tokens.lock().map_err(|e| e.to_string())?;
+
+    let Some(index) = tokens.iter().position(|pending| pending.token == token) else {
+        drop(tokens);
+        if is_pin(token) {
+            record_failed_pin(app, &state)?;
+        }
+        return Err("token_expired".to_string());
+    };
+
+    let pending = &mut tokens[index];
+    if pending.expires_at < now {
+        return Err("token_expired".to_string());
+    }
+    if pending.is_used {
+        return Err("token_used".to_string());
+    }
+    if pending
+        .device_id
+        .as_deref()
+        .is_some_and(|bound| bound != device_id)
+    {
+        return Err("token_expired".to_string());
+    }
+
+    pending.is_used = true;
+    let kind = pending.kind;
+    prune_tokens(&mut tokens, now);
+    drop(tokens);
+
+    if kind == PairingKind::Pin {
+        *state
+            .failed_pin_attempts
+            .lock()
+            .map_err(|e| e.to_string())? = 0;
+    }
+    Ok(())
+}
+
+/// Queues a `register` that came without a token for the operator, tells
+/// the UI and returns the `pairing_pending` reply for the device.
+pub fn request_approval(
+    app: &AppHandle,
+    payload: &RegisterPayload,
+    ip: String,
+    sender: UnboundedSender<Message>,
+) -> Result<(String, Message), String> {
+    let state = app.state::<PairingState>();
+    expire_requests(app, &state)?;
+
+    {
+        let mut buckets = state.request_buckets.lock().map_err(|e| e.to_string())?;
+        buckets.retain(|_, bucket| !bucket.is_full());
+        let bucket = buckets.entry(ip.clone()).or_insert_with(|| {
+            TokenBucket::per_interval(PAIRING_REQUEST_BURST, PAIRING_REQUEST_INTERVAL)
+        });
+        if !bucket.try_take() {
+            println!("Dropped pairing request from {}: too many requests", ip);
+            return Err("too_many_pairing_requests".to_string());
+        }
+    }
+
+    let now = now_ts();
+    let request = PairingRequest {
+        request_id: Uuid::new_v4().to_string(),
+        device_id: payload.device_id.clone(),
+        device_name: payload.device_name.clone(),
+        device_type: payload.device_type.clone(),
+        os: payload.os.clone(),
+        version: payload.version.clone(),
+        ip,
+        requested_at: now,
+        expires_at: now + APPROVAL_REQUEST_LIFETIME_SECS,
+    };
+
+    let replaced = {
+        let mut requests = state.requests.lock().map_err(|e| e.to_string())?;
+        let replaced = requests
+            .iter()
+            .find(|(_, pending)| pending.request.device_id == request.device_id)
+            .map(|(request_id, _)| request_id.clone())
+            .and_then(|request_id| requests.remove(&request_id));
+        if requests.len() >= MAX_PAIRING_REQUESTS {
+            return Err("too_many_pairing_requests".to_string());
+        }
+        requests.insert(
+            request.request_id.clone(),
+            PendingPairing {
+                request: request.clone(),
+                sender,
+            },
+        );
+        replaced
+    };
+    if let Some(replaced) = replaced {
+        emit_resolved(app, &replaced.request, "replaced");
+    }
+
+    println!(
+        "Pairing request from {} ({}) is waiting for approval",
+        request.device_name, request.ip
+    );
+    audit_request(app, &request, "request_pairing");
+    let _ = app.emit("pairing_request", request.clone());
+    let _ = app
+        .notification()
+        .builder()
+        .title("Pairing request")
+        .body(format!(
+            "{} ({}) wants to connect to this computer.",
+            request.device_name, request.ip
+        ))
+        .show();
+
+    let reply = json_message(&PairingPendingResponse {
+        event: "pairing_pending",
+        request_id: &request.request_id,
+        expires_at: request.expires_at,
+    })?;
+    Ok((request.request_id, reply))
+}
+
+/// Drops the request of a device that disconnected before a decision.
+pub fn withdraw_request(app: &AppHandle, request_id: &str) -> Result<(), String> {
+    let removed = app
+        .state::<PairingState>()
+        .requests
+        .lock()
+        .map_err(|e| e.to_string())?
+        .remove(request_id);
+    if let Some(removed) = removed {
+        emit_resolved(app, &removed.request, "withdrawn");
+    }
+    Ok(())
+}
+
+fn reject(app: &AppHandle, request_id: &str) -> Result<bool, String> {
+    let removed = app
+        .state::<PairingState>()
+        .requests
+        .lock()
+        .map_err(|e| e.to_string())?
+        .remove(request_id);
+    let Some(pending) = removed else {
+        return Ok(false);
+    };
+
+    let _ = pending.sender.send(auth_fail_message("pairing_rejected")?);
+    let _ = pending.sender.send(Message::Close(Some(
+        tokio_tungstenite::tungstenite::protocol::CloseFrame {
+            code: tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode::from(
+                PAIRING_REJECTED_CLOSE_CODE,
+            ),
+            reason: "".into(),
+        },
+    )));
+
+    println!(
+        "Rejected pairing request from {} ({})",
+        pending.request.device_name, pending.request.ip
+    );
+    audit_request(app, &pending.request, "reject_pairing");
+    emit_resolved(app, &pending.request, "rejected");
+    Ok(true)
+}
+
+fn expire_requests(app: &AppHandle, state: &PairingState) -> Result<(), String> {
+    let now = now_ts();
+    let expired = {
+        let mut requests = state.requests.lock().map_err(|e| e.to_string())?;
+        let request_ids = requests
+            .iter()
+            .filter(|(_, pending)| pending.request.expires_at < now)
+            .map(|(request_id, _)| request_id.clone())
+            .collect::<Vec<_>>();
+        request_ids
+            .iter()
+            .filter_map(|request_id| requests.remove(request_id))
+            .collect::<Vec<_>>()
+    };
+
+    for pending in expired {
+        let _ = pending.sender.send(auth_fail_message("pairing_expired")?);
+        emit_resolved(app, &pending.request, "expired");
+    }
+    Ok(())
+}
+
+fn issue_token(
+    state: &PairingState,
+    token: String,
+    kind: PairingKind,
+    lifetime_secs: u64,
+    device_id: Option<String>,
+) -> Result<RegistrationToken, String> {
+    let created_at = now_ts();
+    let token = RegistrationToken {
+        token,
+        kind,
+        created_at,
+        expires_at: created_at + lifetime_secs,
+        is_used: false,
+        device_id,
+    };
+
+    let mut tokens = state.tokens.lock().map_err(|e| e.to_string())?;
+    prune_tokens(&mut tokens, created_at);
+    if tokens.len() >= MAX_PAIRING_TOKENS {
+        tokens.remove(0);
+    }
+    tokens.push(token.clone());
+    Ok(token)
+}
+
+fn prune_tokens(tokens: &mut Vec<RegistrationToken>, now: u64) {
+    tokens.retain(|token| !token.is_used && token.expires_at >= now);
+}
+
+/// A wrong PIN counts towards the lockout; after too many, every PIN is
+/// invalidated so the operator has to hand out a new one.
+fn record_failed_pin(app: &AppHandle, state: &PairingState) -> Result<(), String> {
+    let mut failed = state
+        .failed_pin_attempts
+        .lock()
+        .map_err(|e| e.to_string())?;
+    *failed += 1;
+    if *failed < MAX_FAILED_PIN_ATTEMPTS {
+        return Ok(());
+    }
+    *failed = 0;
+    drop(failed);
+
+    state
+        .tokens
+        .lock()
+        .map_err(|e| e.to_string())?
+        .retain(|token| token.kind != PairingKind::Pin);
+    eprintln!(
+        "Invalidated pairing PINs after {} wrong attempts",
+        MAX_FAILED_PIN_ATTEMPTS
+    );
+    audit::record(
+        app,
+        AuditActor::desktop(),
+        audit::PAIRING,
+        "invalidate_pins",
+        &format!("{} wrong PINs", MAX_FAILED_PIN_ATTEMPTS),
+        AuditOutcome::Ok,
+    );
+    let _ = app.emit("pairing_pins_invalidated", MAX_FAILED_PIN_ATTEMPTS);
+    Ok(())
+}
+
+fn random_pin() -> String {
+    let value = u128::from_le_bytes(*Uuid::new_v4().as_bytes());
+    format!(
+        "{:0width$}",
+        value % 10u128.pow(PIN_DIGITS as u32),
+        width = PIN_DIGITS
+    )
+}
+
+fn is_pin(token: &str) -> bool {
+    token.len() == PIN_DIGITS && token.bytes().all(|byte| byte.is_ascii_digit())
+}
+
+fn audit_request(app: &AppHandle, request: &PairingRequest, action: &str) {
+    audit::record(
+        app,
+        AuditActor {
+            device_id: Some(&request.device_id),
+            session_id: None,
+            peer: Some(&request.ip),
+        },
+        audit::PAIRING,
+        action,
+        &format!(
+            "{} ({} {}, {})",
+            request.device_name, request.device_type, request.os, request.version
+        ),
+        AuditOutcome::Ok,
+    );
+}
+
+fn emit_resolved(app: &AppHandle, request: &PairingRequest, outcome: &'static str) {
+    let _ = app.emit(
+        "pairing_request_resolved",
+        PairingResolvedEvent {
+            request_id: request.request_id.clone(),
+            device_id: request.device_id.clone(),
+            outcome,
+        },
+    );
+}
+
+fn json_message<T: Serialize>(payload: &T) -> Result<Message, String> {
+    serde_json::to_string(payload)
+        .map(Message::Text)
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,656 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    sync::Mutex,
+};
+
+use rusqlite::{Connection, params};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, State};
+
+use crate::devices::{open_device_db, refresh_role_permissions};
+use crate::follower::FOLLOWER_DEVICE_TYPE;
+use crate::protocol::ClientMessage;
+
+const PERMISSION_TABLES_SQL: &str = r#"
+CREATE TABLE IF NOT EXISTS permission_roles (
+    role_id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    scopes TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS device_scopes (
+    device_id TEXT NOT NULL,
+    scope TEXT NOT NULL,
+    PRIMARY KEY (device_id, scope)
+);
+"#;
+
+/// The boolean columns permissions used to live in, and the scope each one
+/// becomes.
+const LEGACY_PERMISSION_COLUMNS: &[(&str, &str)] = &[
+    ("permissions_player", "player"),
+    ("permissions_lyrics", "lyrics"),
+    ("permissions_bible", "bible"),
+    ("permissions_media", "media"),
+    ("permissions_streaming", "streaming"),
+];
+
+/// Areas that gate which broadcasts a device receives. Holding any scope in
+/// an area is enough to see it.
+const AREAS: &[&str] = &["player", "lyrics", "bible", "media", "streaming"];
+
+const BUILT_IN_SCOPES: &[(&str, &str)] = &[
+    ("player", "Full player control"),
+    ("player.view", "See what is playing"),
+    ("player.transport", "Play, pause, stop, next and previous"),
+    ("player.seek", "Seek and report progress"),
+    ("player.volume", "Change volume and mute"),
+    ("player.loop", "Toggle looping"),
+    ("player.load", "Load media and edit its metadata"),
+    ("lyrics", "Full lyrics control"),
+    ("lyrics.view", "Receive lyric slides"),
+    ("lyrics.load", "Load lyrics"),
+    ("lyrics.slide", "Jump to a lyric slide"),
+    ("lyrics.language", "Choose the lyric language shown"),
+    ("bible", "Full Bible control"),
+    ("bible.view", "Search and receive Bible passages"),
+    ("bible.show", "Show Bible passages on screen"),
+    ("media", "Full media library control"),
+    ("media.view", "Browse the media library"),
+    ("media.queue", "Add library items to the queue"),
+    ("media.show", "Play or present library items"),
+    ("media.upload", "Upload files to the media library"),
+    ("streaming", "Every stream"),
+    ("streaming.preview", "Watch the preview stream"),
+    (
+        "streaming.app_preview",
+        "Watch the desktop app preview stream",
+    ),
+    ("streaming.main", "Watch the main output stream"),
+    ("streaming.mobile", "Send the phone camera to the desktop"),
+    (
+        "streaming.mobile_preview",
+        "Watch the phone camera relayed by the desktop",
+    ),
+];
+
+const BUILT_IN_ROLES: &[(&str, &str, &[&str])] = &[
+    ("clicker", "Clicker", &["player.transport", "lyrics.view"]),
+    (
+        "worship_leader",
+        "Worship leader",
+        &["player", "lyrics", "bible", "media"],
+    ),
+    (
+        "tech",
+        "Tech",
+        &["player", "lyrics", "bible", "media", "streaming"],
+    ),
+    (
+        "viewer",
+        "Viewer",
+        &["player.view", "lyrics.view", "bible.view"],
+    ),
+    (
+        "follower",
+        "Standby follower",
+        &["player.view", "lyrics.view", "media.view"],
+    ),
+];
+
+/// Role given to newly paired devices.
+pub const DEFAULT_ROLE: &str = "worship_leader";
+/// Role given to another Lumen desktop pairing as a follower.
+pub const FOLLOWER_ROLE: &str = "follower";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScopeInfo {
+    pub scope: String,
+    pub description: String,
+    /// Module that registered the scope; `None` for built-in scopes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub module_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PermissionRole {
+    pub role_id: String,
+    pub name: String,
+    pub scopes: BTreeSet<String>,
+    #[serde(default, skip_deserializing)]
+    pub built_in: bool,
+}
+
+/// What a device may do: the scopes of its role plus any granted directly.
+/// A scope also grants everything below it, so `player` covers
+/// `player.seek`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DevicePermissions {
+    #[serde(default)]
+    pub role: Option<String>,
+    #[serde(default)]
+    pub scopes: BTreeSet<String>,
+    /// Effective scopes, derived from `role` and `scopes`.
+    #[serde(default, skip_deserializing)]
+    pub granted: BTreeSet<String>,
+    /// Derived per-area flags kept for clients that predate scopes.
+    #[serde(default, skip_deserializing)]
+    pub player: bool,
+    #[serde(default, skip_deserializing)]
+    pub lyrics: bool,
+    #[serde(default, skip_deserializing)]
+    pub bible: bool,
+    #[serde(default, skip_deserializing)]
+    pub media: bool,
+    #[serde(default, skip_deserializing)]
+    pub streaming: bool,
+}
+
+impl Default for DevicePermissions {
+    fn default() -> Self {
+        let mut permissions = Self::new(Some(DEFAULT_ROLE.to_string()), BTreeSet::new());
+        permissions.resolve(&built_in_roles());
+        permissions
+    }
+}
+
+impl DevicePermissions {
+    /// What a device gets when it first pairs: followers mirror the desktop
+    /// and only need to see it, everything else starts with the default role.
+    pub fn for_new_device(device_type: &str) -> Self {
+        if device_type != FOLLOWER_DEVICE_TYPE {
+            return Self::default();
+        }
+        let mut permissions = Self::new(Some(FOLLOWER_ROLE.to_string()), BTreeSet::new());
+        permissions.resolve(&built_in_roles());
+        permissions
+    }
+
+    pub fn new(role: Option<String>, scopes: BTreeSet<String>) -> Self {
+        Self {
+            role,
+            scopes,
+            granted: BTreeSet::new(),
+            player: false,
+            lyrics: false,
+            bible: false,
+            media: false,
+            streaming: false,
+        }
+    }
+
+    /// Recomputes `granted` and the legacy flags. A role missing from
+    /// `roles` contributes nothing.
+    pub fn resolve(&mut self, roles: &BTreeMap<String, PermissionRole>) {
+        let mut granted = self.scopes.clone();
+        if let Some(role) = self.role.as_ref().and_then(|role_id| roles.get(role_id)) {
+            granted.extend(role.scopes.iter().cloned());
+        }
+        self.granted = granted;
+
+        self.player = can_receive(self, "player");
+        self.lyrics = can_receive(self, "lyrics");
+        self.bible = can_receive(self, "bible");
+        self.media = can_receive(self, "media");
+        self.streaming = can_receive(self, "streaming");
+    }
+}
+
+/// Known scopes and roles. Built-ins are fixed; modules add scopes at
+/// runtime and custom roles are stored in `lumen.db`.
+pub struct PermissionState {
+    scopes: Mutex<BTreeMap<String, ScopeInfo>>,
+    roles: Mutex<BTreeMap<String, PermissionRole>>,
+}
+
+pub fn default_permission_state() -> PermissionState {
+    let scopes = BUILT_IN_SCOPES
+        .iter()
+        .map(|(scope, description)| {
+            (
+                scope.to_string(),
+                ScopeInfo {
+                    scope: scope.to_string(),
+                    description: description.to_string(),
+                    module_id: None,
+                },
+            )
+        })
+        .collect();
+
+    PermissionState {
+        scopes: Mutex::new(scopes),
+        roles: Mutex::new(built_in_roles()),
+    }
+}
+
+#[tauri::command]
+pub fn get_permission_scopes(state: State<'_, PermissionState>) -> Result<Vec<ScopeInfo>, String> {
+    let scopes = state.scopes.lock().map_err(|e| e.to_string())?;
+    Ok(scopes.values().cloned().collect())
+}
+
+/// Lets a module declare a scope devices can be granted. Module scopes live
+/// under the module id, e.g. `setlist.edit` for the `setlist` module.
+#[tauri::command]
+pub fn register_permission_scope(
+    state: State<'_, PermissionState>,
+    module_id: String,
+    scope: String,
+    description: String,
+) -> Result<ScopeInfo, String> {
+    let scope = scope.trim().to_lowercase();
+    if !is_valid_scope(&scope) {
+        return Err(format!("Invalid scope name: {}", scope));
+    }
+    if AREAS.contains(&module_id.as_str()) {
+        return Err(format!("Module id `{}` is reserved", module_id));
+    }
+    if !scope_allows(&module_id, &scope) {
+        return Err(format!("Module scopes must start with `{}.`", module_id));
+    }
+
+    let info = ScopeInfo {
+        scope: scope.clone(),
+        description: description.trim().to_string(),
+        module_id: Some(module_id),
+    };
+    let mut scopes = state.scopes.lock().map_err(|e| e.to_string())?;
+    scopes.insert(scope, info.clone());
+    Ok(info)
+}
+
+#[tauri::command]
+pub fn get_permission_roles(
+    state: State<'_, PermissionState>,
+) -> Result<Vec<PermissionRole>, String> {
+    let roles = state.roles.lock().map_err(|e| e.to_string())?;
+    Ok(roles.values().cloned().collect())
+}
+
+/// Creates or updates a custom role and re-applies it to devices using it.
+#[tauri::command]
+pub fn save_permission_role(
+    app: AppHandle,
+    state: State<'_, PermissionState>,
+    role: PermissionRole,
+) -> Result<PermissionRole, String> {
+    let role_id = role.role_id.trim().to_lowercase();
+    if !is_valid_scope(&role_id) || role_id.contains('.') {
+        return Err(format!("Invalid role id: {}", role_id));
+    }
+    if is_built_in_role(&role_id) {
+        return Err(format!("Built-in role `{}` cannot be changed", role_id));
+    }
+    let name = role.name.trim().to_string();
+    if name.is_empty() {
+        return Err("Role name cannot be empty".to_string());
+    }
+    validate_scopes(&state, &role.scopes)?;
+
+    let role = PermissionRole {
+        role_id,
+        name,
+        scopes: role.scopes,
+        built_in: false,
+    };
+    store_role(&role)?;
+
+    let roles = {
+        let mut roles = state.roles.lock().map_err(|e| e.to_string())?;
+        roles.insert(role.role_id.clone(), role.clone());
+        roles.clone()
+    };
+    refresh_role_permissions(&app, &role.role_id, &roles)?;
+    Ok(role)
+}
+
+/// Deletes a custom role. Devices that used it keep only their direct scopes.
+#[tauri::command]
+pub fn delete_permission_role(
+    app: AppHandle,
+    state: State<'_, PermissionState>,
+    role_id: String,
+) -> Result<(), String> {
+    if is_built_in_role(&role_id) {
+        return Err(format!("Built-in role `{}` cannot be deleted", role_id));
+    }
+
+    open_device_db()?
+        .execute(
+            "DELETE FROM permission_roles WHERE role_id = ?1",
+            [&role_id],
+        )
+        .map_err(|e| e.to_string())?;
+
+    let roles = {
+        let mut roles = state.roles.lock().map_err(|e| e.to_string())?;
+        roles.remove(&role_id);
+        roles.clone()
+    };
+    refresh_role_permissions(&app, &role_id, &roles)
+}
+
+/// Creates the permission tables. The first time, every device's legacy
+/// `permissions_*` columns are copied into `device_scopes`.
+pub fn ensure_permission_tables(connection: &Connection) -> Result<(), String> {
+    let migrated = connection
+        .query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'device_scopes'",
+            [],
+            |row| row.get::<_, i64>(0),
+        )
+        .map_err(|e| e.to_string())?
+        > 0;
+
+    connection
+        .execute_batch(PERMISSION_TABLES_SQL)
+        .map_err(|e| e.to_string())?;
+
+    if migrated {
+        return Ok(());
+    }
+
+    for (column, scope) in LEGACY_PERMISSION_COLUMNS {
+        connection
+            .execute(
+                &format!(
+                    "INSERT OR IGNORE INTO device_scopes (device_id, scope) \
+                     SELECT device_id, ?1 FROM devices WHERE {} = 1",
+                    column
+                ),
+                [scope],
+            )
+            .map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
+}
+
+/// Built-in roles plus the custom roles stored in the database.
+pub fn load_roles(connection: &Connection) -> Result<BTreeMap<String, PermissionRole>, String> {
+    let mut roles = built_in_roles();
+    let mut statement = connection
+        .prepare("SELECT role_id, name, scopes FROM permission_roles")
+        .map_err(|e| e.to_string())?;
+    let rows = statement
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?;
+
+    for row in rows {
+        let (role_id, name, scopes) = row.map_err(|e| e.to_string())?;
+        let scopes = serde_json::from_str(&scopes)
+            .map_err(|e| format!("Role `{}` has unreadable scopes: {}", role_id, e))?;
+        roles.insert(
+            role_id.clone(),
+            PermissionRole {
+                role_id,
+                name,
+                scopes,
+                built_in: false,
+            },
+        );
+    }
+
+    Ok(roles)
+}
+
+/// Adds a custom role carried over from another desktop. Its scopes must be
+/// known here, so module scopes need their module loaded first.
+pub fn import_role(state: &PermissionState, role: PermissionRole) -> Result<(), String> {
+    if is_built_in_role(&role.role_id) {
+        return Err(format!(
+            "Built-in role `{}` cannot be changed",
+            role.role_id
+        ));
+    }
+    validate_scopes(state, &role.scopes)
+        .map_err(|reason| format!("Role `{}`: {}", role.role_id, reason))?;
+    let role = PermissionRole {
+        built_in: false,
+        ..role
+    };
+    store_role(&role)?;
+    state
+        .roles
+        .lock()
+        .map_err(|e| e.to_string())?
+        .insert(role.role_id.clone(), role);
+    Ok(())
+}
+
+pub fn set_roles(
+    state: &PermissionState,
+    roles: BTreeMap<String, PermissionRole>,
+) -> Result<(), String> {
+    *state.roles.lock().map_err(|e| e.to_string())? = roles;
+    Ok(())
+}
+
+pub fn roles(state: &PermissionState) -> Result<BTreeMap<String, PermissionRole>, String> {
+    state
+        .roles
+        .lock()
+        .map(|roles| roles.clone())
+        .map_err(|e| e.to_string())
+}
+
+pub fn load_device_scopes(
+    connection: &Connection,
+) -> Result<HashMap<String, BTreeSet<String>>, String> {
+    let mut statement = connection
+        .prepare("SELECT device_id, scope FROM device_scopes")
+        .map_err(|e| e.to_string())?;
+    let rows = statement
+        .query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })
+        .map_err(|e| e.to_string())?;
+
+    let mut scopes: HashMap<String, BTreeSet<String>> = HashMap::new();
+    for row in rows {
+        let (device_id, scope) = row.map_err(|e| e.to_string())?;
+        scopes.entry(device_id).or_default().insert(scope);
+    }
+    Ok(scopes)
+}
+
+pub fn save_device_scopes(
+    connection: &Connection,
+    device_id: &str,
+    scopes: &BTreeSet<String>,
+) -> Result<(), String> {
+    delete_device_scopes(connection, device_id)?;
+    for scope in scopes {
+        connection
+            .execute(
+                "INSERT INTO device_scopes (device_id, scope) VALUES (?1, ?2)",
+                params![device_id, scope],
+            )
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+pub fn delete_device_scopes(connection: &Connection, device_id: &str) -> Result<(), String> {
+    connection
+        .execute(
+            "DELETE FROM device_scopes WHERE device_id = ?1",
+            [device_id],
+        )
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Checks that `permissions` names a known role and known scopes, then
+/// resolves it.
+pub fn prepare_permissions(
+    state: &PermissionState,
+    mut permissions: DevicePermissions,
+) -> Result<DevicePermissions, String> {
+    let roles = roles(state)?;
+    if let Some(role_id) = permissions
+        .role
+        .as_ref()
+        .filter(|role_id| !roles.contains_key(*role_id))
+    {
+        return Err(format!("Unknown role: {}", role_id));
+    }
+    validate_scopes(state, &permissions.scopes)?;
+    permissions.resolve(&roles);
+    Ok(permissions)
+}
+
+/// Whether holding `granted` allows `required`: the same scope or one of its
+/// ancestors.
+pub fn scope_allows(granted: &str, required: &str) -> bool {
+    granted == required
+        || required
+            .strip_prefix(granted)
+            .is_some_and(|rest| rest.starts_with('.'))
+}
+
+/// Whether the device may perform an action that needs `scope`.
+pub fn is_permission_allowed(permissions: &DevicePermissions, scope: &str) -> bool {
+    permissions
+        .granted
+        .iter()
+        .any(|granted| scope_allows(granted, scope))
+}
+
+/// Whether the device should receive broadcasts about `area`: it holds the
+/// area itself or any scope inside it.
+pub fn can_receive(permissions: &DevicePermissions, area: &str) -> bool {
+    permissions
+        .granted
+        .iter()
+        .any(|granted| scope_allows(granted, area) || scope_allows(area, granted))
+}
+
+/// Whether the device may take the control lock for `area`: it holds a
+/// scope in it beyond `.view`.
+pub fn can_control(permissions: &DevicePermissions, area: &str) -> bool {
+    permissions.granted.iter().any(|granted| {
+        (scope_allows(granted, area) || scope_allows(area, granted)) && !granted.ends_with(".view")
+    })
+}
+
+/// Scope a client message needs, or `None` when any paired device may send
+/// it.
+pub fn required_scope(message: &ClientMessage) -> Option<String> {
+    let scope = match message {
+        ClientMessage::PlayPause
+        | ClientMessage::Stop
+        | ClientMessage::Next
+        | ClientMessage::Previous => "player.transport",
+        ClientMessage::Seek { .. } | ClientMessage::Progress { .. } => "player.seek",
+        ClientMessage::SetVolume { .. } | ClientMessage::Mute => "player.volume",
+        ClientMessage::SetLoop { .. } => "player.loop",
+        ClientMessage::LoadUrl { .. } | ClientMessage::Metadata { .. } => "player.load",
+        ClientMessage::LoadLyric { .. } => "lyrics.load",
+        ClientMessage::LyricsSlide { .. } => "lyrics.slide",
+        ClientMessage::SetLyricsLanguage { .. } => "lyrics.language",
+        ClientMessage::SubscribeStream { stream_type }
+        | ClientMessage::UnsubscribeStream { stream_type }
+        | ClientMessage::WebrtcAnswer { stream_type, .. }
+        | ClientMessage::WebrtcIceCandidate { stream_type, .. } => {
+            return Some(format!("streaming.{}", stream_type));
+        }
+        ClientMessage::MobileOffer { .. } => "streaming.mobile",
+        ClientMessage::BibleTranslations | ClientMessage::BibleSearch { .. } => "bible.view",
+        ClientMessage::BibleShow { .. } => "bible.show",
+        ClientMessage::MediaBrowse { .. } => "media.view",
+        ClientMessage::LyricsBrowse { .. } => "lyrics.view",
+        ClientMessage::MediaQueue { .. } => "media.queue",
+        ClientMessage::MediaShow { .. } => "media.show",
+        _ => return None,
+    };
+    Some(scope.to_string())
+}
+
+fn built_in_roles() -> BTreeMap<String, PermissionRole> {
+    BUILT_IN_ROLES
+        .iter()
+        .map(|(role_id, name, scopes)| {
+            (
+                role_id.to_string(),
+                PermissionRole {
+                    role_id: role_id.to_string(),
+                    name: name.to_string(),
+                    scopes: scopes.iter().map(|scope| scope.to_string()).collect(),
+                    built_in: true,
+                },
+            )
+        })
+        .collect()
+}
+
+fn is_built_in_role(role_id: &str) -> bool {
+    BUILT_IN_ROLES.iter().any(|(id, _, _)| *id == role_id)
+}
+
+fn validate_scopes(state: &PermissionState, scopes: &BTreeSet<String>) -> Result<(), String> {
+    let known = state.scopes.lock().map_err(|e| e.to_string())?;
+    match scopes.iter().find(|scope| !known.contains_key(*scope)) {
+        Some(scope) => Err(format!("Unknown scope: {}", scope)),
+        None => Ok(()),
+    }
+}
+
+/// Lowercase dot-separated segments of letters, digits, `_` and `-`.
+fn is_valid_scope(scope: &str) -> bool {
+    !scope.is_empty()
+        && scope.split('.').all(|segment| {
+            !segment.is_empty()
+                && segment
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-')
+        })
+}
+
+fn store_role(role: &PermissionRole) -> Result<(), String> {
+    let scopes_json = serde_json::to_string(&role.scopes).map_err(|e| e.to_string())?;
+    open_device_db()?
+        .execute(
+            "INSERT OR REPLACE INTO permission_roles (role_id, name, scopes) VALUES (?1, ?2, ?3)",
+            params![role.role_id, role.name, scopes_json],
+        )
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_stream_type_needs_a_built_in_scope() {
+        for stream_type in ["preview", "app_preview", "main", "mobile", "mobile_preview"] {
+            let message = ClientMessage::SubscribeStream {
+                stream_type: stream_type.to_string(),
+            };
+            let scope = required_scope(&message).unwrap();
+            assert!(
+                BUILT_IN_SCOPES.iter().any(|(known, _)| *known == scope),
+                "no built-in scope for {}",
+                scope
+            );
+        }
+    }
+
+    #[test]
+    fn scopes_cover_their_descendants_only() {
+        assert!(scope_allows("streaming", "streaming.mobile_preview"));
+        assert!(scope_allows("streaming.mobile", "streaming.mobile"));
+        assert!(!scope_allows(
+            "streaming.mobile",
+            "streaming.mobile_preview"
+        ));
+        assert!(!scope_allows("player.seek", "player"));
+    }
+}
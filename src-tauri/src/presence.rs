@@ -0,0 +1,166 @@
+use std::time::Duration;
+
+use serde::Serialize;
+use serde_json::json;
+use tauri::{AppHandle, Emitter, Manager, State, async_runtime};
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::control_lock;
+use crate::devices::{
+    DeviceState, RemoteAccessSettings, RemoteSyncEnvelope, WebSocketSession,
+    broadcast_remote_event_inner, close_message, now_ts, set_heartbeat_setting,
+};
+use crate::streaming::handle_session_closed;
+
+/// Close code sent to sessions that missed their heartbeats.
+pub const IDLE_TIMEOUT_CLOSE_CODE: u16 = 4008;
+
+const MIN_HEARTBEAT_INTERVAL_SECS: u64 = 5;
+const MAX_HEARTBEAT_INTERVAL_SECS: u64 = 120;
+const MAX_SESSION_TIMEOUT_SECS: u64 = 600;
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PresenceReason {
+    Connected,
+    Disconnected,
+    TimedOut,
+}
+
+/// Payload of `device_presence_changed`, emitted to the UI and broadcast to
+/// remote devices on the `devices` topic.
+#[derive(Debug, Clone, Serialize)]
+pub struct DevicePresence {
+    pub device_id: String,
+    pub session_id: String,
+    pub online: bool,
+    pub reason: PresenceReason,
+    pub at: u64,
+}
+
+/// Changes how often sessions are pinged and how long they may stay silent.
+/// The timeout must cover at least two heartbeats.
+#[tauri::command]
+pub fn set_session_heartbeat(
+    state: State<'_, DeviceState>,
+    heartbeat_interval_secs: u64,
+    session_timeout_secs: u64,
+) -> Result<RemoteAccessSettings, String> {
+    if !(MIN_HEARTBEAT_INTERVAL_SECS..=MAX_HEARTBEAT_INTERVAL_SECS)
+        .contains(&heartbeat_interval_secs)
+    {
+        return Err(format!(
+            "Heartbeat interval must be between {} and {} seconds",
+            MIN_HEARTBEAT_INTERVAL_SECS, MAX_HEARTBEAT_INTERVAL_SECS
+        ));
+    }
+    if session_timeout_secs < heartbeat_interval_secs * 2
+        || session_timeout_secs > MAX_SESSION_TIMEOUT_SECS
+    {
+        return Err(format!(
+            "Session timeout must be between {} and {} seconds",
+            heartbeat_interval_secs * 2,
+            MAX_SESSION_TIMEOUT_SECS
+        ));
+    }
+
+    set_heartbeat_setting(&state, heartbeat_interval_secs, session_timeout_secs)
+}
+
+pub fn emit_presence(app: &AppHandle, session: &WebSocketSession, reason: PresenceReason) {
+    let presence = DevicePresence {
+        device_id: session.device_id.clone(),
+        session_id: session.session_id.clone(),
+        online: matches!(reason, PresenceReason::Connected),
+        reason,
+        at: now_ts(),
+    };
+
+    let _ = app.emit("device_presence_changed", presence.clone());
+
+    if let Ok(payload) = serde_json::to_value(&presence) {
+        let envelope = RemoteSyncEnvelope {
+            event: "device_presence_changed".to_string(),
+            payload,
+        };
+        let _ = broadcast_remote_event_inner(&app.state::<DeviceState>(), &envelope, None);
+    }
+}
+
+/// Pings every remote session each heartbeat and closes the ones that have
+/// been silent for longer than the session timeout.
+pub fn start_session_reaper(app: AppHandle) {
+    async_runtime::spawn(async move {
+        loop {
+            let (interval, timeout) = heartbeat_settings(&app);
+            tokio::time::sleep(Duration::from_secs(interval)).await;
+
+            ping_sessions(&app);
+            control_lock::expire_locks(&app);
+            for session in take_stale_sessions(&app, timeout) {
+                println!(
+                    "Closing idle session {} of device {}",
+                    session.session_id, session.device_id
+                );
+                if let Some(sender) = &session.sender {
+                    let _ = sender.send(Message::Text(
+                        json!({ "event": "session_timeout", "timeout_secs": timeout }).to_string(),
+                    ));
+                    let _ = sender.send(close_message(IDLE_TIMEOUT_CLOSE_CODE));
+                }
+                session.closer.notify_one();
+                let _ = handle_session_closed(&app, &session.session_id).await;
+                emit_presence(&app, &session, PresenceReason::TimedOut);
+            }
+        }
+    });
+}
+
+fn heartbeat_settings(app: &AppHandle) -> (u64, u64) {
+    let defaults = RemoteAccessSettings::default();
+    app.state::<DeviceState>()
+        .remote_settings
+        .lock()
+        .map(|settings| {
+            (
+                settings.heartbeat_interval_secs,
+                settings.session_timeout_secs,
+            )
+        })
+        .unwrap_or((
+            defaults.heartbeat_interval_secs,
+            defaults.session_timeout_secs,
+        ))
+}
+
+fn ping_sessions(app: &AppHandle) {
+    let state = app.state::<DeviceState>();
+    let Ok(sessions) = state.sessions.lock() else {
+        return;
+    };
+
+    for session in sessions.values() {
+        if let Some(sender) = &session.sender {
+            let _ = sender.send(Message::Ping(Vec::new()));
+        }
+    }
+}
+
+fn take_stale_sessions(app: &AppHandle, timeout: u64) -> Vec<WebSocketSession> {
+    let state = app.state::<DeviceState>();
+    let Ok(mut sessions) = state.sessions.lock() else {
+        return Vec::new();
+    };
+
+    let cutoff = now_ts().saturating_sub(timeout);
+    let stale = sessions
+        .iter()
+        .filter(|(_, session)| session.last_activity_at < cutoff)
+        .map(|(session_id, _)| session_id.clone())
+        .collect::<Vec<_>>();
+
+    stale
+        .into_iter()
+        .filter_map(|session_id| sessions.remove(&session_id))
+        .collect()
+}
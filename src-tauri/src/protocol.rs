@@ -0,0 +1,555 @@
+use schemars::{JsonSchema, schema_for};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use tokio::sync::mpsc::UnboundedSender;
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::streaming::WebRtcIceCandidatePayload;
+use crate::topics::Topic;
+
+/// Version of the remote-control protocol spoken by this desktop. Clients
+/// send theirs in `register`/`auth`; omitting it means version 1.
+pub const PROTOCOL_VERSION: u32 = 1;
+pub const MIN_PROTOCOL_VERSION: u32 = 1;
+
+/// Messages a client may send over the control socket, tagged by `event`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ClientMessage {
+    /// Pairs a new device using the token from the desktop QR code or a
+    /// PIN. Without a token the device asks the operator for approval.
+    Register {
+        #[serde(default)]
+        token: String,
+        device_id: String,
+        device_name: String,
+        device_type: String,
+        os: String,
+        version: String,
+        #[serde(default)]
+        protocol_version: Option<u32>,
+    },
+    /// Reconnects a paired device.
+    Auth {
+        device_id: String,
+        access_token: String,
+        #[serde(default)]
+        protocol_version: Option<u32>,
+    },
+    ForgetDevice,
+    /// `null` or `"all"` shows every language of a slide.
+    SetLyricsLanguage {
+        #[serde(default)]
+        language: Option<String>,
+    },
+    SubscribeStream {
+        stream_type: String,
+    },
+    UnsubscribeStream {
+        stream_type: String,
+    },
+    WebrtcAnswer {
+        stream_type: String,
+        sdp: String,
+    },
+    WebrtcIceCandidate {
+        stream_type: String,
+        candidate: WebRtcIceCandidatePayload,
+    },
+    MobileOffer {
+        sdp: String,
+        #[serde(default)]
+        video_orientation: Option<String>,
+    },
+    /// Volume from 0 to 100.
+    SetVolume {
+        value: f64,
+    },
+    /// Position in seconds.
+    Seek {
+        value: f64,
+    },
+    Metadata {
+        #[serde(default)]
+        title: Option<String>,
+        #[serde(default)]
+        url: Option<String>,
+        #[serde(default)]
+        artist: Option<String>,
+    },
+    Progress {
+        value: f64,
+        #[serde(default)]
+        duration: Option<f64>,
+    },
+    Mute,
+    PlayPause,
+    Stop,
+    /// `value` is the start position in seconds.
+    LoadUrl {
+        url: String,
+        #[serde(default)]
+        value: Option<f64>,
+    },
+    LoadLyric {
+        url: String,
+    },
+    /// Jumps to a slide of the loaded lyrics, counting from 0.
+    LyricsSlide {
+        index: usize,
+    },
+    /// Any non-zero `value` enables looping.
+    SetLoop {
+        #[serde(default)]
+        value: Option<f64>,
+    },
+    Next,
+    Previous,
+    ManualPause,
+    /// Asks for a fresh `state_snapshot`, e.g. after a `state_patch` gap.
+    GetState,
+    /// Adds broadcast topics. `throttle_ms` limits how often high-frequency
+    /// topics such as `player.progress` are delivered; `0` removes it.
+    Subscribe {
+        topics: Vec<Topic>,
+        #[serde(default)]
+        throttle_ms: Option<u64>,
+    },
+    Unsubscribe {
+        topics: Vec<Topic>,
+    },
+    /// Keeps the session alive for clients that cannot answer WebSocket
+    /// pings; any other message does the same.
+    Heartbeat,
+    BibleTranslations,
+    /// `query` is a reference such as `John 3:16-18` or words to find in
+    /// verse text. Defaults to the first installed translation.
+    BibleSearch {
+        query: String,
+        #[serde(default)]
+        translation: Option<String>,
+        #[serde(default)]
+        limit: Option<usize>,
+    },
+    /// Shows a passage of at most 30 verses on every output.
+    BibleShow {
+        reference: String,
+        #[serde(default)]
+        translation: Option<String>,
+    },
+    /// One page of the media library, sorted by name.
+    MediaBrowse {
+        #[serde(default)]
+        media_type: Option<String>,
+        #[serde(default)]
+        query: Option<String>,
+        #[serde(default)]
+        offset: Option<u32>,
+        #[serde(default)]
+        limit: Option<u32>,
+    },
+    /// One page of the lyrics in the library, sorted by name. Queue or show
+    /// one with `media_queue` or `media_show`.
+    LyricsBrowse {
+        #[serde(default)]
+        query: Option<String>,
+        #[serde(default)]
+        offset: Option<u32>,
+        #[serde(default)]
+        limit: Option<u32>,
+    },
+    /// Appends a library item to the desktop queue.
+    MediaQueue {
+        media_id: i64,
+    },
+    /// Plays or presents a library item right away.
+    MediaShow {
+        media_id: i64,
+    },
+    /// Takes the control lock for `scope` (`player`, `lyrics`, `bible` or
+    /// `media`), or renews it. Other devices cannot change that scope until
+    /// it is released or `lease_secs` pass without a command from this one.
+    RequestControl {
+        scope: String,
+        #[serde(default)]
+        lease_secs: Option<u64>,
+    },
+    ReleaseControl {
+        scope: String,
+    },
+}
+
+impl ClientMessage {
+    /// Every `event` tag, in declaration order. A test checks it against the
+    /// enum's schema.
+    pub const EVENTS: &[&str] = &[
+        "register",
+        "auth",
+        "forget_device",
+        "set_lyrics_language",
+        "subscribe_stream",
+        "unsubscribe_stream",
+        "webrtc_answer",
+        "webrtc_ice_candidate",
+        "mobile_offer",
+        "set_volume",
+        "seek",
+        "metadata",
+        "progress",
+        "mute",
+        "play_pause",
+        "stop",
+        "load_url",
+        "load_lyric",
+        "lyrics_slide",
+        "set_loop",
+        "next",
+        "previous",
+        "manual_pause",
+        "get_state",
+        "subscribe",
+        "unsubscribe",
+        "heartbeat",
+        "bible_translations",
+        "bible_search",
+        "bible_show",
+        "media_browse",
+        "lyrics_browse",
+        "media_queue",
+        "media_show",
+        "request_control",
+        "release_control",
+    ];
+
+    pub fn event_name(&self) -> &'static str {
+        match self {
+            Self::Register { .. } => "register",
+            Self::Auth { .. } => "auth",
+            Self::ForgetDevice => "forget_device",
+            Self::SetLyricsLanguage { .. } => "set_lyrics_language",
+            Self::SubscribeStream { .. } => "subscribe_stream",
+            Self::UnsubscribeStream { .. } => "unsubscribe_stream",
+            Self::WebrtcAnswer { .. } => "webrtc_answer",
+            Self::WebrtcIceCandidate { .. } => "webrtc_ice_candidate",
+            Self::MobileOffer { .. } => "mobile_offer",
+            Self::SetVolume { .. } => "set_volume",
+            Self::Seek { .. } => "seek",
+            Self::Metadata { .. } => "metadata",
+            Self::Progress { .. } => "progress",
+            Self::Mute => "mute",
+            Self::PlayPause => "play_pause",
+            Self::Stop => "stop",
+            Self::LoadUrl { .. } => "load_url",
+            Self::LoadLyric { .. } => "load_lyric",
+            Self::LyricsSlide { .. } => "lyrics_slide",
+            Self::SetLoop { .. } => "set_loop",
+            Self::Next => "next",
+            Self::Previous => "previous",
+            Self::ManualPause => "manual_pause",
+            Self::GetState => "get_state",
+            Self::Subscribe { .. } => "subscribe",
+            Self::Unsubscribe { .. } => "unsubscribe",
+            Self::Heartbeat => "heartbeat",
+            Self::BibleTranslations => "bible_translations",
+            Self::BibleSearch { .. } => "bible_search",
+            Self::BibleShow { .. } => "bible_show",
+            Self::MediaBrowse { .. } => "media_browse",
+            Self::LyricsBrowse { .. } => "lyrics_browse",
+            Self::MediaQueue { .. } => "media_queue",
+            Self::MediaShow { .. } => "media_show",
+            Self::RequestControl { .. } => "request_control",
+            Self::ReleaseControl { .. } => "release_control",
+        }
+    }
+
+    /// The stream a streaming message refers to, for `stream_error` replies.
+    pub fn stream_type(&self) -> Option<&str> {
+        match self {
+            Self::SubscribeStream { stream_type }
+            | Self::UnsubscribeStream { stream_type }
+            | Self::WebrtcAnswer { stream_type, .. }
+            | Self::WebrtcIceCandidate { stream_type, .. } => Some(stream_type),
+            Self::MobileOffer { .. } => Some("mobile"),
+            _ => None,
+        }
+    }
+}
+
+/// A client message plus the optional `request_id` that asks for `ack`,
+/// `result` and `error` replies.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ClientEnvelope {
+    /// Opaque id chosen by the client and echoed in every reply to this
+    /// message.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    #[serde(flatten)]
+    pub message: ClientMessage,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    MalformedJson,
+    InvalidPayload,
+    UnknownEvent,
+    UnsupportedVersion,
+    PermissionDenied,
+    CommandFailed,
+    Timeout,
+    RateLimited,
+    ControlLocked,
+    Unauthorized,
+}
+
+/// Reply sent when a client message cannot be handled.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ErrorReply {
+    pub event: &'static str,
+    pub code: ErrorCode,
+    pub message: String,
+    /// The `event` of the offending message, when it could be read.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_event: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
+
+impl ErrorReply {
+    pub fn new(code: ErrorCode, message: impl Into<String>, source_event: Option<&str>) -> Self {
+        Self {
+            event: "error",
+            code,
+            message: message.into(),
+            source_event: source_event.map(str::to_string),
+            request_id: None,
+        }
+    }
+
+    pub fn to_message(&self) -> Result<Message, String> {
+        serde_json::to_string(self)
+            .map(Message::Text)
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Sent as soon as a command with a `request_id` has been accepted.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct AckReply {
+    pub event: &'static str,
+    pub request_id: String,
+    pub source_event: &'static str,
+}
+
+/// Sent once a command with a `request_id` has been applied.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ResultReply {
+    pub event: &'static str,
+    pub request_id: String,
+    pub source_event: &'static str,
+    pub data: Value,
+}
+
+/// Replies to a single client message. `ack`, `result` and command errors are
+/// only sent when the client supplied a `request_id`, so older clients see
+/// exactly the replies they always did.
+#[derive(Clone)]
+pub struct Responder {
+    sender: UnboundedSender<Message>,
+    request_id: Option<String>,
+    source_event: &'static str,
+}
+
+impl Responder {
+    pub fn new(
+        sender: UnboundedSender<Message>,
+        request_id: Option<String>,
+        source_event: &'static str,
+    ) -> Self {
+        Self {
+            sender,
+            request_id,
+            source_event,
+        }
+    }
+
+    pub fn request_id(&self) -> Option<&str> {
+        self.request_id.as_deref()
+    }
+
+    pub fn ack(&self) {
+        let Some(request_id) = self.request_id.clone() else {
+            return;
+        };
+        self.send(&AckReply {
+            event: "ack",
+            request_id,
+            source_event: self.source_event,
+        });
+    }
+
+    pub fn result(&self, data: Value) {
+        let Some(request_id) = self.request_id.clone() else {
+            return;
+        };
+        self.send(&ResultReply {
+            event: "result",
+            request_id,
+            source_event: self.source_event,
+            data,
+        });
+    }
+
+    pub fn error(&self, code: ErrorCode, message: impl Into<String>) {
+        if self.request_id.is_none() {
+            return;
+        }
+        self.send_error(ErrorReply::new(code, message, Some(self.source_event)));
+    }
+
+    /// Sends `reply` whether or not a `request_id` was given.
+    pub fn send_error(&self, mut reply: ErrorReply) {
+        reply.request_id = self.request_id.clone();
+        self.send(&reply);
+    }
+
+    fn send<T: Serialize>(&self, payload: &T) {
+        if let Ok(text) = serde_json::to_string(payload) {
+            let _ = self.sender.send(Message::Text(text));
+        }
+    }
+}
+
+/// Parses one text frame, telling apart broken JSON, unknown events and
+/// known events with a bad payload.
+pub fn parse_client_message(text: &str) -> Result<ClientEnvelope, ErrorReply> {
+    let value: Value = serde_json::from_str(text)
+        .map_err(|error| ErrorReply::new(ErrorCode::MalformedJson, error.to_string(), None))?;
+
+    let request_id = match value.get("request_id") {
+        None | Some(Value::Null) => None,
+        Some(Value::String(id)) => Some(id.clone()),
+        Some(_) => {
+            return Err(ErrorReply::new(
+                ErrorCode::InvalidPayload,
+                "`request_id` must be a string",
+                value.get("event").and_then(Value::as_str),
+            ));
+        }
+    };
+    let with_request_id = |mut reply: ErrorReply| {
+        reply.request_id = request_id.clone();
+        reply
+    };
+
+    let Some(event) = value.get("event").and_then(Value::as_str) else {
+        return Err(with_request_id(ErrorReply::new(
+            ErrorCode::InvalidPayload,
+            "Missing string field `event`",
+            None,
+        )));
+    };
+
+    if !ClientMessage::EVENTS.contains(&event) {
+        return Err(with_request_id(ErrorReply::new(
+            ErrorCode::UnknownEvent,
+            format!("Unknown event `{}`", event),
+            Some(event),
+        )));
+    }
+
+    let event = event.to_string();
+    serde_json::from_value(value).map_err(|error| {
+        with_request_id(ErrorReply::new(
+            ErrorCode::InvalidPayload,
+            error.to_string(),
+            Some(&event),
+        ))
+    })
+}
+
+/// Rejects clients speaking a protocol version this desktop does not support.
+pub fn check_protocol_version(version: Option<u32>, event: &str) -> Result<u32, ErrorReply> {
+    let version = version.unwrap_or(MIN_PROTOCOL_VERSION);
+    if (MIN_PROTOCOL_VERSION..=PROTOCOL_VERSION).contains(&version) {
+        return Ok(version);
+    }
+
+    Err(ErrorReply::new(
+        ErrorCode::UnsupportedVersion,
+        format!(
+            "Protocol version {} is not supported; this desktop speaks {} to {}",
+            version, MIN_PROTOCOL_VERSION, PROTOCOL_VERSION
+        ),
+        Some(event),
+    ))
+}
+
+/// JSON Schemas for client messages and the generic replies, for client
+/// developers and code generators.
+#[tauri::command]
+pub fn get_remote_protocol_schema() -> Result<Value, String> {
+    let client_message =
+        serde_json::to_value(schema_for!(ClientEnvelope)).map_err(|e| e.to_string())?;
+    let ack_reply = serde_json::to_value(schema_for!(AckReply)).map_err(|e| e.to_string())?;
+    let result_reply = serde_json::to_value(schema_for!(ResultReply)).map_err(|e| e.to_string())?;
+    let error_reply = serde_json::to_value(schema_for!(ErrorReply)).map_err(|e| e.to_string())?;
+
+    Ok(json!({
+        "protocol_version": PROTOCOL_VERSION,
+        "min_protocol_version": MIN_PROTOCOL_VERSION,
+        "client_message": client_message,
+        "ack_reply": ack_reply,
+        "result_reply": result_reply,
+        "error_reply": error_reply,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error_code(text: &str) -> ErrorCode {
+        parse_client_message(text).unwrap_err().code
+    }
+
+    #[test]
+    fn event_list_matches_the_enum() {
+        let schema = serde_json::to_value(schema_for!(ClientMessage)).unwrap();
+        let mut events = schema["oneOf"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|variant| variant["properties"]["event"]["const"].as_str().unwrap())
+            .collect::<Vec<_>>();
+        events.sort();
+        let mut listed = ClientMessage::EVENTS.to_vec();
+        listed.sort();
+        assert_eq!(events, listed);
+    }
+
+    #[test]
+    fn parse_tells_unknown_events_from_bad_payloads() {
+        assert_eq!(error_code("{"), ErrorCode::MalformedJson);
+        assert_eq!(error_code(r#"{"value":1}"#), ErrorCode::InvalidPayload);
+        assert_eq!(
+            error_code(r#"{"event":"teleport"}"#),
+            ErrorCode::UnknownEvent
+        );
+        assert_eq!(error_code(r#"{"event":"seek"}"#), ErrorCode::InvalidPayload);
+        assert_eq!(
+            error_code(r#"{"event":"subscribe","topics":["weather"]}"#),
+            ErrorCode::InvalidPayload
+        );
+    }
+
+    #[test]
+    fn parse_keeps_the_request_id() {
+        let envelope = parse_client_message(r#"{"event":"mute","request_id":"r1"}"#).unwrap();
+        assert_eq!(envelope.request_id.as_deref(), Some("r1"));
+        assert!(matches!(envelope.message, ClientMessage::Mute));
+
+        let error = parse_client_message(r#"{"event":"seek","request_id":"r2"}"#).unwrap_err();
+        assert_eq!(error.request_id.as_deref(), Some("r2"));
+    }
+}
@@ -0,0 +1,127 @@
+use std::{collections::HashMap, sync::Mutex, time::Duration};
+
+use serde::Deserialize;
+use serde_json::{Value, json};
+use tauri::{AppHandle, Manager, State, async_runtime};
+use tokio::sync::oneshot;
+use uuid::Uuid;
+
+use crate::protocol::{ErrorCode, Responder};
+
+/// How long a remote command waits for the desktop UI to report back.
+const CONFIRMATION_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CommandOutcome {
+    pub ok: bool,
+    #[serde(default)]
+    pub message: Option<String>,
+    #[serde(default)]
+    pub data: Option<Value>,
+}
+
+/// Remote commands forwarded to the UI that are still waiting for
+/// `complete_remote_command`, by command id.
+pub struct RemoteCommandState {
+    pending: Mutex<HashMap<String, oneshot::Sender<CommandOutcome>>>,
+}
+
+pub fn default_remote_command_state() -> RemoteCommandState {
+    RemoteCommandState {
+        pending: Mutex::new(HashMap::new()),
+    }
+}
+
+/// A command registered before its event is emitted. The event payload
+/// carries `id` as `command_id`, and the UI handler that applies it echoes
+/// the id back. Dropping it stops waiting.
+pub struct PendingCommand {
+    app: AppHandle,
+    id: String,
+    receiver: oneshot::Receiver<CommandOutcome>,
+}
+
+impl PendingCommand {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Waits for the UI handler to report back, returning the result data or
+    /// the error to answer with.
+    pub async fn outcome(mut self) -> Result<Value, (ErrorCode, String)> {
+        match tokio::time::timeout(CONFIRMATION_TIMEOUT, &mut self.receiver).await {
+            Ok(Ok(outcome)) if outcome.ok => Ok(outcome.data.unwrap_or_else(|| json!({}))),
+            Ok(Ok(outcome)) => Err((
+                ErrorCode::CommandFailed,
+                outcome
+                    .message
+                    .unwrap_or_else(|| "The desktop could not apply the command".to_string()),
+            )),
+            _ => Err((
+                ErrorCode::Timeout,
+                "The desktop did not confirm the command in time".to_string(),
+            )),
+        }
+    }
+}
+
+impl Drop for PendingCommand {
+    fn drop(&mut self) {
+        let state = self.app.state::<RemoteCommandState>();
+        if let Ok(mut pending) = state.pending.lock() {
+            pending.remove(&self.id);
+        }
+    }
+}
+
+/// Registers a command that will be emitted to the UI with a new
+/// `command_id`.
+pub fn expect_confirmation(app: &AppHandle) -> Result<PendingCommand, String> {
+    let id = Uuid::new_v4().to_string();
+    let (sender, receiver) = oneshot::channel();
+    app.state::<RemoteCommandState>()
+        .pending
+        .lock()
+        .map_err(|e| e.to_string())?
+        .insert(id.clone(), sender);
+
+    Ok(PendingCommand {
+        app: app.clone(),
+        id,
+        receiver,
+    })
+}
+
+/// Called by the UI handler that applied a command with the `command_id`
+/// from its event payload. Events without a `command_id`, such as those from
+/// the desktop itself, OSC or the follower, need no answer.
+#[tauri::command]
+pub fn complete_remote_command(
+    state: State<'_, RemoteCommandState>,
+    command_id: String,
+    outcome: CommandOutcome,
+) -> Result<(), String> {
+    let sender = state
+        .pending
+        .lock()
+        .map_err(|e| e.to_string())?
+        .remove(&command_id);
+
+    if let Some(sender) = sender {
+        let _ = sender.send(outcome);
+    }
+    Ok(())
+}
+
+/// Acknowledges a command that was just emitted to the UI and replies with
+/// `result` or `error` once the handler applying it reports back or the wait
+/// times out.
+pub fn reply_when_confirmed(command: PendingCommand, responder: Responder) {
+    responder.ack();
+    async_runtime::spawn(async move {
+        match command.outcome().await {
+            Ok(data) => responder.result(data),
+            Err((code, message)) => responder.error(code, message),
+        }
+    });
+}
@@ -0,0 +1,734 @@
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+
+use axum::{
+    Json, Router,
+    body::Bytes,
+    extract::{ConnectInfo, Path, Query, State as AxumState},
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+};
+use serde::{Deserialize, de::DeserializeOwned};
+use serde_json::{Value, json};
+use tauri::{AppHandle, Manager};
+
+use crate::audit::{self, AuditActor, AuditOutcome};
+use crate::bible;
+use crate::control_limits::{TokenBucket, control_limits};
+use crate::control_lock::{self, ControlLock};
+use crate::devices::{Device, DeviceState, verify_device_credentials};
+use crate::live_state;
+use crate::media_library::{self, MediaPage};
+use crate::media_upload;
+use crate::permissions::{is_permission_allowed, required_scope};
+use crate::protocol::{ClientMessage, ErrorCode, ErrorReply};
+use crate::thumbnail;
+use crate::websocket::run_confirmed_control_message;
+
+/// Header naming the device whose token is in `Authorization`.
+const DEVICE_ID_HEADER: &str = "x-lumen-device-id";
+const UPLOAD_BEGIN: &str = "media_upload_begin";
+const UPLOAD_COMPLETE: &str = "media_upload_complete";
+const DEFAULT_THUMBNAIL_SIZE: u32 = 200;
+const MIN_THUMBNAIL_SIZE: u32 = 64;
+const MAX_THUMBNAIL_SIZE: u32 = 512;
+
+/// REST paths under `/api/v1/` and the WebSocket event each one runs. The
+/// JSON body carries the event's fields.
+const COMMAND_ROUTES: &[(&str, &str)] = &[
+    ("player/next", "next"),
+    ("player/previous", "previous"),
+    ("player/play_pause", "play_pause"),
+    ("player/stop", "stop"),
+    ("player/mute", "mute"),
+    ("player/volume", "set_volume"),
+    ("player/seek", "seek"),
+    ("player/loop", "set_loop"),
+    ("player/load", "load_url"),
+    ("lyrics/load", "load_lyric"),
+    ("lyrics/slide", "lyrics_slide"),
+    ("bible/show", "bible_show"),
+    ("media/queue", "media_queue"),
+    ("media/show", "media_show"),
+];
+
+#[derive(Clone)]
+struct RestRouteState {
+    app: AppHandle,
+    /// Request budget per device, refilled at the control socket's rate.
+    buckets: Arc<Mutex<HashMap<String, TokenBucket>>>,
+}
+
+/// A request that could not be run, with the HTTP status it maps to.
+struct RestError {
+    status: StatusCode,
+    code: Value,
+    message: String,
+    /// Boxed to keep `Result<_, RestError>` small.
+    lock: Option<Box<ControlLock>>,
+}
+
+impl RestError {
+    fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            status: status_for(code),
+            code: json!(code),
+            message: message.into(),
+            lock: None,
+        }
+    }
+
+    /// An `auth_fail` reason such as `invalid_token`.
+    fn auth(reason: &str) -> Self {
+        Self {
+            status: StatusCode::UNAUTHORIZED,
+            code: json!(reason),
+            message: format!("Authentication failed: {}", reason),
+            lock: None,
+        }
+    }
+
+    fn not_found(message: impl Into<String>) -> Self {
+        Self {
+            status: StatusCode::NOT_FOUND,
+            code: json!("not_found"),
+            message: message.into(),
+            lock: None,
+        }
+    }
+
+    fn locked(lock: ControlLock) -> Self {
+        Self {
+            message: format!("`{}` is locked by {}", lock.scope, lock.device_name),
+            lock: Some(Box::new(lock)),
+            ..Self::new(ErrorCode::ControlLocked, "")
+        }
+    }
+}
+
+impl From<ErrorReply> for RestError {
+    fn from(reply: ErrorReply) -> Self {
+        Self::new(reply.code, reply.message)
+    }
+}
+
+impl IntoResponse for RestError {
+    fn into_response(self) -> Response {
+        let mut error = json!({ "code": self.code, "message": self.message });
+        if let Some(lock) = self.lock {
+            error["lock"] = json!(lock);
+        }
+        (self.status, Json(json!({ "ok": false, "error": error }))).into_response()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct UploadRequest {
+    file_name: String,
+    size: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChunkQuery {
+    offset: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct BrowseQuery {
+    #[serde(default)]
+    media_type: Option<String>,
+    #[serde(default)]
+    query: Option<String>,
+    #[serde(default)]
+    offset: Option<u32>,
+    #[serde(default)]
+    limit: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ThumbnailQuery {
+    #[serde(default)]
+    size: Option<u32>,
+}
+
+/// Authenticated control endpoints, mounted on the HTML server. They run the
+/// same commands, permission checks and control locks as the control
+/// socket, without opening a session.
+pub fn router(app: AppHandle) -> Router {
+    let state = RestRouteState {
+        app,
+        buckets: Arc::new(Mutex::new(HashMap::new())),
+    };
+
+    Router::new()
+        .route("/api/v1/state", get(rest_state))
+        .route("/api/v1/media", get(rest_media_browse))
+        .route("/api/v1/media/:media_id/thumbnail", get(rest_thumbnail))
+        .route("/api/v1/lyrics", get(rest_lyrics_browse))
+        .route("/api/v1/media/uploads", post(rest_upload_begin))
+        .route(
+            "/api/v1/media/uploads/:upload_id",
+            get(rest_upload_progress)
+                .put(rest_upload_chunk)
+                .delete(rest_upload_cancel),
+        )
+        .route(
+            "/api/v1/media/uploads/:upload_id/complete",
+            post(rest_upload_complete),
+        )
+        .route("/api/v1/:area/:action", post(rest_command))
+        .with_state(state)
+}
+
+async fn rest_state(AxumState(state): AxumState<RestRouteState>, headers: HeaderMap) -> Response {
+    let result = authorize(&state, &headers).and_then(|device| {
+        live_state::filtered_snapshot(&state.app, &device.permissions)
+            .map_err(|reason| RestError::new(ErrorCode::CommandFailed, reason))
+    });
+
+    match result {
+        Ok((revision, snapshot, _)) => ok_response(json!({
+            "revision": revision,
+            "state": snapshot,
+        })),
+        Err(error) => error.into_response(),
+    }
+}
+
+async fn rest_command(
+    AxumState(state): AxumState<RestRouteState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path((area, action)): Path<(String, String)>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let path = format!("{}/{}", area, action);
+    let Some(event) = COMMAND_ROUTES
+        .iter()
+        .find(|(route, _)| *route == path)
+        .map(|(_, event)| *event)
+    else {
+        return RestError::new(
+            ErrorCode::UnknownEvent,
+            format!("Unknown command: {}", path),
+        )
+        .into_response();
+    };
+
+    let device = match authorize(&state, &headers) {
+        Ok(device) => device,
+        Err(error) => return error.into_response(),
+    };
+    let message = match parse_command(event, &body) {
+        Ok(message) => message,
+        Err(error) => return error.into_response(),
+    };
+
+    let peer = addr.to_string();
+    let actor = AuditActor {
+        device_id: Some(&device.device_id),
+        session_id: None,
+        peer: Some(&peer),
+    };
+    let summary = serde_json::to_string(&message).unwrap_or_default();
+
+    let result = match check_access(
+        &state.app,
+        &device,
+        required_scope(&message).as_deref(),
+        control_lock::lock_scopes(&message),
+    ) {
+        Ok(()) => run_command(&state.app, &peer, &message).await,
+        Err(error) => Err(error),
+    };
+    record(&state.app, actor, event, &summary, &result);
+
+    match result {
+        Ok(data) => ok_response(data),
+        Err(error) => error.into_response(),
+    }
+}
+
+async fn rest_upload_begin(
+    AxumState(state): AxumState<RestRouteState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let device = match authorize_upload(&state, &headers) {
+        Ok(device) => device,
+        Err(error) => return error.into_response(),
+    };
+    let request = match parse_body::<UploadRequest>(&body) {
+        Ok(request) => request,
+        Err(error) => return error.into_response(),
+    };
+
+    let result = media_upload::begin(
+        &state.app,
+        &device.device_id,
+        &request.file_name,
+        request.size,
+    )
+    .map(|ticket| json!(ticket))
+    .map_err(RestError::from);
+    let peer = addr.to_string();
+    record(
+        &state.app,
+        AuditActor {
+            device_id: Some(&device.device_id),
+            session_id: None,
+            peer: Some(&peer),
+        },
+        UPLOAD_BEGIN,
+        &format!("{} ({} bytes)", request.file_name, request.size),
+        &result,
+    );
+
+    match result {
+        Ok(data) => ok_response(data),
+        Err(error) => error.into_response(),
+    }
+}
+
+async fn rest_upload_progress(
+    AxumState(state): AxumState<RestRouteState>,
+    Path(upload_id): Path<String>,
+    headers: HeaderMap,
+) -> Response {
+    let result = authorize_upload(&state, &headers).and_then(|device| {
+        media_upload::progress(&state.app, &device.device_id, &upload_id).map_err(RestError::from)
+    });
+
+    match result {
+        Ok(progress) => ok_response(json!(progress)),
+        Err(error) => error.into_response(),
+    }
+}
+
+/// Chunks are not audited; the start and end of an upload are.
+async fn rest_upload_chunk(
+    AxumState(state): AxumState<RestRouteState>,
+    Path(upload_id): Path<String>,
+    Query(query): Query<ChunkQuery>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let device = match authorize_upload(&state, &headers) {
+        Ok(device) => device,
+        Err(error) => return error.into_response(),
+    };
+
+    match media_upload::write_chunk(
+        &state.app,
+        &device.device_id,
+        &upload_id,
+        query.offset,
+        &body,
+    )
+    .await
+    {
+        Ok(progress) => ok_response(json!(progress)),
+        Err(error) => RestError::from(error).into_response(),
+    }
+}
+
+async fn rest_upload_complete(
+    AxumState(state): AxumState<RestRouteState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    Path(upload_id): Path<String>,
+    headers: HeaderMap,
+) -> Response {
+    let device = match authorize_upload(&state, &headers) {
+        Ok(device) => device,
+        Err(error) => return error.into_response(),
+    };
+
+    // The desktop path stays on the desktop; the device gets the library id
+    // it can pass to `media_queue` or `media_show`.
+    let result = media_upload::complete(
+        &state.app,
+        &device.device_id,
+        &device.device_name,
+        &upload_id,
+    )
+    .await
+    .map(|uploaded| {
+        json!({
+            "media_id": uploaded.media_id,
+            "name": uploaded.name,
+            "media_type": uploaded.media_type,
+            "size": uploaded.size,
+        })
+    })
+    .map_err(RestError::from);
+    let peer = addr.to_string();
+    let summary = result
+        .as_ref()
+        .ok()
+        .and_then(|data| data["name"].as_str())
+        .unwrap_or(&upload_id)
+        .to_string();
+    record(
+        &state.app,
+        AuditActor {
+            device_id: Some(&device.device_id),
+            session_id: None,
+            peer: Some(&peer),
+        },
+        UPLOAD_COMPLETE,
+        &summary,
+        &result,
+    );
+
+    match result {
+        Ok(data) => ok_response(data),
+        Err(error) => error.into_response(),
+    }
+}
+
+async fn rest_upload_cancel(
+    AxumState(state): AxumState<RestRouteState>,
+    Path(upload_id): Path<String>,
+    headers: HeaderMap,
+) -> Response {
+    let result = authorize_upload(&state, &headers).and_then(|device| {
+        media_upload::cancel(&state.app, &device.device_id, &upload_id).map_err(RestError::from)
+    });
+
+    match result {
+        Ok(()) => ok_response(json!({})),
+        Err(error) => error.into_response(),
+    }
+}
+
+async fn rest_media_browse(
+    AxumState(state): AxumState<RestRouteState>,
+    Query(query): Query<BrowseQuery>,
+    headers: HeaderMap,
+) -> Response {
+    browse(&state, &headers, "media.view", || {
+        media_library::browse(
+            query.media_type.as_deref(),
+            query.query.as_deref(),
+            query.offset,
+            query.limit,
+        )
+    })
+}
+
+async fn rest_lyrics_browse(
+    AxumState(state): AxumState<RestRouteState>,
+    Query(query): Query<BrowseQuery>,
+    headers: HeaderMap,
+) -> Response {
+    browse(&state, &headers, "lyrics.view", || {
+        media_library::browse(
+            Some("lyrics"),
+            query.query.as_deref(),
+            query.offset,
+            query.limit,
+        )
+    })
+}
+
+fn browse(
+    state: &RestRouteState,
+    headers: &HeaderMap,
+    scope: &str,
+    page: impl FnOnce() -> Result<MediaPage, String>,
+) -> Response {
+    let result = authorize(state, headers)
+        .and_then(|device| check_access(&state.app, &device, Some(scope), &[]))
+        .and_then(|()| page().map_err(|reason| RestError::new(ErrorCode::InvalidPayload, reason)));
+
+    match result {
+        Ok(page) => ok_response(json!(page)),
+        Err(error) => error.into_response(),
+    }
+}
+
+/// Serves the cached JPEG thumbnail of a library item. Thumbnails skip the
+/// request budget, since a page of results asks for many at once.
+async fn rest_thumbnail(
+    AxumState(state): AxumState<RestRouteState>,
+    Path(media_id): Path<i64>,
+    Query(query): Query<ThumbnailQuery>,
+    headers: HeaderMap,
+) -> Response {
+    let result = async {
+        let device = authenticate(&state, &headers)?;
+        check_access(&state.app, &device, Some("media.view"), &[])?;
+
+        let item = media_library::find(media_id).map_err(RestError::not_found)?;
+        if !media_library::THUMBNAIL_TYPES.contains(&item.media_type.as_str()) {
+            return Err(RestError::not_found(format!(
+                "`{}` items have no thumbnail",
+                item.media_type
+            )));
+        }
+
+        let size = query
+            .size
+            .unwrap_or(DEFAULT_THUMBNAIL_SIZE)
+            .clamp(MIN_THUMBNAIL_SIZE, MAX_THUMBNAIL_SIZE);
+        let path = thumbnail::get_thumbnail(state.app.clone(), item.path, Some(size))
+            .await
+            .map_err(|reason| RestError::new(ErrorCode::CommandFailed, reason))?;
+        tokio::fs::read(path)
+            .await
+            .map_err(|error| RestError::new(ErrorCode::CommandFailed, error.to_string()))
+    }
+    .await;
+
+    match result {
+        Ok(bytes) => (
+            [
+                (header::CONTENT_TYPE, "image/jpeg"),
+                (header::CACHE_CONTROL, "private, max-age=86400"),
+            ],
+            bytes,
+        )
+            .into_response(),
+        Err(error) => error.into_response(),
+    }
+}
+
+/// Uploads need `media.upload`; they do not change what is on screen, so the
+/// media lock does not apply.
+fn authorize_upload(state: &RestRouteState, headers: &HeaderMap) -> Result<Device, RestError> {
+    let device = authorize(state, headers)?;
+    check_access(&state.app, &device, Some("media.upload"), &[])?;
+    Ok(device)
+}
+
+/// Checks the device credentials. Expects `Authorization: Bearer
+/// <access_token>` and `X-Lumen-Device-Id`.
+fn authenticate(state: &RestRouteState, headers: &HeaderMap) -> Result<Device, RestError> {
+    let device_id =
+        header_value(headers, DEVICE_ID_HEADER).ok_or_else(|| RestError::auth("not_registered"))?;
+    let access_token = header_value(headers, header::AUTHORIZATION.as_str())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+        .ok_or_else(|| RestError::auth("invalid_token"))?;
+
+    let devices = state.app.state::<DeviceState>();
+    verify_device_credentials(&devices, device_id, access_token)
+        .map(|(device, _)| device)
+        .map_err(|reason| RestError::auth(&reason))
+}
+
+/// Checks the device credentials and its request budget.
+fn authorize(state: &RestRouteState, headers: &HeaderMap) -> Result<Device, RestError> {
+    let device = authenticate(state, headers)?;
+
+    let limits = control_limits(&state.app.state::<DeviceState>());
+    let allowed = state.buckets.lock().is_ok_and(|mut buckets| {
+        buckets
+            .entry(device.device_id.clone())
+            .or_insert_with(|| TokenBucket::new(limits.messages_per_second, limits.burst))
+            .try_take()
+    });
+    if !allowed {
+        return Err(RestError::new(
+            ErrorCode::RateLimited,
+            "Too many requests, slow down",
+        ));
+    }
+
+    Ok(device)
+}
+
+/// The permission and control lock checks the control socket applies before
+/// running a command.
+fn check_access(
+    app: &AppHandle,
+    device: &Device,
+    scope: Option<&str>,
+    lock_scopes: &[&str],
+) -> Result<(), RestError> {
+    if let Some(scope) = scope.filter(|scope| !is_permission_allowed(&device.permissions, scope)) {
+        return Err(RestError::new(
+            ErrorCode::PermissionDenied,
+            format!("Device lacks the `{}` permission", scope),
+        ));
+    }
+    match control_lock::blocking_device_lock(app, &device.device_id, lock_scopes) {
+        Some(lock) => Err(RestError::locked(lock)),
+        None => Ok(()),
+    }
+}
+
+async fn run_command(
+    app: &AppHandle,
+    peer: &str,
+    message: &ClientMessage,
+) -> Result<Value, RestError> {
+    if let ClientMessage::BibleShow {
+        reference,
+        translation,
+    } = message
+    {
+        return bible::show(app, reference, translation.as_deref())
+            .await
+            .map(|passage| json!({ "passage": passage }))
+            .map_err(|reason| RestError::new(ErrorCode::InvalidPayload, reason));
+    }
+
+    Ok(run_confirmed_control_message(app, peer, message).await?)
+}
+
+/// Builds the WebSocket message for `event` from the request body, so REST
+/// calls accept exactly the fields the socket does.
+fn parse_command(event: &str, body: &[u8]) -> Result<ClientMessage, RestError> {
+    let mut fields = parse_body::<Value>(body)?;
+    let Some(object) = fields.as_object_mut() else {
+        return Err(RestError::new(
+            ErrorCode::InvalidPayload,
+            "Request body must be a JSON object",
+        ));
+    };
+    object.insert("event".to_string(), json!(event));
+
+    serde_json::from_value(fields)
+        .map_err(|error| RestError::new(ErrorCode::InvalidPayload, error.to_string()))
+}
+
+/// An empty body reads as `{}`.
+fn parse_body<T: DeserializeOwned>(body: &[u8]) -> Result<T, RestError> {
+    let body = if body.iter().all(u8::is_ascii_whitespace) {
+        b"{}".as_slice()
+    } else {
+        body
+    };
+    serde_json::from_slice(body).map_err(|error| {
+        let code = if error.is_data() {
+            ErrorCode::InvalidPayload
+        } else {
+            ErrorCode::MalformedJson
+        };
+        RestError::new(code, error.to_string())
+    })
+}
+
+fn record(
+    app: &AppHandle,
+    actor: AuditActor<'_>,
+    action: &str,
+    summary: &str,
+    result: &Result<Value, RestError>,
+) {
+    let outcome = match result {
+        Ok(_) => AuditOutcome::Ok,
+        Err(error)
+            if error.status == StatusCode::FORBIDDEN || error.status == StatusCode::CONFLICT =>
+        {
+            AuditOutcome::Denied
+        }
+        Err(error) => AuditOutcome::Failed(&error.message),
+    };
+    audit::record(app, actor, audit::COMMAND, action, summary, outcome);
+}
+
+fn header_value<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+}
+
+fn status_for(code: ErrorCode) -> StatusCode {
+    match code {
+        ErrorCode::MalformedJson | ErrorCode::InvalidPayload | ErrorCode::UnsupportedVersion => {
+            StatusCode::BAD_REQUEST
+        }
+        ErrorCode::UnknownEvent => StatusCode::NOT_FOUND,
+        ErrorCode::Unauthorized => StatusCode::UNAUTHORIZED,
+        ErrorCode::PermissionDenied => StatusCode::FORBIDDEN,
+        ErrorCode::ControlLocked => StatusCode::CONFLICT,
+        ErrorCode::RateLimited => StatusCode::TOO_MANY_REQUESTS,
+        ErrorCode::Timeout => StatusCode::GATEWAY_TIMEOUT,
+        ErrorCode::CommandFailed => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+fn ok_response(data: Value) -> Response {
+    Json(json!({ "ok": true, "data": data })).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rejection(result: Result<ClientMessage, RestError>) -> (StatusCode, Value) {
+        let error = result.expect_err("the body should be rejected");
+        (error.status, error.code)
+    }
+
+    #[test]
+    fn commands_take_their_fields_from_the_body() {
+        assert!(matches!(
+            parse_command("set_volume", br#"{ "value": 80 }"#),
+            Ok(ClientMessage::SetVolume { value }) if value == 80.0
+        ));
+        assert!(matches!(
+            parse_command("lyrics_slide", br#"{ "index": 3 }"#),
+            Ok(ClientMessage::LyricsSlide { index: 3 })
+        ));
+        // The route decides the event, whatever the body says.
+        assert!(matches!(
+            parse_command("next", br#"{ "event": "stop" }"#),
+            Ok(ClientMessage::Next)
+        ));
+    }
+
+    #[test]
+    fn empty_bodies_read_as_an_empty_object() {
+        assert!(matches!(
+            parse_command("next", b""),
+            Ok(ClientMessage::Next)
+        ));
+        assert!(matches!(
+            parse_command("stop", b" \n"),
+            Ok(ClientMessage::Stop)
+        ));
+    }
+
+    #[test]
+    fn bad_bodies_get_the_socket_error_codes() {
+        let malformed = (StatusCode::BAD_REQUEST, json!("malformed_json"));
+        let invalid = (StatusCode::BAD_REQUEST, json!("invalid_payload"));
+
+        assert_eq!(rejection(parse_command("next", b"{")), malformed);
+        assert_eq!(rejection(parse_command("next", b"[1]")), invalid);
+        assert_eq!(rejection(parse_command("set_volume", b"{}")), invalid);
+        assert_eq!(
+            rejection(parse_command("lyrics_slide", br#"{ "index": -1 }"#)),
+            invalid
+        );
+    }
+
+    #[test]
+    fn error_codes_map_to_http_statuses() {
+        let cases = [
+            (ErrorCode::MalformedJson, StatusCode::BAD_REQUEST),
+            (ErrorCode::InvalidPayload, StatusCode::BAD_REQUEST),
+            (ErrorCode::UnsupportedVersion, StatusCode::BAD_REQUEST),
+            (ErrorCode::UnknownEvent, StatusCode::NOT_FOUND),
+            (ErrorCode::Unauthorized, StatusCode::UNAUTHORIZED),
+            (ErrorCode::PermissionDenied, StatusCode::FORBIDDEN),
+            (ErrorCode::ControlLocked, StatusCode::CONFLICT),
+            (ErrorCode::RateLimited, StatusCode::TOO_MANY_REQUESTS),
+            (ErrorCode::Timeout, StatusCode::GATEWAY_TIMEOUT),
+            (ErrorCode::CommandFailed, StatusCode::INTERNAL_SERVER_ERROR),
+        ];
+        for (code, status) in cases {
+            assert_eq!(status_for(code), status, "{:?}", code);
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use std::{fs, path::PathBuf};
+use std::{collections::HashMap, fs, path::PathBuf};
 
 use serde::{Deserialize, Serialize};
 
@@ -11,6 +11,10 @@ pub struct StreamingConfig {
     pub html_server_port: u16,
     pub hardware_encoding: bool,
     pub content_protection: bool,
+    #[serde(default)]
+    pub html_output_languages: HashMap<String, String>,
+    #[serde(default)]
+    pub audience_enabled: bool,
 }
 
 impl Default for StreamingConfig {
@@ -23,6 +27,8 @@ impl Default for StreamingConfig {
             html_server_port: 8090,
             hardware_encoding: false,
             content_protection: true,
+            html_output_languages: HashMap::new(),
+            audience_enabled: false,
         }
     }
 }
@@ -64,6 +70,10 @@ pub fn sanitize_config(mut config: StreamingConfig) -> StreamingConfig {
         config.html_server_port = 8090;
     }
 
+    config.html_output_languages.retain(|output_id, language| {
+        !output_id.trim().is_empty() && !language.trim().is_empty()
+    });
+
     config
 }
 
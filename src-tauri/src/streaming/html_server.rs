@@ -1,4 +1,5 @@
 use std::{
+    collections::HashMap,
     net::{IpAddr, Ipv4Addr, SocketAddr},
     sync::Arc,
 };
@@ -6,16 +7,17 @@ use std::{
 use axum::{
     Json, Router,
     extract::{
-        ConnectInfo, State as AxumState,
+        ConnectInfo, Query, State as AxumState,
         ws::{Message as AxumWsMessage, WebSocket, WebSocketUpgrade},
     },
     response::{Html, IntoResponse},
     routing::get,
 };
+use axum_server::tls_rustls::RustlsConfig;
 use futures_util::{SinkExt, StreamExt};
-use local_ip_address::local_ip;
+use rustls::ServerConfig;
 use serde::{Deserialize, Serialize};
-use serde_json::json;
+use serde_json::{Value, json};
 use tauri::async_runtime;
 use tauri::async_runtime::JoinHandle;
 use tokio::{
@@ -23,9 +25,24 @@ use tokio::{
     sync::{RwLock, broadcast},
 };
 
+/// Language choice that renders every language of a slide stacked.
+pub const ALL_LANGUAGES: &str = "all";
+
+const DEFAULT_OUTPUT_ID: &str = "default";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlideTranslation {
+    pub language: String,
+    pub lines: Vec<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SlideUpdate {
     pub lines: Vec<String>,
+    #[serde(default)]
+    pub language: Option<String>,
+    #[serde(default)]
+    pub translations: Vec<SlideTranslation>,
     pub font: Option<String>,
     pub font_size: Option<u32>,
     pub alignment: Option<String>,
@@ -35,45 +52,159 @@ pub struct SlideUpdate {
     pub active: bool,
 }
 
+impl SlideUpdate {
+    /// Returns the language blocks to render for `choice`. `all` stacks every
+    /// language with the primary one first; a language the slide has no lines
+    /// for falls back to the primary lines so an output never goes blank
+    /// mid-song.
+    pub fn blocks_for(&self, choice: &str) -> Vec<SlideTranslation> {
+        let primary = SlideTranslation {
+            language: self.language.clone().unwrap_or_default(),
+            lines: self.lines.clone(),
+        };
+
+        if choice == ALL_LANGUAGES {
+            let mut blocks = vec![primary];
+            blocks.extend(
+                self.translations
+                    .iter()
+                    .filter(|translation| !translation.lines.is_empty())
+                    .cloned(),
+            );
+            return blocks;
+        }
+
+        if self.language.as_deref() == Some(choice) {
+            return vec![primary];
+        }
+
+        self.translations
+            .iter()
+            .find(|translation| translation.language == choice && !translation.lines.is_empty())
+            .cloned()
+            .map(|translation| vec![translation])
+            .unwrap_or_else(|| vec![primary])
+    }
+
+    /// Serializes the slide for a single output, keeping the flat `lines`
+    /// field for clients that only understand one language.
+    pub fn to_payload(&self, choice: &str) -> Value {
+        let blocks = self.blocks_for(choice);
+        let lines = blocks
+            .iter()
+            .flat_map(|block| block.lines.iter().cloned())
+            .collect::<Vec<_>>();
+
+        json!({
+            "type": "slide",
+            "lines": lines,
+            "blocks": blocks,
+            "language": choice,
+            "font": self.font,
+            "font_size": self.font_size,
+            "alignment": self.alignment,
+            "background": self.background,
+            "slide_index": self.slide_index,
+            "total_slides": self.total_slides,
+            "active": self.active,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HtmlOutputInfo {
+    pub output_id: String,
+    pub language: String,
+    pub connected: usize,
+}
+
+#[derive(Debug, Clone)]
+enum HtmlFrame {
+    Slide(SlideUpdate),
+    Blank,
+    LanguageChanged(String),
+}
+
 #[derive(Clone)]
 struct HtmlServerState {
-    tx: broadcast::Sender<String>,
-    latest: Arc<RwLock<Option<String>>>,
+    tx: broadcast::Sender<HtmlFrame>,
+    latest: Arc<RwLock<Option<HtmlFrame>>>,
+    output_languages: Arc<RwLock<HashMap<String, String>>>,
+    connected_outputs: Arc<RwLock<HashMap<String, usize>>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct HtmlWsQuery {
+    output: Option<String>,
 }
 
 pub struct HtmlServerRuntime {
     port: Option<u16>,
+    tls: Option<Arc<ServerConfig>>,
+    /// Routes served next to the slide endpoints, such as the audience pages.
+    extra_routes: Router,
     state: Option<HtmlServerState>,
     task: Option<JoinHandle<()>>,
+    output_languages: Arc<RwLock<HashMap<String, String>>>,
 }
 
 impl HtmlServerRuntime {
-    pub fn new() -> Self {
+    pub fn new(output_languages: HashMap<String, String>, extra_routes: Router) -> Self {
         Self {
             port: None,
+            tls: None,
+            extra_routes,
             state: None,
             task: None,
+            output_languages: Arc::new(RwLock::new(output_languages)),
         }
     }
 
-    pub fn start(&mut self, port: u16) {
-        if self.is_active() && self.port == Some(port) {
+    pub fn start(&mut self, port: u16, tls: Option<Arc<ServerConfig>>) {
+        let same_tls = match (&self.tls, &tls) {
+            (Some(current), Some(next)) => Arc::ptr_eq(current, next),
+            (None, None) => true,
+            _ => false,
+        };
+        if self.is_active() && self.port == Some(port) && same_tls {
             return;
         }
 
         self.stop();
 
         let bind_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), port);
-        let (tx, _) = broadcast::channel::<String>(128);
+        let (tx, _) = broadcast::channel::<HtmlFrame>(128);
         let latest = Arc::new(RwLock::new(None));
         let state = HtmlServerState {
             tx: tx.clone(),
             latest: latest.clone(),
+            output_languages: self.output_languages.clone(),
+            connected_outputs: Arc::new(RwLock::new(HashMap::new())),
         };
 
         let app_state = state.clone();
+        let tls_config = tls.clone();
+        let extra_routes = self.extra_routes.clone();
 
         let task = async_runtime::spawn(async move {
+            let router = Router::new()
+                .route("/", get(html_index))
+                .route("/health", get(html_health))
+                .route("/ws", get(html_ws))
+                .with_state(app_state)
+                .merge(extra_routes);
+
+            if let Some(tls_config) = tls_config {
+                if let Err(error) =
+                    axum_server::bind_rustls(bind_addr, RustlsConfig::from_config(tls_config))
+                        .serve(router.into_make_service_with_connect_info::<SocketAddr>())
+                        .await
+                {
+                    eprintln!("HTML server error: {}", error);
+                }
+                return;
+            }
+
             let listener = match TcpListener::bind(bind_addr).await {
                 Ok(listener) => listener,
                 Err(error) => {
@@ -82,12 +213,6 @@ impl HtmlServerRuntime {
                 }
             };
 
-            let router = Router::new()
-                .route("/", get(html_index))
-                .route("/health", get(html_health))
-                .route("/ws", get(html_ws))
-                .with_state(app_state);
-
             if let Err(error) = axum::serve(
                 listener,
                 router.into_make_service_with_connect_info::<SocketAddr>(),
@@ -99,6 +224,7 @@ impl HtmlServerRuntime {
         });
 
         self.port = Some(port);
+        self.tls = tls;
         self.state = Some(state);
         self.task = Some(task);
     }
@@ -109,6 +235,7 @@ impl HtmlServerRuntime {
         }
 
         self.port = None;
+        self.tls = None;
         self.state = None;
     }
 
@@ -116,46 +243,76 @@ impl HtmlServerRuntime {
         self.task.is_some()
     }
 
-    pub fn url(&self) -> Option<String> {
+    pub fn url(&self, host: IpAddr) -> Option<String> {
         let port = self.port?;
-        local_ip().ok().map(|ip| format!("http://{}:{}", ip, port))
+        let scheme = if self.tls.is_some() { "https" } else { "http" };
+        Some(format!("{}://{}", scheme, SocketAddr::new(host, port)))
     }
 
     pub fn push_slide(&self, slide: SlideUpdate) {
-        let Some(state) = &self.state else {
-            return;
+        self.publish(HtmlFrame::Slide(slide));
+    }
+
+    pub fn push_blank(&self) {
+        self.publish(HtmlFrame::Blank);
+    }
+
+    /// Replaces the persisted per-output language choices.
+    pub async fn load_output_languages(&self, languages: HashMap<String, String>) {
+        *self.output_languages.write().await = languages;
+    }
+
+    /// Changes the language of one output and re-renders its current slide.
+    pub async fn set_output_language(&self, output_id: &str, language: &str) {
+        self.output_languages
+            .write()
+            .await
+            .insert(output_id.to_string(), language.to_string());
+
+        if let Some(state) = &self.state {
+            let _ = state
+                .tx
+                .send(HtmlFrame::LanguageChanged(output_id.to_string()));
+        }
+    }
+
+    pub async fn outputs(&self) -> Vec<HtmlOutputInfo> {
+        let languages = self.output_languages.read().await.clone();
+        let connected = match &self.state {
+            Some(state) => state.connected_outputs.read().await.clone(),
+            None => HashMap::new(),
         };
 
-        let payload = json!({
-            "type": "slide",
-            "lines": slide.lines,
-            "font": slide.font,
-            "font_size": slide.font_size,
-            "alignment": slide.alignment,
-            "background": slide.background,
-            "slide_index": slide.slide_index,
-            "total_slides": slide.total_slides,
-            "active": slide.active,
-        })
-        .to_string();
+        let mut outputs = languages
+            .keys()
+            .chain(connected.keys())
+            .cloned()
+            .collect::<Vec<_>>();
+        outputs.sort();
+        outputs.dedup();
 
-        let _ = state.tx.send(payload.clone());
-        let latest = state.latest.clone();
-        async_runtime::spawn(async move {
-            *latest.write().await = Some(payload);
-        });
+        outputs
+            .into_iter()
+            .map(|output_id| HtmlOutputInfo {
+                language: languages
+                    .get(&output_id)
+                    .cloned()
+                    .unwrap_or_else(|| ALL_LANGUAGES.to_string()),
+                connected: connected.get(&output_id).copied().unwrap_or(0),
+                output_id,
+            })
+            .collect()
     }
 
-    pub fn push_blank(&self) {
+    fn publish(&self, frame: HtmlFrame) {
         let Some(state) = &self.state else {
             return;
         };
 
-        let payload = json!({ "type": "blank" }).to_string();
-        let _ = state.tx.send(payload.clone());
+        let _ = state.tx.send(frame.clone());
         let latest = state.latest.clone();
         async_runtime::spawn(async move {
-            *latest.write().await = Some(payload);
+            *latest.write().await = Some(frame);
         });
     }
 }
@@ -170,16 +327,55 @@ async fn html_ws(
     ws: WebSocketUpgrade,
     AxumState(state): AxumState<HtmlServerState>,
     ConnectInfo(_addr): ConnectInfo<SocketAddr>,
+    Query(query): Query<HtmlWsQuery>,
 ) -> impl IntoResponse {
+    let output_id = query
+        .output
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .unwrap_or_else(|| DEFAULT_OUTPUT_ID.to_string());
+
     ws.on_upgrade(move |socket| async move {
-        handle_html_ws(socket, state).await;
+        *state
+            .connected_outputs
+            .write()
+            .await
+            .entry(output_id.clone())
+            .or_insert(0) += 1;
+
+        handle_html_ws(socket, state.clone(), &output_id).await;
+
+        let mut connected = state.connected_outputs.write().await;
+        if let Some(count) = connected.get_mut(&output_id) {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                connected.remove(&output_id);
+            }
+        }
     })
 }
 
-async fn handle_html_ws(socket: WebSocket, state: HtmlServerState) {
+async fn render_frame(state: &HtmlServerState, output_id: &str, frame: &HtmlFrame) -> String {
+    match frame {
+        HtmlFrame::Slide(slide) => {
+            let language = state
+                .output_languages
+                .read()
+                .await
+                .get(output_id)
+                .cloned()
+                .unwrap_or_else(|| ALL_LANGUAGES.to_string());
+            slide.to_payload(&language).to_string()
+        }
+        HtmlFrame::Blank | HtmlFrame::LanguageChanged(_) => json!({ "type": "blank" }).to_string(),
+    }
+}
+
+async fn handle_html_ws(socket: WebSocket, state: HtmlServerState, output_id: &str) {
     let (mut sender, mut receiver) = socket.split();
 
-    if let Some(initial_payload) = state.latest.read().await.clone() {
+    if let Some(initial_frame) = state.latest.read().await.clone() {
+        let initial_payload = render_frame(&state, output_id, &initial_frame).await;
         let _ = sender
             .send(AxumWsMessage::Text(initial_payload.into()))
             .await;
@@ -203,7 +399,20 @@ async fn handle_html_ws(socket: WebSocket, state: HtmlServerState) {
             }
             outgoing = subscription.recv() => {
                 match outgoing {
-                    Ok(message) => {
+                    Ok(frame) => {
+                        let frame = match frame {
+                            HtmlFrame::LanguageChanged(changed) => {
+                                if changed != output_id {
+                                    continue;
+                                }
+                                match state.latest.read().await.clone() {
+                                    Some(latest) => latest,
+                                    None => continue,
+                                }
+                            }
+                            frame => frame,
+                        };
+                        let message = render_frame(&state, output_id, &frame).await;
                         if sender.send(AxumWsMessage::Text(message.into())).await.is_err() {
                             break;
                         }
@@ -266,6 +475,13 @@ const HTML_TEMPLATE: &str = r#"<!doctype html>
       line-height: 1.4;
     }
     .line { margin: 0.15em 0; }
+    .block + .block {
+      margin-top: 0.6em;
+      padding-top: 0.6em;
+      border-top: 1px solid rgba(255, 255, 255, 0.25);
+      font-size: 0.75em;
+      opacity: 0.85;
+    }
   </style>
 </head>
 <body>
@@ -290,18 +506,27 @@ const HTML_TEMPLATE: &str = r#"<!doctype html>
           root.style.background = `#000 url("${background}") center/cover no-repeat`;
         }
         text.innerHTML = '';
-        for (const line of payload.lines || []) {
-          const div = document.createElement('div');
-          div.className = 'line';
-          div.textContent = line;
-          text.appendChild(div);
+        const blocks = payload.blocks || [{ lines: payload.lines || [] }];
+        for (const block of blocks) {
+          const container = document.createElement('div');
+          container.className = 'block';
+          if (block.language) container.lang = block.language;
+          for (const line of block.lines || []) {
+            const div = document.createElement('div');
+            div.className = 'line';
+            div.textContent = line;
+            container.appendChild(div);
+          }
+          text.appendChild(container);
         }
         root.style.opacity = '1';
       }, 250);
     }
     function connect() {
       const protocol = window.location.protocol === 'https:' ? 'wss' : 'ws';
-      const ws = new WebSocket(`${protocol}://${window.location.host}/ws`);
+      const output = new URLSearchParams(window.location.search).get('output');
+      const query = output ? `?output=${encodeURIComponent(output)}` : '';
+      const ws = new WebSocket(`${protocol}://${window.location.host}/ws${query}`);
       ws.onmessage = (event) => {
         try {
           const payload = JSON.parse(event.data);
@@ -321,3 +546,75 @@ const HTML_TEMPLATE: &str = r#"<!doctype html>
 </body>
 </html>
 "#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    fn slide(translations: Vec<SlideTranslation>) -> SlideUpdate {
+        SlideUpdate {
+            lines: lines(&["Amazing grace"]),
+            language: Some("en".to_string()),
+            translations,
+            font: None,
+            font_size: None,
+            alignment: None,
+            background: None,
+            slide_index: 0,
+            total_slides: 1,
+            active: true,
+        }
+    }
+
+    fn spanish(values: &[&str]) -> SlideTranslation {
+        SlideTranslation {
+            language: "es".to_string(),
+            lines: lines(values),
+        }
+    }
+
+    fn languages(blocks: &[SlideTranslation]) -> Vec<&str> {
+        blocks.iter().map(|block| block.language.as_str()).collect()
+    }
+
+    #[test]
+    fn one_language_shows_only_that_language() {
+        let update = slide(vec![spanish(&["Sublime gracia"])]);
+
+        let primary = update.blocks_for("en");
+        assert_eq!(languages(&primary), ["en"]);
+        assert_eq!(primary[0].lines, lines(&["Amazing grace"]));
+
+        let translated = update.blocks_for("es");
+        assert_eq!(languages(&translated), ["es"]);
+        assert_eq!(translated[0].lines, lines(&["Sublime gracia"]));
+    }
+
+    #[test]
+    fn all_stacks_the_primary_language_first() {
+        let update = slide(vec![spanish(&["Sublime gracia"])]);
+        assert_eq!(languages(&update.blocks_for(ALL_LANGUAGES)), ["en", "es"]);
+    }
+
+    #[test]
+    fn missing_translation_falls_back_to_the_primary_lines() {
+        let empty = slide(vec![spanish(&[])]);
+        assert_eq!(languages(&empty.blocks_for("es")), ["en"]);
+        assert_eq!(languages(&empty.blocks_for(ALL_LANGUAGES)), ["en"]);
+
+        let absent = slide(Vec::new());
+        assert_eq!(languages(&absent.blocks_for("es")), ["en"]);
+    }
+
+    #[test]
+    fn unknown_language_falls_back_to_the_primary_lines() {
+        let update = slide(vec![spanish(&["Sublime gracia"])]);
+        let blocks = update.blocks_for("fr");
+        assert_eq!(languages(&blocks), ["en"]);
+        assert_eq!(blocks[0].lines, lines(&["Amazing grace"]));
+    }
+}
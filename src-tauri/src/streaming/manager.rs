@@ -1,11 +1,12 @@
 use std::{collections::HashMap, sync::Arc};
 
 use serde_json::json;
-use tauri::{AppHandle, Emitter, State};
+use tauri::{AppHandle, Emitter, Manager, State};
 use tokio::sync::Mutex;
 use webrtc::{
     api::{
         APIBuilder, interceptor_registry::register_default_interceptors, media_engine::MediaEngine,
+        setting_engine::SettingEngine,
     },
     interceptor::registry::Registry,
     peer_connection::RTCPeerConnection,
@@ -20,8 +21,13 @@ use super::{
         StreamingConfig, StreamingStatus, ensure_streaming_storage, load_streaming_config,
         sanitize_config, save_streaming_config,
     },
-    html_server::{HtmlServerRuntime, SlideUpdate},
+    html_server::{ALL_LANGUAGES, HtmlOutputInfo, HtmlServerRuntime, SlideUpdate},
 };
+use crate::devices::{DeviceState, broadcast_lyric_slide};
+use crate::discovery::announce_html_service;
+use crate::live_state;
+use crate::network::{advertised_ip, is_ice_interface_allowed};
+use crate::tls::server_config;
 
 struct MobilePeer {
     pub peer: Arc<RTCPeerConnection>,
@@ -63,19 +69,29 @@ impl StreamManager {
             main_subs: self.main_peers.len().min(u8::MAX as usize) as u8,
             mobile_connected: !self.mobile_peers.is_empty(),
             html_active: self.html_server.is_active(),
-            html_url: self.html_server.url(),
+            html_url: advertised_ip(&self.app)
+                .ok()
+                .and_then(|ip| self.html_server.url(ip)),
         }
     }
 
     pub fn emit_status(&self) {
-        let _ = self.app.emit("streaming_status_changed", self.status());
+        let status = self.status();
+        live_state::apply_streams(&self.app, status.clone());
+        let _ = self.app.emit("streaming_status_changed", status);
     }
 
     pub fn apply_html_server_config(&mut self) {
         if self.config.html_server_enabled {
-            self.html_server.start(self.config.html_server_port);
+            let tls = server_config(&self.app).unwrap_or_else(|error| {
+                eprintln!("HTML server TLS unavailable, serving plain HTTP: {}", error);
+                None
+            });
+            self.html_server.start(self.config.html_server_port, tls);
+            announce_html_service(&self.app, Some(self.config.html_server_port));
         } else {
             self.html_server.stop();
+            announce_html_service(&self.app, None);
         }
     }
 
@@ -168,9 +184,18 @@ pub fn initialize_streaming_state(app: &AppHandle) -> Result<StreamingState, Str
     interceptor_registry = register_default_interceptors(interceptor_registry, &mut media_engine)
         .map_err(|error| error.to_string())?;
 
+    // Host candidates follow the preferred interface so phones are not handed
+    // VPN or container addresses they cannot reach.
+    let mut setting_engine = SettingEngine::default();
+    let filter_app = app.clone();
+    setting_engine.set_interface_filter(Box::new(move |name: &str| {
+        is_ice_interface_allowed(&filter_app, name)
+    }));
+
     let api = APIBuilder::new()
         .with_media_engine(media_engine)
         .with_interceptor_registry(interceptor_registry)
+        .with_setting_engine(setting_engine)
         .build();
 
     let config = load_streaming_config()?;
@@ -209,7 +234,10 @@ pub fn initialize_streaming_state(app: &AppHandle) -> Result<StreamingState, Str
         mobile_peers: HashMap::new(),
         selected_mobile_preview_device: None,
         is_content_protected: false,
-        html_server: HtmlServerRuntime::new(),
+        html_server: HtmlServerRuntime::new(
+            config.html_output_languages.clone(),
+            crate::audience::router(app.clone()).merge(crate::rest_api::router(app.clone())),
+        ),
         config,
     };
 
@@ -244,6 +272,10 @@ pub async fn update_streaming_config(
     let was_content_protected = manager.is_content_protected;
     manager.config = next.clone();
     manager.is_content_protected = manager.config.content_protection && was_content_protected;
+    manager
+        .html_server
+        .load_output_languages(next.html_output_languages.clone())
+        .await;
     manager.apply_html_server_config();
     manager.emit_status();
 
@@ -268,17 +300,62 @@ pub async fn set_stream_content_protected(
 }
 
 #[tauri::command]
-pub async fn push_stream_slide(
-    state: State<'_, StreamingState>,
-    update: SlideUpdate,
-) -> Result<(), String> {
+pub async fn push_stream_slide(app: AppHandle, update: SlideUpdate) -> Result<(), String> {
+    publish_slide(&app, update).await
+}
+
+/// Sends a slide to paired devices and every HTML output.
+pub async fn publish_slide(app: &AppHandle, update: SlideUpdate) -> Result<(), String> {
+    broadcast_lyric_slide(&app.state::<DeviceState>(), &update)?;
+    live_state::apply_slide(app, &update);
+    let state = app.state::<StreamingState>();
     let manager = state.manager.lock().await;
     manager.html_server.push_slide(update);
     Ok(())
 }
 
 #[tauri::command]
-pub async fn push_stream_blank(state: State<'_, StreamingState>) -> Result<(), String> {
+pub async fn get_html_outputs(
+    state: State<'_, StreamingState>,
+) -> Result<Vec<HtmlOutputInfo>, String> {
+    Ok(state.manager.lock().await.html_server.outputs().await)
+}
+
+#[tauri::command]
+pub async fn set_html_output_language(
+    state: State<'_, StreamingState>,
+    output_id: String,
+    language: Option<String>,
+) -> Result<StreamingConfig, String> {
+    let output_id = output_id.trim().to_string();
+    if output_id.is_empty() {
+        return Err("Output id cannot be empty".to_string());
+    }
+    let language = language
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .unwrap_or_else(|| ALL_LANGUAGES.to_string());
+
+    let mut manager = state.manager.lock().await;
+    let mut next = manager.config.clone();
+    next.html_output_languages
+        .insert(output_id.clone(), language.clone());
+    save_streaming_config(&next)?;
+    manager.config = next.clone();
+    manager
+        .html_server
+        .set_output_language(&output_id, &language)
+        .await;
+
+    Ok(next)
+}
+
+#[tauri::command]
+pub async fn push_stream_blank(
+    app: AppHandle,
+    state: State<'_, StreamingState>,
+) -> Result<(), String> {
+    live_state::apply_blank(&app);
     let manager = state.manager.lock().await;
     manager.html_server.push_blank();
     Ok(())
@@ -4,6 +4,8 @@ mod html_server;
 pub mod manager;
 mod signaling;
 
+pub use config::StreamingStatus;
+pub use html_server::{ALL_LANGUAGES, SlideUpdate};
 pub use manager::initialize_streaming_state;
 pub use signaling::{
     StreamErrorPayload, WebRtcIceCandidatePayload, add_webrtc_ice_candidate, handle_mobile_offer,
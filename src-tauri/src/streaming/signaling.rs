@@ -1,5 +1,6 @@
 use std::sync::Arc;
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use tauri::{AppHandle, Emitter, Manager, async_runtime};
@@ -33,7 +34,7 @@ enum StreamType {
     MobilePreview,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct WebRtcIceCandidatePayload {
     pub candidate: String,
     #[serde(rename = "sdpMid")]
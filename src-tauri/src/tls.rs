@@ -0,0 +1,448 @@
+use std::{
+    fs,
+    net::IpAddr,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use local_ip_address::list_afinet_netifas;
+use rcgen::{
+    BasicConstraints, CertificateParams, DistinguishedName, DnType, IsCa, KeyPair, KeyUsagePurpose,
+    SanType,
+};
+use rustls::{
+    ServerConfig,
+    pki_types::{CertificateDer, PrivateKeyDer},
+};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Manager, State, async_runtime};
+use tokio_rustls::TlsAcceptor;
+
+use crate::devices::{DeviceState, app_base_dir, resolve_desktop_name, set_tls_enabled_setting};
+use crate::discovery::announce_control_service;
+use crate::streaming::manager::StreamingState;
+
+const CA_CERT_FILE: &str = "ca.pem";
+const CA_KEY_FILE: &str = "ca-key.pem";
+const SERVER_CERT_FILE: &str = "server.pem";
+const SERVER_KEY_FILE: &str = "server-key.pem";
+/// How often the machine's addresses are compared with the server
+/// certificate's IP names.
+const ADDRESS_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Certificate material shared by the control socket and the HTML server.
+pub struct TlsMaterial {
+    pub server_config: Arc<ServerConfig>,
+    pub ca_pem: String,
+    pub fingerprint: String,
+    pub ca_fingerprint: String,
+}
+
+pub struct TlsState {
+    material: Mutex<Option<Arc<TlsMaterial>>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TlsInfo {
+    pub enabled: bool,
+    pub fingerprint: Option<String>,
+    pub ca_fingerprint: Option<String>,
+    pub ca_pem: Option<String>,
+}
+
+pub fn default_tls_state() -> TlsState {
+    TlsState {
+        material: Mutex::new(None),
+    }
+}
+
+#[tauri::command]
+pub fn get_tls_info(app: AppHandle) -> Result<TlsInfo, String> {
+    if !is_tls_enabled(&app)? {
+        return Ok(TlsInfo {
+            enabled: false,
+            fingerprint: None,
+            ca_fingerprint: None,
+            ca_pem: None,
+        });
+    }
+
+    let material = ensure_tls_material(&app)?;
+    Ok(TlsInfo {
+        enabled: true,
+        fingerprint: Some(material.fingerprint.clone()),
+        ca_fingerprint: Some(material.ca_fingerprint.clone()),
+        ca_pem: Some(material.ca_pem.clone()),
+    })
+}
+
+#[tauri::command]
+pub async fn set_remote_tls_enabled(
+    app: AppHandle,
+    state: State<'_, DeviceState>,
+    streaming: State<'_, StreamingState>,
+    enabled: bool,
+) -> Result<TlsInfo, String> {
+    if enabled {
+        ensure_tls_material(&app)?;
+    }
+
+    let settings = set_tls_enabled_setting(&state, enabled)?;
+    announce_control_service(&app, settings.remote_enabled);
+    streaming.manager.lock().await.apply_html_server_config();
+
+    get_tls_info(app)
+}
+
+/// Issues a new server certificate from the existing local CA, for example
+/// after the machine moved to a network with different addresses. Clients
+/// that pinned the CA keep working; clients that pinned the server
+/// certificate need the new fingerprint from the pairing QR code.
+#[tauri::command]
+pub async fn regenerate_tls_certificate(
+    app: AppHandle,
+    streaming: State<'_, StreamingState>,
+) -> Result<TlsInfo, String> {
+    let dir = tls_dir()?;
+    for file in [SERVER_CERT_FILE, SERVER_KEY_FILE] {
+        let path = dir.join(file);
+        if path.exists() {
+            fs::remove_file(path).map_err(|e| e.to_string())?;
+        }
+    }
+
+    reload_tls_material(&app)?;
+    streaming.manager.lock().await.apply_html_server_config();
+    get_tls_info(app)
+}
+
+/// The local CA certificate and key as PEM, if one was created.
+pub fn local_ca_pem() -> Result<Option<(String, String)>, String> {
+    let dir = tls_dir()?;
+    let cert_path = dir.join(CA_CERT_FILE);
+    let key_path = dir.join(CA_KEY_FILE);
+    if !cert_path.exists() || !key_path.exists() {
+        return Ok(None);
+    }
+    let cert_pem = fs::read_to_string(cert_path).map_err(|e| e.to_string())?;
+    let key_pem = fs::read_to_string(key_path).map_err(|e| e.to_string())?;
+    Ok(Some((cert_pem, key_pem)))
+}
+
+/// Installs a local CA taken from another desktop, so clients that pinned it
+/// keep trusting this one. A new server certificate is issued from it the
+/// next time TLS material is needed.
+pub fn replace_local_ca(app: &AppHandle, cert_pem: &str, key_pem: &str) -> Result<(), String> {
+    KeyPair::from_pem(key_pem).map_err(|e| format!("Invalid CA key: {}", e))?;
+    CertificateParams::from_ca_cert_pem(cert_pem)
+        .map_err(|e| format!("Invalid CA certificate: {}", e))?;
+
+    let dir = tls_dir()?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    fs::write(dir.join(CA_CERT_FILE), cert_pem).map_err(|e| e.to_string())?;
+    write_private_file(&dir.join(CA_KEY_FILE), key_pem)?;
+    for file in [SERVER_CERT_FILE, SERVER_KEY_FILE] {
+        let path = dir.join(file);
+        if path.exists() {
+            fs::remove_file(path).map_err(|e| e.to_string())?;
+        }
+    }
+
+    let state = app.state::<TlsState>();
+    let mut material = state.material.lock().map_err(|e| e.to_string())?;
+    *material = None;
+    Ok(())
+}
+
+/// Watches the machine's addresses and issues a new server certificate from
+/// the local CA when one of them is missing from the current certificate,
+/// so `https://` and `wss://` keep validating after a network change.
+pub fn start_address_watch(app: &AppHandle) {
+    let app = app.clone();
+    async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(ADDRESS_CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            match server_certificate_outdated(&app) {
+                Ok(false) => {}
+                Ok(true) => {
+                    if let Err(error) = reload_tls_material(&app) {
+                        eprintln!("Failed to reissue server certificate: {}", error);
+                        continue;
+                    }
+                    println!("Network addresses changed, reissued the server certificate");
+                    let streaming = app.state::<StreamingState>();
+                    streaming.manager.lock().await.apply_html_server_config();
+                }
+                Err(error) => eprintln!("Failed to check server certificate: {}", error),
+            }
+        }
+    });
+}
+
+pub fn is_tls_enabled(app: &AppHandle) -> Result<bool, String> {
+    app.state::<DeviceState>()
+        .remote_settings
+        .lock()
+        .map(|settings| settings.tls_enabled)
+        .map_err(|e| e.to_string())
+}
+
+/// The acceptor for new control connections, or `None` when TLS is off.
+pub fn tls_acceptor(app: &AppHandle) -> Result<Option<TlsAcceptor>, String> {
+    server_config(app).map(|config| config.map(TlsAcceptor::from))
+}
+
+pub fn server_config(app: &AppHandle) -> Result<Option<Arc<ServerConfig>>, String> {
+    if !is_tls_enabled(app)? {
+        return Ok(None);
+    }
+
+    ensure_tls_material(app).map(|material| Some(material.server_config.clone()))
+}
+
+/// SHA-256 fingerprints of the server certificate and of the local CA while
+/// TLS is enabled.
+pub fn tls_fingerprints(app: &AppHandle) -> Result<Option<(String, String)>, String> {
+    if !is_tls_enabled(app)? {
+        return Ok(None);
+    }
+
+    ensure_tls_material(app).map(|material| {
+        Some((
+            material.fingerprint.clone(),
+            material.ca_fingerprint.clone(),
+        ))
+    })
+}
+
+fn server_certificate_outdated(app: &AppHandle) -> Result<bool, String> {
+    if !is_tls_enabled(app)? {
+        return Ok(false);
+    }
+    let path = tls_dir()?.join(SERVER_CERT_FILE);
+    if !path.exists() {
+        return Ok(false);
+    }
+    let pem = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    Ok(!covers_current_addresses(&pem))
+}
+
+/// Drops the cached material and loads it again, issuing a new server
+/// certificate if the current one no longer covers this machine.
+fn reload_tls_material(app: &AppHandle) -> Result<Arc<TlsMaterial>, String> {
+    {
+        let state = app.state::<TlsState>();
+        let mut material = state.material.lock().map_err(|e| e.to_string())?;
+        *material = None;
+    }
+    ensure_tls_material(app)
+}
+
+fn ensure_tls_material(app: &AppHandle) -> Result<Arc<TlsMaterial>, String> {
+    let state = app.state::<TlsState>();
+    let mut material = state.material.lock().map_err(|e| e.to_string())?;
+    if let Some(existing) = material.as_ref() {
+        return Ok(existing.clone());
+    }
+
+    let loaded = Arc::new(load_or_create_material(app)?);
+    *material = Some(loaded.clone());
+    Ok(loaded)
+}
+
+fn load_or_create_material(app: &AppHandle) -> Result<TlsMaterial, String> {
+    let dir = tls_dir()?;
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+
+    let (ca_params, ca_key, ca_pem) = load_or_create_ca(app, &dir)?;
+    let ca_cert = ca_params
+        .self_signed(&ca_key)
+        .map_err(|e| format!("Failed to load local CA: {}", e))?;
+
+    let server_cert_path = dir.join(SERVER_CERT_FILE);
+    let server_key_path = dir.join(SERVER_KEY_FILE);
+    let reusable = server_key_path.exists()
+        && fs::read_to_string(&server_cert_path)
+            .map(|pem| covers_current_addresses(&pem))
+            .unwrap_or(false);
+    if !reusable {
+        let server_key = KeyPair::generate().map_err(|e| e.to_string())?;
+        let mut params =
+            CertificateParams::new(subject_alt_names(app)).map_err(|e| e.to_string())?;
+        params
+            .distinguished_name
+            .push(DnType::CommonName, resolve_desktop_name(app));
+        let server_cert = params
+            .signed_by(&server_key, &ca_cert, &ca_key)
+            .map_err(|e| format!("Failed to issue server certificate: {}", e))?;
+
+        fs::write(&server_cert_path, server_cert.pem()).map_err(|e| e.to_string())?;
+        write_private_file(&server_key_path, &server_key.serialize_pem())?;
+    }
+
+    let server_pem = fs::read(&server_cert_path).map_err(|e| e.to_string())?;
+    let key_pem = fs::read(&server_key_path).map_err(|e| e.to_string())?;
+    let certs = rustls_pemfile::certs(&mut server_pem.as_slice())
+        .collect::<Result<Vec<CertificateDer<'static>>, _>>()
+        .map_err(|e| e.to_string())?;
+    let key: PrivateKeyDer<'static> = rustls_pemfile::private_key(&mut key_pem.as_slice())
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Server key file is empty".to_string())?;
+
+    let fingerprint = certs
+        .first()
+        .map(|cert| sha256_fingerprint(cert.as_ref()))
+        .ok_or_else(|| "Server certificate file is empty".to_string())?;
+
+    // Clients pin the CA exactly as written to disk, so chain and fingerprint
+    // come from that copy rather than from the freshly re-signed `ca_cert`.
+    let ca_der = rustls_pemfile::certs(&mut ca_pem.as_bytes())
+        .next()
+        .ok_or_else(|| "CA certificate file is empty".to_string())?
+        .map_err(|e| e.to_string())?;
+
+    let mut chain = certs;
+    chain.push(ca_der.clone());
+
+    let server_config =
+        ServerConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .map_err(|e| e.to_string())?
+            .with_no_client_auth()
+            .with_single_cert(chain, key)
+            .map_err(|e| e.to_string())?;
+
+    Ok(TlsMaterial {
+        server_config: Arc::new(server_config),
+        ca_fingerprint: sha256_fingerprint(ca_der.as_ref()),
+        ca_pem,
+        fingerprint,
+    })
+}
+
+fn load_or_create_ca(
+    app: &AppHandle,
+    dir: &Path,
+) -> Result<(CertificateParams, KeyPair, String), String> {
+    let cert_path = dir.join(CA_CERT_FILE);
+    let key_path = dir.join(CA_KEY_FILE);
+
+    if cert_path.exists() && key_path.exists() {
+        let ca_pem = fs::read_to_string(&cert_path).map_err(|e| e.to_string())?;
+        let key_pem = fs::read_to_string(&key_path).map_err(|e| e.to_string())?;
+        let key = KeyPair::from_pem(&key_pem).map_err(|e| e.to_string())?;
+        let params = CertificateParams::from_ca_cert_pem(&ca_pem).map_err(|e| e.to_string())?;
+        // Keys written by earlier versions used the default permissions.
+        write_private_file(&key_path, &key_pem)?;
+        return Ok((params, key, ca_pem));
+    }
+
+    let key = KeyPair::generate().map_err(|e| e.to_string())?;
+    let mut params = CertificateParams::default();
+    let mut name = DistinguishedName::new();
+    name.push(
+        DnType::CommonName,
+        format!("Lumen Local CA ({})", resolve_desktop_name(app)),
+    );
+    name.push(DnType::OrganizationName, "Lumen");
+    params.distinguished_name = name;
+    params.is_ca = IsCa::Ca(BasicConstraints::Constrained(0));
+    params.key_usages = vec![KeyUsagePurpose::KeyCertSign, KeyUsagePurpose::CrlSign];
+
+    let cert = params
+        .clone()
+        .self_signed(&key)
+        .map_err(|e| format!("Failed to create local CA: {}", e))?;
+    let ca_pem = cert.pem();
+    fs::write(&cert_path, &ca_pem).map_err(|e| e.to_string())?;
+    write_private_file(&key_path, &key.serialize_pem())?;
+
+    Ok((params, key, ca_pem))
+}
+
+fn subject_alt_names(app: &AppHandle) -> Vec<String> {
+    let mut names = vec!["localhost".to_string()];
+
+    let desktop = resolve_desktop_name(app);
+    if desktop
+        .chars()
+        .all(|ch| ch.is_ascii_alphanumeric() || ch == '-' || ch == '.')
+    {
+        names.push(desktop.clone());
+        names.push(format!("{}.local", desktop));
+    }
+
+    for ip in interface_addresses() {
+        let ip = ip.to_string();
+        if !names.contains(&ip) {
+            names.push(ip);
+        }
+    }
+
+    names
+}
+
+fn interface_addresses() -> Vec<IpAddr> {
+    list_afinet_netifas()
+        .map(|interfaces| interfaces.into_iter().map(|(_, ip)| ip).collect())
+        .unwrap_or_default()
+}
+
+/// Whether a server certificate names every address the machine has now.
+fn covers_current_addresses(cert_pem: &str) -> bool {
+    let Ok(params) = CertificateParams::from_ca_cert_pem(cert_pem) else {
+        return false;
+    };
+    let issued = params
+        .subject_alt_names
+        .iter()
+        .filter_map(|name| match name {
+            SanType::IpAddress(ip) => Some(*ip),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    interface_addresses().iter().all(|ip| issued.contains(ip))
+}
+
+/// Writes a file readable only by the current user, for private keys and
+/// other secrets.
+pub(crate) fn write_private_file(path: &Path, contents: &str) -> Result<(), String> {
+    #[cfg(unix)]
+    {
+        use std::io::Write;
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(path)
+            .map_err(|e| e.to_string())?;
+        // `mode` only applies when the file is created.
+        file.set_permissions(fs::Permissions::from_mode(0o600))
+            .map_err(|e| e.to_string())?;
+        file.write_all(contents.as_bytes())
+            .map_err(|e| e.to_string())
+    }
+
+    #[cfg(not(unix))]
+    {
+        fs::write(path, contents).map_err(|e| e.to_string())
+    }
+}
+
+pub(crate) fn sha256_fingerprint(der: &[u8]) -> String {
+    Sha256::digest(der)
+        .iter()
+        .map(|byte| format!("{:02X}", byte))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+fn tls_dir() -> Result<PathBuf, String> {
+    Ok(app_base_dir()?.join("config").join("tls"))
+}
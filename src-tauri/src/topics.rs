@@ -0,0 +1,372 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    time::{Duration, Instant},
+};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::live_state::compose_patches;
+
+/// Longest throttle a client may ask for.
+pub const MAX_THROTTLE_MS: u64 = 60_000;
+
+/// Groups of server-to-client broadcasts a remote device can opt out of.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+pub enum Topic {
+    #[serde(rename = "player")]
+    Player,
+    /// Position ticks sent several times a second while media plays.
+    #[serde(rename = "player.progress")]
+    PlayerProgress,
+    #[serde(rename = "lyrics")]
+    Lyrics,
+    #[serde(rename = "queue")]
+    Queue,
+    #[serde(rename = "streaming")]
+    Streaming,
+    #[serde(rename = "devices")]
+    Devices,
+}
+
+impl Topic {
+    pub const ALL: [Topic; 6] = [
+        Topic::Player,
+        Topic::PlayerProgress,
+        Topic::Lyrics,
+        Topic::Queue,
+        Topic::Streaming,
+        Topic::Devices,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Topic::Player => "player",
+            Topic::PlayerProgress => "player.progress",
+            Topic::Lyrics => "lyrics",
+            Topic::Queue => "queue",
+            Topic::Streaming => "streaming",
+            Topic::Devices => "devices",
+        }
+    }
+
+    /// Topic of a broadcast event, or `None` for events every session gets.
+    pub fn for_event(event: &str, payload: &Value) -> Option<Topic> {
+        match event {
+            "player_sync" => {
+                let action = payload.get("action").and_then(Value::as_str);
+                if action == Some("interval") {
+                    Some(Topic::PlayerProgress)
+                } else {
+                    Some(Topic::Player)
+                }
+            }
+            "lyric_slide" => Some(Topic::Lyrics),
+            event if event.starts_with("queue") => Some(Topic::Queue),
+            event if event.starts_with("stream") => Some(Topic::Streaming),
+            event if event.starts_with("device") => Some(Topic::Devices),
+            _ => None,
+        }
+    }
+
+    /// Only high-frequency topics accept a throttle.
+    pub fn is_throttleable(self) -> bool {
+        matches!(self, Topic::PlayerProgress)
+    }
+}
+
+/// What a session wants to receive. New sessions get every topic unthrottled
+/// so clients that never subscribe keep the old behavior.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TopicSubscriptions {
+    topics: BTreeSet<Topic>,
+    throttle_ms: BTreeMap<Topic, u64>,
+    #[serde(skip)]
+    last_sent: HashMap<Topic, Instant>,
+    #[serde(skip)]
+    held: Option<HeldPatch>,
+}
+
+/// `state_patch` sections a throttle kept back, merged into one patch that
+/// goes out when the throttle ends or with the next patch sent before that.
+#[derive(Debug, Clone)]
+struct HeldPatch {
+    sections: Map<String, Value>,
+    revision: u64,
+    topics: BTreeSet<Topic>,
+}
+
+impl Default for TopicSubscriptions {
+    fn default() -> Self {
+        Self {
+            topics: Topic::ALL.into_iter().collect(),
+            throttle_ms: BTreeMap::new(),
+            last_sent: HashMap::new(),
+            held: None,
+        }
+    }
+}
+
+impl TopicSubscriptions {
+    /// Adds `topics`. A `throttle_ms` applies to each of them; `0` removes
+    /// the throttle.
+    pub fn subscribe(&mut self, topics: &[Topic], throttle_ms: Option<u64>) -> Result<(), String> {
+        if let Some(throttle_ms) = throttle_ms {
+            if throttle_ms > MAX_THROTTLE_MS {
+                return Err(format!("`throttle_ms` must be at most {}", MAX_THROTTLE_MS));
+            }
+            if let Some(topic) = topics.iter().find(|topic| !topic.is_throttleable()) {
+                return Err(format!("Topic `{}` cannot be throttled", topic.as_str()));
+            }
+        }
+
+        for topic in topics {
+            self.topics.insert(*topic);
+            match throttle_ms {
+                Some(0) => {
+                    self.throttle_ms.remove(topic);
+                }
+                Some(throttle_ms) => {
+                    self.throttle_ms.insert(*topic, throttle_ms);
+                }
+                None => {}
+            }
+        }
+        Ok(())
+    }
+
+    pub fn unsubscribe(&mut self, topics: &[Topic]) {
+        for topic in topics {
+            self.topics.remove(topic);
+            self.throttle_ms.remove(topic);
+            self.last_sent.remove(topic);
+        }
+        if self
+            .held
+            .as_ref()
+            .is_some_and(|held| topics.iter().any(|topic| held.topics.contains(topic)))
+        {
+            self.held = None;
+        }
+    }
+
+    pub fn is_subscribed(&self, topic: Topic) -> bool {
+        self.topics.contains(&topic)
+    }
+
+    /// Whether a message on `topic` should go out now. Records the send, so
+    /// only call it right before sending.
+    pub fn admit(&mut self, topic: Topic) -> bool {
+        if !self.is_subscribed(topic) {
+            return false;
+        }
+
+        if let Some(throttle_ms) = self.throttle_ms.get(&topic) {
+            let now = Instant::now();
+            let throttled = self.last_sent.get(&topic).is_some_and(|last_sent| {
+                now.duration_since(*last_sent) < Duration::from_millis(*throttle_ms)
+            });
+            if throttled {
+                return false;
+            }
+            self.last_sent.insert(topic, now);
+        }
+        true
+    }
+
+    /// Keeps a patch section that `admit` turned away until the throttle on
+    /// `topic` ends. Returns how long until then when nothing was held yet,
+    /// so the caller schedules one `take_held` per window.
+    pub fn hold(
+        &mut self,
+        topic: Topic,
+        section: &str,
+        value: &Value,
+        revision: u64,
+    ) -> Option<Duration> {
+        let delay = self.throttle_remaining(topic);
+        let first = self.held.is_none();
+        let held = self.held.get_or_insert_with(|| HeldPatch {
+            sections: Map::new(),
+            revision,
+            topics: BTreeSet::new(),
+        });
+        compose_patches(
+            held.sections
+                .entry(section.to_string())
+                .or_insert(Value::Null),
+            value,
+        );
+        held.revision = revision;
+        held.topics.insert(topic);
+        first.then_some(delay)
+    }
+
+    /// Puts anything held in front of `sections`, which are about to go out
+    /// with a later revision, so the held changes are not lost.
+    pub fn release_into(&mut self, sections: Map<String, Value>) -> Map<String, Value> {
+        let Some(held) = self.held.take() else {
+            return sections;
+        };
+        let mut merged = Value::Object(held.sections);
+        compose_patches(&mut merged, &Value::Object(sections));
+        match merged {
+            Value::Object(merged) => merged,
+            _ => Map::new(),
+        }
+    }
+
+    /// Takes the held sections and their latest revision once the throttle
+    /// has ended, recording the send.
+    pub fn take_held(&mut self) -> Option<(Map<String, Value>, u64)> {
+        let held = self.held.take()?;
+        let now = Instant::now();
+        for topic in &held.topics {
+            self.last_sent.insert(*topic, now);
+        }
+        Some((held.sections, held.revision))
+    }
+
+    fn throttle_remaining(&self, topic: Topic) -> Duration {
+        let (Some(throttle_ms), Some(last_sent)) =
+            (self.throttle_ms.get(&topic), self.last_sent.get(&topic))
+        else {
+            return Duration::ZERO;
+        };
+        Duration::from_millis(*throttle_ms).saturating_sub(last_sent.elapsed())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn throttled() -> TopicSubscriptions {
+        let mut subscriptions = TopicSubscriptions::default();
+        subscriptions
+            .subscribe(&[Topic::PlayerProgress], Some(MAX_THROTTLE_MS))
+            .unwrap();
+        assert!(subscriptions.admit(Topic::PlayerProgress));
+        subscriptions
+    }
+
+    fn sections(value: Value) -> Map<String, Value> {
+        match value {
+            Value::Object(sections) => sections,
+            _ => panic!("expected an object"),
+        }
+    }
+
+    #[test]
+    fn unsubscribed_topics_are_filtered() {
+        let mut subscriptions = TopicSubscriptions::default();
+        assert!(Topic::ALL.iter().all(|topic| subscriptions.admit(*topic)));
+
+        subscriptions.unsubscribe(&[Topic::Lyrics]);
+        assert!(!subscriptions.admit(Topic::Lyrics));
+        assert!(subscriptions.admit(Topic::Player));
+
+        subscriptions.subscribe(&[Topic::Lyrics], None).unwrap();
+        assert!(subscriptions.admit(Topic::Lyrics));
+    }
+
+    #[test]
+    fn subscribe_rejects_invalid_throttles() {
+        let mut subscriptions = TopicSubscriptions::default();
+        assert!(
+            subscriptions
+                .subscribe(&[Topic::Player], Some(100))
+                .is_err()
+        );
+        assert!(
+            subscriptions
+                .subscribe(&[Topic::PlayerProgress], Some(MAX_THROTTLE_MS + 1))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn throttle_turns_away_sends_inside_the_window() {
+        let mut subscriptions = throttled();
+        assert!(!subscriptions.admit(Topic::PlayerProgress));
+        assert!(subscriptions.admit(Topic::Player));
+
+        subscriptions
+            .subscribe(&[Topic::PlayerProgress], Some(0))
+            .unwrap();
+        assert!(subscriptions.admit(Topic::PlayerProgress));
+    }
+
+    #[test]
+    fn held_sections_merge_into_the_next_patch() {
+        let mut subscriptions = throttled();
+        let delay = subscriptions.hold(
+            Topic::PlayerProgress,
+            "player",
+            &json!({ "position": 1.0, "volume": 50 }),
+            3,
+        );
+        assert!(delay.is_some_and(|delay| delay <= Duration::from_millis(MAX_THROTTLE_MS)));
+        let again = subscriptions.hold(
+            Topic::PlayerProgress,
+            "player",
+            &json!({ "position": 2.0 }),
+            4,
+        );
+        assert_eq!(again, None);
+
+        let merged = subscriptions.release_into(sections(json!({ "lyrics": { "slide": 1 } })));
+        assert_eq!(
+            Value::Object(merged),
+            json!({
+                "player": { "position": 2.0, "volume": 50 },
+                "lyrics": { "slide": 1 },
+            })
+        );
+        assert_eq!(subscriptions.take_held(), None);
+    }
+
+    #[test]
+    fn take_held_empties_the_buffer_and_records_the_send() {
+        let mut subscriptions = throttled();
+        subscriptions.hold(
+            Topic::PlayerProgress,
+            "player",
+            &json!({ "position": 1.0 }),
+            5,
+        );
+        subscriptions.hold(
+            Topic::PlayerProgress,
+            "player",
+            &json!({ "position": 2.0 }),
+            6,
+        );
+
+        let (held, revision) = subscriptions.take_held().unwrap();
+        assert_eq!(
+            Value::Object(held),
+            json!({ "player": { "position": 2.0 } })
+        );
+        assert_eq!(revision, 6);
+        assert_eq!(subscriptions.take_held(), None);
+        assert!(!subscriptions.admit(Topic::PlayerProgress));
+    }
+
+    #[test]
+    fn unsubscribe_drops_held_sections() {
+        let mut subscriptions = throttled();
+        subscriptions.hold(
+            Topic::PlayerProgress,
+            "player",
+            &json!({ "position": 1.0 }),
+            2,
+        );
+        subscriptions.unsubscribe(&[Topic::PlayerProgress]);
+        assert_eq!(subscriptions.take_held(), None);
+    }
+}
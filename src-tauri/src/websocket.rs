@@ -1,99 +1,164 @@
+use crate::audit::{self, AuditActor, AuditOutcome};
+use crate::bible;
+use crate::control_limits::{
+    INTERNAL_MAX_MESSAGE_BYTES, MESSAGE_TOO_BIG_CLOSE_CODE, RATE_LIMITED_CLOSE_CODE,
+    TOO_MANY_CONNECTIONS_CLOSE_CODE, TokenBucket, acquire_connection, control_limits,
+};
+use crate::control_lock;
+use crate::device_tokens::TOKEN_REVOKED_CLOSE_CODE;
 use crate::devices::{
-    AuthPayload, DeviceState, RegisterPayload, auth_fail_message, authenticate_device,
-    deactivate_device_registration, device_deactivated_message, is_permission_allowed,
-    is_remote_access_enabled, map_event_permission, permission_denied_message, register_device,
-    remove_session, touch_session,
+    AuthPayload, DeviceState, RegisterPayload, RemoteAccessSettings, auth_fail_message,
+    authenticate_device, close_message, deactivate_device_registration, device_deactivated_message,
+    is_pairing_approval_enabled, is_remote_access_enabled, now_ts, permission_denied_message,
+    register_device, remove_session, set_lyrics_language_for_device, touch_session,
+    update_session_subscriptions,
+};
+use crate::internal_auth;
+use crate::live_state;
+use crate::media_library;
+use crate::pairing;
+use crate::permissions::{is_permission_allowed, required_scope};
+use crate::presence::{PresenceReason, emit_presence};
+use crate::protocol::{
+    ClientEnvelope, ClientMessage, ErrorCode, ErrorReply, Responder, check_protocol_version,
+    parse_client_message,
 };
+use crate::remote_commands::{expect_confirmation, reply_when_confirmed};
 use crate::streaming::{
-    StreamErrorPayload, WebRtcIceCandidatePayload, add_webrtc_ice_candidate, handle_mobile_offer,
-    handle_session_closed, set_webrtc_answer, subscribe_stream, unsubscribe_stream,
+    StreamErrorPayload, add_webrtc_ice_candidate, handle_mobile_offer, handle_session_closed,
+    set_webrtc_answer, subscribe_stream, unsubscribe_stream,
 };
+use crate::tls::tls_acceptor;
 use futures_util::{SinkExt, StreamExt as FStreamExt};
-use serde::{Deserialize, Serialize};
+use serde::Serialize;
 use serde_json::{Value, json};
 use std::{
     net::SocketAddr,
-    time::{SystemTime, UNIX_EPOCH},
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    sync::{Notify, mpsc},
+};
+use tokio_tungstenite::{
+    WebSocketStream,
+    tungstenite::{
+        Error as WsError, Message,
+        handshake::server::{ErrorResponse, Request, Response},
+        protocol::WebSocketConfig,
+    },
 };
-use tauri::{AppHandle, Emitter, Manager};
-use tokio::sync::mpsc;
-use tokio_tungstenite::tungstenite::Message;
-
-#[derive(Debug, Deserialize, Serialize, Clone)]
-struct AudioEvent {
-    event: String,
-    value: Option<f64>,
-    duration: Option<f64>,
-    title: Option<String>,
-    url: Option<String>,
-    artist: Option<String>,
-}
 
-#[derive(Debug, Deserialize)]
-struct RegisterMessage {
-    token: String,
-    device_id: String,
-    device_name: String,
-    device_type: String,
-    os: String,
-    version: String,
-}
+const TLS_HANDSHAKE_RECORD: u8 = 0x16;
+const WRITER_DRAIN_TIMEOUT: Duration = Duration::from_secs(1);
 
-#[derive(Debug, Deserialize)]
-struct AuthMessage {
-    device_id: String,
-    access_token: String,
-}
-
-#[derive(Debug, Deserialize)]
-struct SubscribeStreamMessage {
-    stream_type: String,
-}
+pub async fn accept_connection(peer: SocketAddr, stream: tokio::net::TcpStream, app: AppHandle) {
+    let acceptor = match tls_acceptor(&app) {
+        Ok(acceptor) => acceptor,
+        Err(error) => {
+            eprintln!("TLS unavailable for connection from {}: {}", peer, error);
+            None
+        }
+    };
 
-#[derive(Debug, Deserialize)]
-struct WebRtcAnswerMessage {
-    stream_type: String,
-    sdp: String,
-}
+    let Some(acceptor) = acceptor else {
+        serve_connection(peer, stream, app).await;
+        return;
+    };
 
-#[derive(Debug, Deserialize)]
-struct MobileOfferMessage {
-    sdp: String,
-    video_orientation: Option<String>,
-}
+    // With TLS on, the desktop's own windows keep using plain ws://localhost,
+    // so the first byte decides which handshake to run.
+    let mut first_byte = [0u8; 1];
+    let is_tls = matches!(
+        stream.peek(&mut first_byte).await,
+        Ok(1) if first_byte[0] == TLS_HANDSHAKE_RECORD
+    );
 
-#[derive(Debug, Deserialize)]
-struct WebRtcIceCandidateMessage {
-    stream_type: String,
-    candidate: WebRtcIceCandidatePayload,
+    if is_tls {
+        match acceptor.accept(stream).await {
+            Ok(tls_stream) => serve_connection(peer, tls_stream, app).await,
+            Err(error) => eprintln!("TLS handshake error from {}: {}", peer, error),
+        }
+    } else if peer.ip().is_loopback() {
+        serve_connection(peer, stream, app).await;
+    } else {
+        eprintln!(
+            "Rejected plain connection from {} while TLS is required",
+            peer
+        );
+    }
 }
 
-pub async fn accept_connection(peer: SocketAddr, stream: tokio::net::TcpStream, app: AppHandle) {
+async fn serve_connection<S>(peer: SocketAddr, stream: S, app: AppHandle)
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
     if let Err(error) = handle_connection(peer, stream, app).await {
         eprintln!("Error handling connection from {}: {}", peer, error);
     }
 }
 
-async fn handle_connection(
+async fn handle_connection<S>(
     peer: SocketAddr,
-    stream: tokio::net::TcpStream,
+    stream: S,
     app: AppHandle,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    if peer.ip().is_loopback() {
-        return handle_internal_connection(peer, stream, app).await;
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let loopback = peer.ip().is_loopback();
+    // The size limits are fixed before the handshake shows whether a
+    // loopback peer holds the internal secret, so loopback peers get the
+    // larger internal ceiling here and the external connection handler
+    // enforces `max_message_bytes` on those without the secret.
+    let max_message_bytes = if loopback {
+        INTERNAL_MAX_MESSAGE_BYTES
+    } else {
+        control_limits(&app.state::<DeviceState>()).max_message_bytes
+    };
+    let ws_config = WebSocketConfig {
+        max_message_size: Some(max_message_bytes),
+        max_frame_size: Some(max_message_bytes),
+        ..WebSocketConfig::default()
+    };
+
+    let mut presented = None;
+    // The callback's error type is fixed by tungstenite.
+    #[allow(clippy::result_large_err)]
+    let ws_stream = tokio_tungstenite::accept_hdr_async_with_config(
+        stream,
+        |request: &Request, response: Response| -> Result<Response, ErrorResponse> {
+            presented = internal_auth::presented_secret(request.uri());
+            Ok(response)
+        },
+        Some(ws_config),
+    )
+    .await
+    .map_err(|e| format!("WebSocket handshake error: {}", e))?;
+
+    if loopback {
+        if internal_auth::is_internal(&app, presented.as_deref()) {
+            return handle_internal_connection(peer, ws_stream, app).await;
+        }
+        println!(
+            "Loopback connection from {} without the internal secret; treating it as external",
+            peer
+        );
     }
 
-    handle_external_connection(peer, stream, app).await
+    handle_external_connection(peer, ws_stream, app).await
 }
 
-async fn handle_internal_connection(
+async fn handle_internal_connection<S>(
     peer: SocketAddr,
-    stream: tokio::net::TcpStream,
+    ws_stream: WebSocketStream<S>,
     app: AppHandle,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let ws_stream = tokio_tungstenite::accept_async(stream)
-        .await
-        .map_err(|e| format!("WebSocket handshake error: {}", e))?;
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
     println!("New internal WebSocket connection: {}", peer);
 
     let (mut outgoing, mut incoming) = ws_stream.split();
@@ -112,39 +177,42 @@ async fn handle_internal_connection(
             Ok(msg) => {
                 if msg.is_text() {
                     let text = msg.to_text()?;
-                    match serde_json::from_str::<Value>(text) {
-                        Ok(value) => {
-                            if let Some(event_name) = value.get("event").and_then(Value::as_str) {
-                                if handle_streaming_event(
-                                    &app,
-                                    &sender,
-                                    &internal_session_id,
-                                    "internal",
-                                    event_name,
-                                    &value,
-                                )
-                                .await?
-                                {
-                                    continue;
-                                }
-                            }
-
-                            match serde_json::from_value::<AudioEvent>(value) {
-                                Ok(audio_event) => {
-                                    handle_audio_event(&app, &peer.to_string(), &audio_event)?
-                                }
-                                Err(error) => {
-                                    eprintln!(
-                                        "Failed to parse internal message from {}: {}",
-                                        peer, error
-                                    );
-                                }
-                            }
-                        }
+                    let ClientEnvelope {
+                        request_id,
+                        message,
+                    } = match parse_client_message(text) {
+                        Ok(envelope) => envelope,
                         Err(error) => {
-                            eprintln!("Failed to parse internal message from {}: {}", peer, error);
+                            eprintln!("Rejected internal message from {}: {}", peer, error.message);
+                            let _ = sender.send(error.to_message()?);
+                            continue;
                         }
+                    };
+                    let responder =
+                        Responder::new(sender.clone(), request_id, message.event_name());
+
+                    if handle_library_event(&app, &responder, &message)
+                        .await
+                        .is_some()
+                    {
+                        continue;
                     }
+
+                    if handle_streaming_event(
+                        &app,
+                        &sender,
+                        &responder,
+                        &internal_session_id,
+                        "internal",
+                        &message,
+                    )
+                    .await
+                    .is_some()
+                    {
+                        continue;
+                    }
+
+                    let _ = dispatch_control_message(&app, &peer.to_string(), &message, responder);
                 } else if msg.is_ping() {
                     let _ = sender.send(Message::Pong(msg.into_data()));
                 } else if msg.is_close() {
@@ -164,19 +232,34 @@ async fn handle_internal_connection(
     Ok(())
 }
 
-async fn handle_external_connection(
+async fn handle_external_connection<S>(
     peer: SocketAddr,
-    stream: tokio::net::TcpStream,
+    mut ws_stream: WebSocketStream<S>,
     app: AppHandle,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let ws_stream = tokio_tungstenite::accept_async(stream)
-        .await
-        .map_err(|e| format!("WebSocket handshake error: {}", e))?;
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let state = app.state::<DeviceState>();
+    let limits = control_limits(&state);
+
+    let Some(_connection) = acquire_connection(&app, peer.ip(), limits.max_connections_per_ip)
+    else {
+        eprintln!(
+            "Rejected connection from {}: more than {} open connections from this address",
+            peer, limits.max_connections_per_ip
+        );
+        let _ = ws_stream
+            .send(close_message(TOO_MANY_CONNECTIONS_CLOSE_CODE))
+            .await;
+        return Ok(());
+    };
     println!("New external WebSocket connection: {}", peer);
+    let peer_address = peer.to_string();
 
     let (mut outgoing, mut incoming) = ws_stream.split();
     let (sender, mut receiver) = mpsc::unbounded_channel::<Message>();
-    let writer = tauri::async_runtime::spawn(async move {
+    let mut writer = tauri::async_runtime::spawn(async move {
         while let Some(message) = receiver.recv().await {
             if outgoing.send(message).await.is_err() {
                 break;
@@ -184,61 +267,181 @@ async fn handle_external_connection(
         }
     });
 
-    let state = app.state::<DeviceState>();
     let mut session_id: Option<String> = None;
+    let mut closer: Option<Arc<Notify>> = None;
+    // A `register` waiting for the operator; the device keeps the
+    // connection open (and sends heartbeats) until it is approved.
+    let mut pairing_request: Option<String> = None;
+    let mut bucket = TokenBucket::new(limits.messages_per_second, limits.burst);
+    let mut rate_limited_in_a_row = 0u32;
+
+    loop {
+        // Before auth the connection gets one session timeout to identify
+        // itself; afterwards the session reaper decides when it is stale.
+        let next = match &closer {
+            Some(closer) => tokio::select! {
+                next = incoming.next() => next,
+                _ = closer.notified() => break,
+            },
+            None => match tokio::time::timeout(session_timeout(&state), incoming.next()).await {
+                Ok(next) => next,
+                Err(_) => break,
+            },
+        };
+        let Some(msg) = next else {
+            break;
+        };
 
-    while let Some(msg) = incoming.next().await {
         match msg {
             Ok(msg) => {
-                if msg.is_ping() {
-                    let _ = sender.send(Message::Pong(msg.into_data()));
-                    continue;
+                if let Some(active_session_id) = session_id.as_deref() {
+                    touch_session(&state, active_session_id)?;
                 }
 
                 if msg.is_close() {
                     break;
                 }
 
+                // Catches loopback peers without the internal secret;
+                // tungstenite already enforces the limit for everyone else.
+                if msg.len() > limits.max_message_bytes {
+                    eprintln!(
+                        "Closing connection from {}: message over {} bytes",
+                        peer, limits.max_message_bytes
+                    );
+                    let _ = sender.send(close_message(MESSAGE_TOO_BIG_CLOSE_CODE));
+                    break;
+                }
+
+                if !msg.is_pong() {
+                    if bucket.try_take() {
+                        rate_limited_in_a_row = 0;
+                    } else {
+                        rate_limited_in_a_row += 1;
+                        if rate_limited_in_a_row > limits.burst {
+                            eprintln!(
+                                "Closing connection from {}: over {} messages per second",
+                                peer, limits.messages_per_second
+                            );
+                            let _ = sender.send(close_message(RATE_LIMITED_CLOSE_CODE));
+                            break;
+                        }
+                        if msg.is_text() {
+                            let reply = ErrorReply::new(
+                                ErrorCode::RateLimited,
+                                format!(
+                                    "Over {} messages per second; message dropped",
+                                    limits.messages_per_second
+                                ),
+                                None,
+                            );
+                            let _ = sender.send(reply.to_message()?);
+                        }
+                        continue;
+                    }
+                }
+
+                if msg.is_ping() {
+                    let _ = sender.send(Message::Pong(msg.into_data()));
+                    continue;
+                }
+
                 if !msg.is_text() {
                     continue;
                 }
 
                 let text = msg.to_text()?;
-                let value: Value = match serde_json::from_str(text) {
-                    Ok(value) => value,
+                let ClientEnvelope {
+                    request_id,
+                    message,
+                } = match parse_client_message(text) {
+                    Ok(envelope) => envelope,
                     Err(error) => {
-                        eprintln!("Failed to parse external message from {}: {}", peer, error);
+                        let _ = sender.send(error.to_message()?);
                         continue;
                     }
                 };
-
-                let Some(event_name) = value.get("event").and_then(Value::as_str) else {
-                    continue;
-                };
+                let event_name = message.event_name();
+                let responder = Responder::new(sender.clone(), request_id, event_name);
 
                 if session_id.is_none() {
-                    match event_name {
-                        "register" => {
+                    match message {
+                        ClientMessage::Register {
+                            token,
+                            device_id,
+                            device_name,
+                            device_type,
+                            os,
+                            version,
+                            protocol_version,
+                        } => {
                             if !is_remote_access_enabled(&state)? {
                                 let _ = sender.send(auth_fail_message("unauthorized")?);
                                 let _ = sender.send(close_message(4001));
                                 break;
                             }
 
-                            let payload: RegisterMessage = serde_json::from_value(value.clone())?;
-                            let response = match register_device(
+                            if let Err(error) = check_protocol_version(protocol_version, event_name)
+                            {
+                                responder.send_error(error);
+                                continue;
+                            }
+
+                            let payload = RegisterPayload {
+                                token,
+                                device_id,
+                                device_name,
+                                device_type,
+                                os,
+                                version,
+                            };
+
+                            if payload.token.is_empty() && is_pairing_approval_enabled(&state)? {
+                                match pairing::request_approval(
+                                    &app,
+                                    &payload,
+                                    peer.ip().to_string(),
+                                    sender.clone(),
+                                ) {
+                                    Ok((request_id, reply)) => {
+                                        if let Some(previous) = pairing_request.replace(request_id)
+                                        {
+                                            pairing::withdraw_request(&app, &previous)?;
+                                        }
+                                        let _ = sender.send(reply);
+                                    }
+                                    Err(reason) => {
+                                        let _ = sender.send(auth_fail_message(&reason)?);
+                                    }
+                                }
+                                continue;
+                            }
+
+                            let device_id = payload.device_id.clone();
+                            let summary = format!(
+                                "{} ({} {}, {})",
+                                payload.device_name,
+                                payload.device_type,
+                                payload.os,
+                                payload.version
+                            );
+                            let result = register_device(&app, &state, payload, sender.clone());
+                            audit::record(
                                 &app,
-                                &state,
-                                RegisterPayload {
-                                    token: payload.token,
-                                    device_id: payload.device_id,
-                                    device_name: payload.device_name,
-                                    device_type: payload.device_type,
-                                    os: payload.os,
-                                    version: payload.version,
+                                AuditActor {
+                                    device_id: Some(&device_id),
+                                    session_id: result
+                                        .as_ref()
+                                        .ok()
+                                        .map(|response| response.session_id.as_str()),
+                                    peer: Some(&peer_address),
                                 },
-                                sender.clone(),
-                            ) {
+                                audit::AUTH,
+                                event_name,
+                                &summary,
+                                AuditOutcome::of(&result),
+                            );
+                            let response = match result {
                                 Ok(response) => response,
                                 Err(reason) => {
                                     let _ = sender.send(auth_fail_message(&reason)?);
@@ -246,20 +449,49 @@ async fn handle_external_connection(
                                 }
                             };
 
+                            if let Some(request_id) = pairing_request.take() {
+                                pairing::withdraw_request(&app, &request_id)?;
+                            }
                             let _ = sender.send(json_message(&response)?);
+                            closer = start_session(&app, &state, &sender, &response.session_id)?;
                             session_id = Some(response.session_id);
                         }
-                        "auth" => {
-                            let payload: AuthMessage = serde_json::from_value(value.clone())?;
-                            let response = match authenticate_device(
+                        ClientMessage::Auth {
+                            device_id,
+                            access_token,
+                            protocol_version,
+                        } => {
+                            if let Err(error) = check_protocol_version(protocol_version, event_name)
+                            {
+                                responder.send_error(error);
+                                continue;
+                            }
+
+                            let result = authenticate_device(
                                 &app,
                                 &state,
                                 AuthPayload {
-                                    device_id: payload.device_id,
-                                    access_token: payload.access_token,
+                                    device_id: device_id.clone(),
+                                    access_token,
                                 },
                                 sender.clone(),
-                            ) {
+                            );
+                            audit::record(
+                                &app,
+                                AuditActor {
+                                    device_id: Some(&device_id),
+                                    session_id: result
+                                        .as_ref()
+                                        .ok()
+                                        .map(|response| response.session_id.as_str()),
+                                    peer: Some(&peer_address),
+                                },
+                                audit::AUTH,
+                                event_name,
+                                "",
+                                AuditOutcome::of(&result),
+                            );
+                            let response = match result {
                                 Ok(response) => response,
                                 Err(reason) => {
                                     let _ = sender.send(auth_fail_message(&reason)?);
@@ -268,11 +500,13 @@ async fn handle_external_connection(
                                         "unauthorized"
                                             | "not_registered"
                                             | "invalid_token"
+                                            | "access_token_expired"
                                             | "not_active"
                                     ) {
                                         let close_code = match reason.as_str() {
                                             "not_registered" => 4003,
                                             "invalid_token" => 4004,
+                                            "access_token_expired" => TOKEN_REVOKED_CLOSE_CODE,
                                             "not_active" => 4005,
                                             _ => 4001,
                                         };
@@ -284,9 +518,14 @@ async fn handle_external_connection(
                             };
 
                             let _ = sender.send(json_message(&response)?);
+                            closer = start_session(&app, &state, &sender, &response.session_id)?;
                             session_id = Some(response.session_id);
                         }
-                        _ => {}
+                        _ => responder.send_error(ErrorReply::new(
+                            ErrorCode::Unauthorized,
+                            "Send `register` or `auth` before other events",
+                            Some(event_name),
+                        )),
                     }
 
                     continue;
@@ -299,51 +538,248 @@ async fn handle_external_connection(
                 let Some(session) = touch_session(&state, active_session_id)? else {
                     continue;
                 };
+                let actor = AuditActor {
+                    device_id: Some(&session.device_id),
+                    session_id: Some(&session.session_id),
+                    peer: Some(&peer_address),
+                };
 
-                if event_name == "forget_device" {
+                if matches!(message, ClientMessage::ForgetDevice) {
+                    audit::record(
+                        &app,
+                        actor,
+                        audit::COMMAND,
+                        event_name,
+                        "",
+                        AuditOutcome::Ok,
+                    );
+                    responder.result(json!({}));
                     let _ = sender.send(device_deactivated_message(&session.device_id)?);
                     deactivate_device_registration(&app, &state, &session.device_id)?;
                     break;
                 }
 
-                if let Some(required_permission) = map_event_permission(event_name) {
-                    if !is_permission_allowed(&session.permissions, required_permission) {
-                        if is_streaming_event(event_name) {
-                            let _ = sender.send(stream_error_message(
-                                stream_type_for_event(event_name, &value),
-                                "no_permission",
-                            )?);
-                        } else {
-                            let _ = sender.send(permission_denied_message(event_name)?);
-                        }
-                        continue;
+                if let Some(required_scope) = required_scope(&message)
+                    .filter(|scope| !is_permission_allowed(&session.permissions, scope))
+                {
+                    audit::record(
+                        &app,
+                        actor,
+                        audit_category(&message),
+                        event_name,
+                        &audit_summary(&message),
+                        AuditOutcome::Denied,
+                    );
+                    if let Some(stream_type) = message.stream_type() {
+                        let _ = sender.send(stream_error_message(stream_type, "no_permission")?);
+                    } else {
+                        let _ = sender.send(permission_denied_message(event_name)?);
                     }
+                    responder.error(
+                        ErrorCode::PermissionDenied,
+                        format!("Device lacks the `{}` permission", required_scope),
+                    );
+                    continue;
                 }
 
-                if handle_streaming_event(
-                    &app,
-                    &sender,
-                    &session.session_id,
-                    &session.device_id,
-                    event_name,
-                    &value,
-                )
-                .await?
+                if let Some(lock) = control_lock::blocking_lock(&app, &session.session_id, &message)
                 {
+                    audit::record(
+                        &app,
+                        actor,
+                        audit_category(&message),
+                        event_name,
+                        &audit_summary(&message),
+                        AuditOutcome::Denied,
+                    );
+                    let _ = sender.send(json_message(&json!({
+                        "event": "control_locked",
+                        "feature": event_name,
+                        "lock": lock,
+                    }))?);
+                    responder.error(
+                        ErrorCode::ControlLocked,
+                        format!("`{}` is locked by {}", lock.scope, lock.device_name),
+                    );
                     continue;
                 }
 
-                match serde_json::from_value::<AudioEvent>(value.clone()) {
-                    Ok(audio_event) => {
-                        handle_audio_event(&app, &peer.to_string(), &audio_event)?;
+                if let ClientMessage::RequestControl { scope, lease_secs } = &message {
+                    let result = control_lock::acquire(&app, &session, scope, *lease_secs);
+                    audit::record(
+                        &app,
+                        actor,
+                        audit::COMMAND,
+                        event_name,
+                        scope,
+                        match &result {
+                            Ok(_) => AuditOutcome::Ok,
+                            Err(error) => AuditOutcome::Failed(&error.message),
+                        },
+                    );
+                    match result {
+                        Ok(lock) => responder.result(json!({ "lock": lock })),
+                        Err(error) => responder.send_error(error),
                     }
-                    Err(error) => {
-                        eprintln!(
-                            "Failed to parse external audio event from {}: {}",
-                            peer, error
-                        );
+                    continue;
+                }
+
+                if let ClientMessage::ReleaseControl { scope } = &message {
+                    let result = control_lock::release(&app, &session.session_id, scope);
+                    audit::record(
+                        &app,
+                        actor,
+                        audit::COMMAND,
+                        event_name,
+                        scope,
+                        match &result {
+                            Ok(_) => AuditOutcome::Ok,
+                            Err(error) => AuditOutcome::Failed(&error.message),
+                        },
+                    );
+                    match result {
+                        Ok(()) => responder.result(json!({})),
+                        Err(error) => responder.send_error(error),
+                    }
+                    continue;
+                }
+
+                if matches!(message, ClientMessage::Heartbeat) {
+                    let _ = sender.send(json_message(&json!({
+                        "event": "heartbeat_ack",
+                        "ts": now_ts(),
+                    }))?);
+                    responder.result(json!({}));
+                    continue;
+                }
+
+                if matches!(message, ClientMessage::GetState) {
+                    let revision = live_state::send_snapshot(
+                        &app,
+                        &sender,
+                        &session.permissions,
+                        &session.lyrics_language,
+                    )?;
+                    control_lock::send_locks(&app, &sender)?;
+                    responder.result(json!({ "revision": revision }));
+                    continue;
+                }
+
+                if let ClientMessage::Subscribe { .. } | ClientMessage::Unsubscribe { .. } =
+                    &message
+                {
+                    let updated = update_session_subscriptions(
+                        &state,
+                        &session.session_id,
+                        |subscriptions| match &message {
+                            ClientMessage::Subscribe {
+                                topics,
+                                throttle_ms,
+                            } => subscriptions.subscribe(topics, *throttle_ms),
+                            ClientMessage::Unsubscribe { topics } => {
+                                subscriptions.unsubscribe(topics);
+                                Ok(())
+                            }
+                            _ => Ok(()),
+                        },
+                    );
+                    audit::record(
+                        &app,
+                        actor,
+                        audit::COMMAND,
+                        event_name,
+                        &audit_summary(&message),
+                        AuditOutcome::of(&updated),
+                    );
+                    let subscriptions = match updated {
+                        Ok(subscriptions) => subscriptions,
+                        Err(reason) => {
+                            responder.send_error(ErrorReply::new(
+                                ErrorCode::InvalidPayload,
+                                reason,
+                                Some(event_name),
+                            ));
+                            continue;
+                        }
+                    };
+                    let _ = sender.send(json_message(&json!({
+                        "event": "subscriptions_updated",
+                        "subscriptions": subscriptions,
+                    }))?);
+                    responder.result(json!({ "subscriptions": subscriptions }));
+                    if matches!(message, ClientMessage::Subscribe { .. }) {
+                        live_state::send_snapshot(
+                            &app,
+                            &sender,
+                            &session.permissions,
+                            &session.lyrics_language,
+                        )?;
                     }
+                    continue;
                 }
+
+                if let ClientMessage::SetLyricsLanguage { language } = &message {
+                    let updated = set_lyrics_language_for_device(
+                        &state,
+                        &session.device_id,
+                        language.as_deref(),
+                    )?;
+                    audit::record(
+                        &app,
+                        actor,
+                        audit::COMMAND,
+                        event_name,
+                        &updated.lyrics_language,
+                        AuditOutcome::Ok,
+                    );
+                    let _ = sender.send(json_message(&json!({
+                        "event": "lyrics_language_updated",
+                        "language": updated.lyrics_language,
+                    }))?);
+                    responder.result(json!({ "language": updated.lyrics_language }));
+                    let _ = app.emit("device_updated", updated);
+                    continue;
+                }
+
+                let outcome =
+                    if let Some(outcome) = handle_library_event(&app, &responder, &message).await {
+                        outcome
+                    } else if let Some(outcome) = handle_streaming_event(
+                        &app,
+                        &sender,
+                        &responder,
+                        &session.session_id,
+                        &session.device_id,
+                        &message,
+                    )
+                    .await
+                    {
+                        outcome
+                    } else {
+                        dispatch_control_message(&app, &peer_address, &message, responder)
+                    };
+                // WebRTC negotiation is too chatty to be worth keeping.
+                if !matches!(
+                    message,
+                    ClientMessage::WebrtcAnswer { .. } | ClientMessage::WebrtcIceCandidate { .. }
+                ) {
+                    audit::record(
+                        &app,
+                        actor,
+                        audit_category(&message),
+                        event_name,
+                        &audit_summary(&message),
+                        AuditOutcome::of(&outcome),
+                    );
+                }
+            }
+            Err(WsError::Capacity(error)) => {
+                eprintln!(
+                    "Closing connection from {}: message over {} bytes ({})",
+                    peer, limits.max_message_bytes, error
+                );
+                let _ = sender.send(close_message(MESSAGE_TOO_BIG_CLOSE_CODE));
+                break;
             }
             Err(error) => {
                 eprintln!("External WebSocket receive error from {}: {}", peer, error);
@@ -352,136 +788,294 @@ async fn handle_external_connection(
         }
     }
 
+    if let Some(request_id) = pairing_request {
+        let _ = pairing::withdraw_request(&app, &request_id);
+    }
     if let Some(active_session_id) = session_id {
         let _ = handle_session_closed(&app, &active_session_id).await;
-        let _ = remove_session(&state, &active_session_id);
+        control_lock::release_session(&app, &active_session_id);
+        if let Ok(Some(session)) = remove_session(&state, &active_session_id) {
+            emit_presence(&app, &session, PresenceReason::Disconnected);
+        }
     }
 
-    writer.abort();
+    // Let queued replies such as close frames go out before dropping the
+    // socket, without waiting on a peer that stopped reading.
+    drop(sender);
+    if tokio::time::timeout(WRITER_DRAIN_TIMEOUT, &mut writer)
+        .await
+        .is_err()
+    {
+        writer.abort();
+    }
     println!("External connection with {} closed", peer);
     Ok(())
 }
 
-fn handle_audio_event(
+/// How a control message was handled.
+enum ControlOutcome {
+    /// Emitted to the UI; the handler that applies it reports back.
+    Forwarded,
+    /// Fully handled here.
+    Handled,
+}
+
+type ControlError = (ErrorCode, String);
+
+fn dispatch_control_message(
     app: &AppHandle,
     peer: &str,
-    audio_event: &AudioEvent,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    match audio_event.event.as_str() {
-        "set_volume" => {
-            if let Some(volume) = audio_event.value {
-                let volume_u8 = volume as u8;
-                if volume_u8 <= 100 {
-                    app.emit("set-volume", volume_u8)
-                        .map_err(|e| e.to_string())?;
-                }
+    message: &ClientMessage,
+    responder: Responder,
+) -> Result<(), String> {
+    // Only commands with a `request_id` get an answer to wait for.
+    let command = match responder.request_id() {
+        Some(_) => match expect_confirmation(app) {
+            Ok(command) => Some(command),
+            Err(reason) => {
+                responder.error(ErrorCode::CommandFailed, reason.clone());
+                return Err(reason);
             }
+        },
+        None => None,
+    };
+    let outcome = handle_control_message(app, peer, message, command.as_ref().map(|c| c.id()));
+    if outcome.is_ok() {
+        live_state::apply_control_message(app, message);
+    }
+
+    match (outcome, command) {
+        (Ok(ControlOutcome::Forwarded), Some(command)) => reply_when_confirmed(command, responder),
+        (Ok(_), _) => responder.result(json!({})),
+        (Err((code, reason)), _) => {
+            eprintln!(
+                "Failed to handle {} event from {}: {}",
+                message.event_name(),
+                peer,
+                reason
+            );
+            responder.error(code, reason.clone());
+            return Err(reason);
         }
-        "seek" => {
-            if let Some(seconds) = audio_event.value {
-                app.emit("seek", seconds).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Runs a control command that did not arrive over the socket, such as one
+/// mapped from OSC or sent over REST, exactly like a remote device's.
+pub fn run_control_message(
+    app: &AppHandle,
+    source: &str,
+    message: &ClientMessage,
+) -> Result<(), ErrorReply> {
+    handle_control_message(app, source, message, None)
+        .map_err(|(code, reason)| ErrorReply::new(code, reason, Some(message.event_name())))?;
+    live_state::apply_control_message(app, message);
+    println!("Applied {} from {}", message.event_name(), source);
+    Ok(())
+}
+
+/// Like `run_control_message`, but waits for the UI to confirm a forwarded
+/// command and returns its result, as a socket request with a `request_id`
+/// gets.
+pub async fn run_confirmed_control_message(
+    app: &AppHandle,
+    source: &str,
+    message: &ClientMessage,
+) -> Result<Value, ErrorReply> {
+    let reply =
+        |(code, reason): ControlError| ErrorReply::new(code, reason, Some(message.event_name()));
+    let command =
+        expect_confirmation(app).map_err(|reason| reply((ErrorCode::CommandFailed, reason)))?;
+    let outcome =
+        handle_control_message(app, source, message, Some(command.id())).map_err(reply)?;
+    live_state::apply_control_message(app, message);
+    println!("Applied {} from {}", message.event_name(), source);
+
+    match outcome {
+        ControlOutcome::Forwarded => command.outcome().await.map_err(reply),
+        ControlOutcome::Handled => Ok(json!({})),
+    }
+}
+
+fn audit_category(message: &ClientMessage) -> &'static str {
+    if message.stream_type().is_some() {
+        audit::STREAM
+    } else {
+        audit::COMMAND
+    }
+}
+
+/// The message as JSON; auth messages never reach the audit log this way.
+fn audit_summary(message: &ClientMessage) -> String {
+    serde_json::to_string(message).unwrap_or_default()
+}
+
+/// Emits the UI event for `message`. `command_id` goes into the payload of
+/// forwarded commands so the UI can answer that command.
+fn handle_control_message(
+    app: &AppHandle,
+    peer: &str,
+    message: &ClientMessage,
+    command_id: Option<&str>,
+) -> Result<ControlOutcome, ControlError> {
+    match message {
+        ClientMessage::SetVolume { value } => {
+            if !(0.0..=100.0).contains(value) {
+                return Err((
+                    ErrorCode::InvalidPayload,
+                    "`value` must be between 0 and 100".to_string(),
+                ));
             }
+            emit_command(
+                app,
+                "set-volume",
+                json!({ "value": *value as u8 }),
+                command_id,
+            )?
+        }
+        ClientMessage::Seek { value } => {
+            emit_command(app, "seek", json!({ "value": value }), command_id)?
         }
-        "metadata" => {
-            app.emit(
+        ClientMessage::Metadata { title, url, artist } => {
+            emit_command(
+                app,
                 "video-metadata",
-                serde_json::json!({
-                    "title": audio_event.title.clone().unwrap_or_default(),
-                    "url": audio_event.url.clone().unwrap_or_default(),
-                    "artist": audio_event.artist.clone().unwrap_or_default(),
+                json!({
+                    "title": title.clone().unwrap_or_default(),
+                    "url": url.clone().unwrap_or_default(),
+                    "artist": artist.clone().unwrap_or_default(),
                 }),
-            )
-            .map_err(|e| e.to_string())?;
-        }
-        "progress" => {
-            if let Some(seconds) = audio_event.value {
-                app.emit(
-                    "video-progress",
-                    serde_json::json!({
-                        "seconds": seconds,
-                        "duration": audio_event.duration.unwrap_or(0.0)
-                    }),
-                )
-                .map_err(|e| e.to_string())?;
-            }
+                None,
+            )?;
+            return Ok(ControlOutcome::Handled);
         }
-        "mute" => app.emit("mute", ()).map_err(|e| e.to_string())?,
-        "play_pause" => app.emit("play-pause", ()).map_err(|e| e.to_string())?,
-        "stop" => app.emit("stop", ()).map_err(|e| e.to_string())?,
-        "load_url" => {
-            app.emit(
-                "load-url",
-                serde_json::json!({
-                    "url": audio_event.url.clone().unwrap_or_default(),
-                    "time": audio_event.value.unwrap_or(0.0),
+        ClientMessage::Progress { value, duration } => {
+            emit_command(
+                app,
+                "video-progress",
+                json!({
+                    "seconds": value,
+                    "duration": duration.unwrap_or(0.0)
                 }),
-            )
-            .map_err(|e| e.to_string())?;
+                None,
+            )?;
+            return Ok(ControlOutcome::Handled);
         }
-        "load_lyric" => {
-            app.emit(
-                "load-lyric",
-                serde_json::json!({
-                    "url": audio_event.url.clone().unwrap_or_default(),
-                }),
-            )
-            .map_err(|e| e.to_string())?;
+        ClientMessage::Mute => emit_command(app, "mute", json!({}), command_id)?,
+        ClientMessage::PlayPause => emit_command(app, "play-pause", json!({}), command_id)?,
+        ClientMessage::Stop => emit_command(app, "stop", json!({}), command_id)?,
+        ClientMessage::LoadUrl { url, value } => emit_command(
+            app,
+            "load-url",
+            json!({
+                "url": url,
+                "time": value.unwrap_or(0.0),
+            }),
+            command_id,
+        )?,
+        ClientMessage::LoadLyric { url } => {
+            emit_command(app, "load-lyric", json!({ "url": url }), command_id)?
         }
-        "set_loop" => {
-            let enabled = audio_event.value.map(|value| value != 0.0).unwrap_or(false);
-            app.emit("video-loop", enabled).map_err(|e| e.to_string())?;
+        ClientMessage::LyricsSlide { index } => emit_command(
+            app,
+            "lyric-start-slide",
+            json!({ "startIndex": index }),
+            command_id,
+        )?,
+        ClientMessage::SetLoop { value } => {
+            let enabled = value.map(|value| value != 0.0).unwrap_or(false);
+            emit_command(app, "video-loop", json!({ "value": enabled }), command_id)?
         }
-        "next" => app.emit("next", ()).map_err(|e| e.to_string())?,
-        "previous" => app.emit("previous", ()).map_err(|e| e.to_string())?,
-        "manual_pause" => {
+        ClientMessage::Next => emit_command(app, "next", json!({}), command_id)?,
+        ClientMessage::Previous => emit_command(app, "previous", json!({}), command_id)?,
+        ClientMessage::MediaQueue { media_id } => {
+            let item = media_library::find(*media_id)
+                .map_err(|reason| (ErrorCode::InvalidPayload, reason))?;
+            emit_command(
+                app,
+                "remote-media-queue",
+                json!({ "path": item.path, "media_type": item.media_type }),
+                command_id,
+            )?
+        }
+        ClientMessage::MediaShow { media_id } => {
+            let item = media_library::find(*media_id)
+                .map_err(|reason| (ErrorCode::InvalidPayload, reason))?;
+            emit_command(
+                app,
+                "remote-media-show",
+                json!({ "path": item.path, "media_type": item.media_type }),
+                command_id,
+            )?
+        }
+        ClientMessage::ManualPause => {
             println!("Received manual_pause event from {}", peer);
+            return Ok(ControlOutcome::Handled);
+        }
+        _ => {
+            return Err((
+                ErrorCode::CommandFailed,
+                format!("`{}` is not accepted here", message.event_name()),
+            ));
         }
-        _ => println!(
-            "Received unknown event from {}: {}",
-            peer, audio_event.event
-        ),
-    }
-
-    if audio_event.event != "manual_pause" {
-        println!("Emitted {} event from {}", audio_event.event, peer);
     }
 
-    Ok(())
+    println!("Emitted {} event from {}", message.event_name(), peer);
+    Ok(ControlOutcome::Forwarded)
 }
 
-fn json_message<T: Serialize>(payload: &T) -> Result<Message, String> {
-    serde_json::to_string(payload)
-        .map(Message::Text)
-        .map_err(|e| e.to_string())
+/// Emits `event` to the UI, adding `command_id` to the payload object when
+/// the sender waits for an answer.
+fn emit_command(
+    app: &AppHandle,
+    event: &str,
+    mut payload: Value,
+    command_id: Option<&str>,
+) -> Result<(), ControlError> {
+    if let (Some(command_id), Some(fields)) = (command_id, payload.as_object_mut()) {
+        fields.insert("command_id".to_string(), json!(command_id));
+    }
+    app.emit(event, payload)
+        .map_err(|e| (ErrorCode::CommandFailed, e.to_string()))
 }
 
-fn close_message(code: u16) -> Message {
-    Message::Close(Some(tokio_tungstenite::tungstenite::protocol::CloseFrame {
-        code: tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode::from(code),
-        reason: "".into(),
-    }))
-}
+/// Announces a session that has just authenticated and sends it the live
+/// state. Returns the handle that wakes the connection when the server drops
+/// the session.
+fn start_session(
+    app: &AppHandle,
+    state: &State<'_, DeviceState>,
+    sender: &mpsc::UnboundedSender<Message>,
+    session_id: &str,
+) -> Result<Option<Arc<Notify>>, String> {
+    let session = {
+        let sessions = state.sessions.lock().map_err(|e| e.to_string())?;
+        let Some(session) = sessions.get(session_id) else {
+            return Ok(None);
+        };
+        session.clone()
+    };
 
-fn is_streaming_event(event_name: &str) -> bool {
-    matches!(
-        event_name,
-        "subscribe_stream"
-            | "unsubscribe_stream"
-            | "webrtc_answer"
-            | "webrtc_ice_candidate"
-            | "mobile_offer"
-    )
+    emit_presence(app, &session, PresenceReason::Connected);
+    live_state::send_snapshot(app, sender, &session.permissions, &session.lyrics_language)?;
+    control_lock::send_locks(app, sender)?;
+    Ok(Some(session.closer))
 }
 
-fn stream_type_for_event<'a>(event_name: &'a str, value: &'a Value) -> &'a str {
-    if event_name == "mobile_offer" {
-        return "mobile";
-    }
+fn session_timeout(state: &State<'_, DeviceState>) -> Duration {
+    let timeout = state
+        .remote_settings
+        .lock()
+        .map(|settings| settings.session_timeout_secs)
+        .unwrap_or_else(|_| RemoteAccessSettings::default().session_timeout_secs);
+    Duration::from_secs(timeout)
+}
 
-    value
-        .get("stream_type")
-        .and_then(Value::as_str)
-        .unwrap_or("unknown")
+fn json_message<T: Serialize>(payload: &T) -> Result<Message, String> {
+    serde_json::to_string(payload)
+        .map(Message::Text)
+        .map_err(|e| e.to_string())
 }
 
 fn stream_error_message(stream_type: &str, reason: &str) -> Result<Message, String> {
@@ -492,6 +1086,18 @@ fn stream_error_message(stream_type: &str, reason: &str) -> Result<Message, Stri
     })
 }
 
+fn send_stream_failure(
+    sender: &mpsc::UnboundedSender<Message>,
+    responder: &Responder,
+    stream_type: &str,
+    reason: &str,
+) {
+    if let Ok(message) = stream_error_message(stream_type, reason) {
+        let _ = sender.send(message);
+    }
+    responder.error(ErrorCode::CommandFailed, reason);
+}
+
 fn now_unix_ms() -> u128 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -518,17 +1124,66 @@ fn emit_streaming_debug(
     );
 }
 
+/// Answers Bible and media library lookups, and shows Bible passages.
+/// Returns `None` for messages that are not library events.
+async fn handle_library_event(
+    app: &AppHandle,
+    responder: &Responder,
+    message: &ClientMessage,
+) -> Option<Result<(), String>> {
+    let outcome = match message {
+        ClientMessage::BibleTranslations => bible::list_translations(app)
+            .map(|translations| json!({ "translations": translations })),
+        ClientMessage::BibleSearch {
+            query,
+            translation,
+            limit,
+        } => bible::search(app, query, translation.as_deref(), *limit)
+            .map(|results| json!({ "results": results })),
+        ClientMessage::BibleShow {
+            reference,
+            translation,
+        } => bible::show(app, reference, translation.as_deref())
+            .await
+            .map(|passage| json!({ "passage": passage })),
+        ClientMessage::MediaBrowse {
+            media_type,
+            query,
+            offset,
+            limit,
+        } => media_library::browse(media_type.as_deref(), query.as_deref(), *offset, *limit)
+            .and_then(|page| serde_json::to_value(page).map_err(|e| e.to_string())),
+        ClientMessage::LyricsBrowse {
+            query,
+            offset,
+            limit,
+        } => media_library::browse(Some("lyrics"), query.as_deref(), *offset, *limit)
+            .and_then(|page| serde_json::to_value(page).map_err(|e| e.to_string())),
+        _ => return None,
+    };
+
+    Some(match outcome {
+        Ok(data) => {
+            responder.result(data);
+            Ok(())
+        }
+        Err(reason) => {
+            responder.error(ErrorCode::InvalidPayload, reason.clone());
+            Err(reason)
+        }
+    })
+}
+
 async fn handle_streaming_event(
     app: &AppHandle,
     sender: &mpsc::UnboundedSender<Message>,
+    responder: &Responder,
     session_id: &str,
     device_id: &str,
-    event_name: &str,
-    value: &Value,
-) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
-    match event_name {
-        "subscribe_stream" => {
-            let payload: SubscribeStreamMessage = serde_json::from_value(value.clone())?;
+    message: &ClientMessage,
+) -> Option<Result<(), String>> {
+    match message {
+        ClientMessage::SubscribeStream { stream_type } => {
             emit_streaming_debug(
                 app,
                 session_id,
@@ -536,12 +1191,11 @@ async fn handle_streaming_event(
                 "subscribe_stream",
                 json!({
                     "status": "requested",
-                    "stream_type": payload.stream_type,
+                    "stream_type": stream_type,
                 }),
             );
-            if let Err(reason) =
-                subscribe_stream(app, session_id, &payload.stream_type, sender.clone()).await
-            {
+            let result = subscribe_stream(app, session_id, stream_type, sender.clone()).await;
+            if let Err(reason) = &result {
                 emit_streaming_debug(
                     app,
                     session_id,
@@ -549,11 +1203,11 @@ async fn handle_streaming_event(
                     "subscribe_stream",
                     json!({
                         "status": "error",
-                        "stream_type": payload.stream_type,
+                        "stream_type": stream_type,
                         "reason": reason,
                     }),
                 );
-                let _ = sender.send(stream_error_message(&payload.stream_type, &reason)?);
+                send_stream_failure(sender, responder, stream_type, reason);
             } else {
                 emit_streaming_debug(
                     app,
@@ -562,14 +1216,14 @@ async fn handle_streaming_event(
                     "subscribe_stream",
                     json!({
                         "status": "ok",
-                        "stream_type": payload.stream_type,
+                        "stream_type": stream_type,
                     }),
                 );
+                responder.result(json!({ "stream_type": stream_type }));
             }
-            Ok(true)
+            Some(result)
         }
-        "unsubscribe_stream" => {
-            let payload: SubscribeStreamMessage = serde_json::from_value(value.clone())?;
+        ClientMessage::UnsubscribeStream { stream_type } => {
             emit_streaming_debug(
                 app,
                 session_id,
@@ -577,10 +1231,11 @@ async fn handle_streaming_event(
                 "unsubscribe_stream",
                 json!({
                     "status": "requested",
-                    "stream_type": payload.stream_type,
+                    "stream_type": stream_type,
                 }),
             );
-            if let Err(reason) = unsubscribe_stream(app, session_id, &payload.stream_type).await {
+            let result = unsubscribe_stream(app, session_id, stream_type).await;
+            if let Err(reason) = &result {
                 emit_streaming_debug(
                     app,
                     session_id,
@@ -588,11 +1243,11 @@ async fn handle_streaming_event(
                     "unsubscribe_stream",
                     json!({
                         "status": "error",
-                        "stream_type": payload.stream_type,
+                        "stream_type": stream_type,
                         "reason": reason,
                     }),
                 );
-                let _ = sender.send(stream_error_message(&payload.stream_type, &reason)?);
+                send_stream_failure(sender, responder, stream_type, reason);
             } else {
                 emit_streaming_debug(
                     app,
@@ -601,18 +1256,20 @@ async fn handle_streaming_event(
                     "unsubscribe_stream",
                     json!({
                         "status": "ok",
-                        "stream_type": payload.stream_type,
+                        "stream_type": stream_type,
                     }),
                 );
-                let _ = sender.send(json_message(&serde_json::json!({
+                if let Ok(stopped) = json_message(&json!({
                     "event": "stream_stopped",
-                    "stream_type": payload.stream_type,
-                }))?);
+                    "stream_type": stream_type,
+                })) {
+                    let _ = sender.send(stopped);
+                }
+                responder.result(json!({ "stream_type": stream_type }));
             }
-            Ok(true)
+            Some(result)
         }
-        "webrtc_answer" => {
-            let payload: WebRtcAnswerMessage = serde_json::from_value(value.clone())?;
+        ClientMessage::WebrtcAnswer { stream_type, sdp } => {
             emit_streaming_debug(
                 app,
                 session_id,
@@ -620,13 +1277,12 @@ async fn handle_streaming_event(
                 "webrtc_answer",
                 json!({
                     "status": "requested",
-                    "stream_type": payload.stream_type,
-                    "sdp_len": payload.sdp.len(),
+                    "stream_type": stream_type,
+                    "sdp_len": sdp.len(),
                 }),
             );
-            if let Err(reason) =
-                set_webrtc_answer(app, session_id, &payload.stream_type, &payload.sdp).await
-            {
+            let result = set_webrtc_answer(app, session_id, stream_type, sdp).await;
+            if let Err(reason) = &result {
                 emit_streaming_debug(
                     app,
                     session_id,
@@ -634,11 +1290,11 @@ async fn handle_streaming_event(
                     "webrtc_answer",
                     json!({
                         "status": "error",
-                        "stream_type": payload.stream_type,
+                        "stream_type": stream_type,
                         "reason": reason,
                     }),
                 );
-                let _ = sender.send(stream_error_message(&payload.stream_type, &reason)?);
+                send_stream_failure(sender, responder, stream_type, reason);
             } else {
                 emit_streaming_debug(
                     app,
@@ -647,14 +1303,17 @@ async fn handle_streaming_event(
                     "webrtc_answer",
                     json!({
                         "status": "ok",
-                        "stream_type": payload.stream_type,
+                        "stream_type": stream_type,
                     }),
                 );
+                responder.result(json!({ "stream_type": stream_type }));
             }
-            Ok(true)
+            Some(result)
         }
-        "webrtc_ice_candidate" => {
-            let payload: WebRtcIceCandidateMessage = serde_json::from_value(value.clone())?;
+        ClientMessage::WebrtcIceCandidate {
+            stream_type,
+            candidate,
+        } => {
             emit_streaming_debug(
                 app,
                 session_id,
@@ -662,13 +1321,12 @@ async fn handle_streaming_event(
                 "webrtc_ice_candidate",
                 json!({
                     "status": "requested",
-                    "stream_type": payload.stream_type,
+                    "stream_type": stream_type,
                 }),
             );
-            if let Err(reason) =
-                add_webrtc_ice_candidate(app, session_id, &payload.stream_type, payload.candidate)
-                    .await
-            {
+            let result =
+                add_webrtc_ice_candidate(app, session_id, stream_type, candidate.clone()).await;
+            if let Err(reason) = &result {
                 emit_streaming_debug(
                     app,
                     session_id,
@@ -676,11 +1334,11 @@ async fn handle_streaming_event(
                     "webrtc_ice_candidate",
                     json!({
                         "status": "error",
-                        "stream_type": payload.stream_type,
+                        "stream_type": stream_type,
                         "reason": reason,
                     }),
                 );
-                let _ = sender.send(stream_error_message(&payload.stream_type, &reason)?);
+                send_stream_failure(sender, responder, stream_type, reason);
             } else {
                 emit_streaming_debug(
                     app,
@@ -689,14 +1347,17 @@ async fn handle_streaming_event(
                     "webrtc_ice_candidate",
                     json!({
                         "status": "ok",
-                        "stream_type": payload.stream_type,
+                        "stream_type": stream_type,
                     }),
                 );
+                responder.result(json!({ "stream_type": stream_type }));
             }
-            Ok(true)
+            Some(result)
         }
-        "mobile_offer" => {
-            let payload: MobileOfferMessage = serde_json::from_value(value.clone())?;
+        ClientMessage::MobileOffer {
+            sdp,
+            video_orientation,
+        } => {
             emit_streaming_debug(
                 app,
                 session_id,
@@ -705,21 +1366,20 @@ async fn handle_streaming_event(
                 json!({
                     "status": "requested",
                     "stream_type": "mobile",
-                    "sdp_len": payload.sdp.len(),
-                    "video_orientation": payload.video_orientation,
+                    "sdp_len": sdp.len(),
+                    "video_orientation": video_orientation,
                 }),
             );
-            if let Err(reason) =
-                handle_mobile_offer(
-                    app,
-                    session_id,
-                    device_id,
-                    &payload.sdp,
-                    payload.video_orientation.as_deref(),
-                    sender.clone(),
-                )
-                .await
-            {
+            let result = handle_mobile_offer(
+                app,
+                session_id,
+                device_id,
+                sdp,
+                video_orientation.as_deref(),
+                sender.clone(),
+            )
+            .await;
+            if let Err(reason) = &result {
                 emit_streaming_debug(
                     app,
                     session_id,
@@ -731,7 +1391,7 @@ async fn handle_streaming_event(
                         "reason": reason,
                     }),
                 );
-                let _ = sender.send(stream_error_message("mobile", &reason)?);
+                send_stream_failure(sender, responder, "mobile", reason);
             } else {
                 emit_streaming_debug(
                     app,
@@ -743,9 +1403,10 @@ async fn handle_streaming_event(
                         "stream_type": "mobile",
                     }),
                 );
+                responder.result(json!({ "stream_type": "mobile" }));
             }
-            Ok(true)
+            Some(result)
         }
-        _ => Ok(false),
+        _ => None,
     }
 }
@@ -4,6 +4,8 @@ import { GlobalAlert } from '@/components/global-alert';
 import { LyricModal } from '@/components/lyric-modal';
 import { QuickShortcutsModal } from '@/components/quick-shortcuts-modal';
 import { Toaster } from '@/components/ui/sonner';
+import { useControlSocketPort } from '@/hooks/use-control-socket-port';
+import { useFollowerTakeover } from '@/hooks/use-follower-takeover';
 import { useModules } from '@/hooks/use-modules';
 import { useProfiles } from '@/hooks/use-profiles';
 import { useSingleInstance } from '@/hooks/use-single-instance';
@@ -21,6 +23,8 @@ function RootComponent() {
   const isAuxiliaryWindow = AUXILIARY_WINDOW_PATHS.has(window.location.pathname);
 
   useSingleInstance(!isAuxiliaryWindow);
+  useControlSocketPort();
+  useFollowerTakeover(!isAuxiliaryWindow);
   useTheme();
   useProfiles();
   useModules(!isAuxiliaryWindow);
@@ -24,6 +24,7 @@ import {
   EmptyTitle,
 } from '@/components/ui/empty';
 import { ScrollArea } from '@/components/ui/scroll-area';
+import { controlSocketUrl } from '@/lib/control-socket';
 import { cn } from '@/lib/utils';
 import { useStreamingStore } from '@/stores/streaming-store';
 
@@ -127,7 +128,7 @@ function RouteComponent() {
     if (streamOverlayActive) return;
 
     const pc = new RTCPeerConnection();
-    const ws = new WebSocket('ws://localhost:8080');
+    const ws = new WebSocket(controlSocketUrl());
     let closed = false;
 
     const attachVideoElement = (stream: MediaStream, retries = 10) => {
@@ -8,6 +8,7 @@ import { LyricPresentation } from '@/components/lyric-presentation';
 import { PptxPresentation } from '@/components/reveal-presentation';
 import { Videoplayer } from '@/components/ui/videoplayer';
 import { useProfiles } from '@/hooks/use-profiles';
+import { controlSocketUrl } from '@/lib/control-socket';
 import { cn } from '@/lib/utils';
 import { PresenterSlot } from '@/modules/components/PresenterSlot';
 import { bootPresenterModules } from '@/modules/presenter-injector';
@@ -20,7 +21,7 @@ function StreamOverlay() {
 
   useEffect(() => {
     const pc = new RTCPeerConnection();
-    const ws = new WebSocket('ws://localhost:8080');
+    const ws = new WebSocket(controlSocketUrl());
     let closed = false;
     let signalingMode: 'mobile_preview' | 'mobile' = 'mobile_preview';
     let subscribed = false;
@@ -5,8 +5,16 @@ import { useCallback, useEffect, useRef, useState } from 'react';
 import { useEventListener, useIsomorphicLayoutEffect, useWindowSize } from 'usehooks-ts';
 import { useProfiles } from '@/hooks/use-profiles';
 import { type LyricData, parseLyricFile } from '@/services/lyric-service';
+import { type RemoteCommand, remoteSyncService } from '@/services/remote-sync-service';
 import { useProfileStore } from '@/stores/profile-store';
 
+/** Answers the remote client that asked for `next` or `previous`. */
+function reportSlideCommand(commandId: string | undefined, failure: string | null) {
+  void remoteSyncService
+    .completeCommand(commandId, failure === null, undefined, failure ?? undefined)
+    .catch(() => {});
+}
+
 function useBackgroundSrc(path?: string) {
   const [src, setSrc] = useState<string | undefined>();
 
@@ -143,6 +151,7 @@ export function LyricPresentation({
 
     const shouldHideLyrics = hideLyrics || useProfileWallpaper;
     const lines = shouldHideLyrics ? [] : (slide?.lines ?? []);
+    const translations = shouldHideLyrics ? [] : (slide?.translations ?? []);
 
     emit('lyric-slide-changed', {
       filePath,
@@ -159,6 +168,8 @@ export function LyricPresentation({
     invoke('push_stream_slide', {
       update: {
         lines,
+        language: lyricData.metadata.language ?? null,
+        translations,
         font: lyricData.metadata.font || null,
         font_size: fontSize,
         alignment: lyricData.metadata.alignment || 'center',
@@ -185,27 +196,43 @@ export function LyricPresentation({
     }, fadeMs);
   }, []);
 
-  const goNext = useCallback(() => {
-    if (!lyricData || pendingSlideRef.current !== null) return;
-    if (currentSlide >= totalSlides - 1) return;
+  /** Moves to the next slide; returns why it could not, if it did not. */
+  const goNext = useCallback((): string | null => {
+    if (!lyricData) return 'No lyrics are being presented';
+    if (pendingSlideRef.current !== null) return 'A slide change is already in progress';
+    if (currentSlide >= totalSlides - 1) return 'Already on the last slide';
     changeSlide(currentSlide + 1);
+    return null;
   }, [currentSlide, totalSlides, lyricData, changeSlide]);
 
-  const goPrev = useCallback(() => {
-    if (pendingSlideRef.current !== null) return;
-    if (currentSlide <= 0) return;
+  /** Moves to the previous slide; returns why it could not, if it did not. */
+  const goPrev = useCallback((): string | null => {
+    if (pendingSlideRef.current !== null) return 'A slide change is already in progress';
+    if (currentSlide <= 0) return 'Already on the first slide';
     changeSlide(currentSlide - 1);
+    return null;
   }, [currentSlide, changeSlide]);
 
   useEffect(() => {
-    const unlistenNext = listen('next', () => goNext());
-    const unlistenPrev = listen('previous', () => goPrev());
-    const unlistenStartSlide = listen<{ startIndex: number }>('lyric-start-slide', (e) => {
-      const data = lyricDataRef.current;
-      if (!data) return;
-      const clamped = Math.max(0, Math.min(e.payload.startIndex, data.slides.length - 1));
-      changeSlide(clamped);
-    });
+    const unlistenNext = listen<RemoteCommand>('next', (e) =>
+      reportSlideCommand(e.payload.command_id, goNext())
+    );
+    const unlistenPrev = listen<RemoteCommand>('previous', (e) =>
+      reportSlideCommand(e.payload.command_id, goPrev())
+    );
+    const unlistenStartSlide = listen<RemoteCommand<{ startIndex: number }>>(
+      'lyric-start-slide',
+      (e) => {
+        const data = lyricDataRef.current;
+        if (!data) {
+          reportSlideCommand(e.payload.command_id, 'No lyrics are being presented');
+          return;
+        }
+        const clamped = Math.max(0, Math.min(e.payload.startIndex, data.slides.length - 1));
+        changeSlide(clamped);
+        reportSlideCommand(e.payload.command_id, null);
+      }
+    );
 
     return () => {
       unlistenNext.then((f) => f());
@@ -24,7 +24,13 @@ import { ScrollArea } from '@/components/ui/scroll-area';
 import { useAnnounce } from '@/hooks/use-announce';
 import { useTranslation } from '@/lib/i18n';
 import { cn } from '@/lib/utils';
-import { type FileInfo, fileInitService, fileManagementService, type MediaType } from '@/services';
+import {
+  type FileInfo,
+  fileInitService,
+  fileManagementService,
+  type MediaType,
+  mediaUploadService,
+} from '@/services';
 import { useLyricEditStore } from '@/stores/lyric-edit-store';
 import { useLyricModalStore } from '@/stores/lyric-modal-store';
 import { usePlayerStore } from '@/stores/player-store';
@@ -212,6 +218,19 @@ export function MediaPanel() {
     }
   }, [activeMedia, isInitialized, loadFiles]);
 
+  useEffect(() => {
+    const unlisten = mediaUploadService.onUploaded((uploaded) => {
+      toast.success(`${uploaded.name} received from ${uploaded.device_name}`);
+      if (activeMedia === uploaded.media_type) {
+        void loadFiles(activeMedia);
+      }
+    });
+
+    return () => {
+      unlisten.then((f) => f());
+    };
+  }, [activeMedia, loadFiles]);
+
   const handleKeyDown = (e: React.KeyboardEvent) => {
     if (!activeMedia || files.length === 0) return;
 
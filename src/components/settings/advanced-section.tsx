@@ -11,6 +11,7 @@ import { Input } from '../ui/input';
 import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from '../ui/select';
 import { Separator } from '../ui/separator';
 import { Switch } from '../ui/switch';
+import { HtmlOutputsSection } from './html-outputs-section';
 
 export function AdvancedSection() {
   const { t } = useTranslation();
@@ -182,6 +183,12 @@ export function AdvancedSection() {
             })}
           </p>
         </CardContent>
+
+        <Separator />
+
+        <CardContent className="rounded-lg p-4">
+          <HtmlOutputsSection active={status.html_active} />
+        </CardContent>
       </CardContent>
 
       <CardContent variant="muted" className="gap-3 p-4 rounded-xl">
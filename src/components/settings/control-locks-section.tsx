@@ -0,0 +1,119 @@
+'use client';
+
+import { useTranslation } from '@/lib/i18n';
+import { Lock, LockOpen } from 'lucide-react';
+import { useCallback, useEffect, useState } from 'react';
+import type { ControlLock, ControlLockScope } from '@/services';
+import { devicesService } from '@/services';
+import { Button } from '../ui/button';
+import { Card } from '../ui/card';
+
+const LOCK_SCOPES: ControlLockScope[] = ['player', 'lyrics', 'bible', 'media'];
+
+const SCOPE_LABELS: Record<ControlLockScope, string> = {
+  player: 'Player',
+  lyrics: 'Lyrics',
+  bible: 'Bible',
+  media: 'Media Library',
+};
+
+export function ControlLocksSection() {
+  const { t } = useTranslation();
+  const [locks, setLocks] = useState<ControlLock[]>([]);
+  const [busy, setBusy] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  const loadLocks = useCallback(async () => {
+    setLocks(await devicesService.getControlLocks());
+  }, []);
+
+  useEffect(() => {
+    let mounted = true;
+    let unlisten: (() => void) | undefined;
+
+    loadLocks().catch(() => {});
+
+    devicesService
+      .onControlLockChanged((change) => {
+        if (!mounted) return;
+        setLocks((current) => {
+          const others = current.filter((lock) => lock.scope !== change.scope);
+          return change.holder ? [...others, change.holder] : others;
+        });
+      })
+      .then((fn) => {
+        unlisten = fn;
+      });
+
+    return () => {
+      mounted = false;
+      unlisten?.();
+    };
+  }, [loadLocks]);
+
+  // biome-ignore lint/correctness/useExhaustiveDependencies: t is stable in practice
+  const run = useCallback(async (action: () => Promise<unknown>) => {
+    try {
+      setBusy(true);
+      setError(null);
+      await action();
+    } catch (err) {
+      setError(err instanceof Error ? err.message : t('Unable to update the control lock.'));
+    } finally {
+      setBusy(false);
+    }
+  }, []);
+
+  return (
+    <Card className="gap-3 bg-background/55 p-4">
+      <div>
+        <p className="text-sm font-medium">{t('Control Locks')}</p>
+        <p className="mt-0.5 text-xs text-muted-foreground">
+          {t('A device holding a lock is the only one that can change that area.')}
+        </p>
+      </div>
+
+      {LOCK_SCOPES.map((scope) => {
+        const lock = locks.find((item) => item.scope === scope);
+        const heldByDesktop = lock && lock.device_id === null;
+
+        return (
+          <div key={scope} className="flex items-center justify-between gap-4">
+            <div className="text-xs">
+              <p className="font-medium text-foreground">{t(SCOPE_LABELS[scope])}</p>
+              <p className="text-muted-foreground">
+                {lock ? t('Held by {{name}}', { name: lock.device_name }) : t('Unlocked')}
+              </p>
+            </div>
+            <div className="flex items-center gap-2">
+              {!heldByDesktop && (
+                <Button
+                  size="sm"
+                  variant="secondary"
+                  disabled={busy}
+                  onClick={() => run(() => devicesService.takeControlLock(scope))}
+                >
+                  <Lock className="size-3.5" />
+                  {lock ? t('Take Over') : t('Lock')}
+                </Button>
+              )}
+              {lock && (
+                <Button
+                  size="sm"
+                  variant="outline"
+                  disabled={busy}
+                  onClick={() => run(() => devicesService.releaseControlLock(scope))}
+                >
+                  <LockOpen className="size-3.5" />
+                  {t('Release')}
+                </Button>
+              )}
+            </div>
+          </div>
+        );
+      })}
+
+      {error && <p className="text-sm text-destructive">{error}</p>}
+    </Card>
+  );
+}
@@ -6,15 +6,16 @@ import type { ReactNode } from 'react';
 import { useEffect, useState } from 'react';
 
 import { devicesService } from '@/services';
-import type { Device, DevicePermissions } from '@/services';
+import type { Device, PermissionArea, PermissionRole } from '@/services';
 import { Button } from '../ui/button';
 import { Card } from '../ui/card';
 import { Checkbox } from '../ui/checkbox';
+import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from '../ui/select';
 import { Switch } from '../ui/switch';
 import { Table, TableBody, TableCell, TableHead, TableHeader, TableRow } from '../ui/table';
 
 const PERMISSION_FEATURES: Array<{
-  id: keyof DevicePermissions;
+  id: PermissionArea;
   label: string;
   icon: ReactNode;
 }> = [
@@ -25,9 +26,13 @@ const PERMISSION_FEATURES: Array<{
     { id: 'streaming', label: 'Streaming', icon: <MonitorPlay className="size-3.5" /> },
   ];
 
+/** Select value for devices with explicit scopes and no role. */
+const CUSTOM_ROLE = 'custom';
+
 export function DevicePermissionsSection() {
   const { t } = useTranslation();
   const [devices, setDevices] = useState<Device[]>([]);
+  const [roles, setRoles] = useState<PermissionRole[]>([]);
   const [loading, setLoading] = useState(true);
   const [busyKey, setBusyKey] = useState<string | null>(null);
   const [error, setError] = useState<string | null>(null);
@@ -44,9 +49,13 @@ export function DevicePermissionsSection() {
       try {
         setLoading(true);
         setError(null);
-        const nextDevices = await devicesService.getDevices();
+        const [nextDevices, nextRoles] = await Promise.all([
+          devicesService.getDevices(),
+          devicesService.getPermissionRoles(),
+        ]);
         if (mounted) {
           setDevices(nextDevices);
+          setRoles(nextRoles);
         }
       } catch (err) {
         if (mounted) {
@@ -128,14 +137,39 @@ export function DevicePermissionsSection() {
     }
   }
 
+  async function handleChangeRole(device: Device, roleId: string) {
+    // Leaving a role keeps what the device can do today as explicit scopes.
+    const nextPermissions =
+      roleId === CUSTOM_ROLE
+        ? { ...device.permissions, role: null, scopes: device.permissions.granted }
+        : { ...device.permissions, role: roleId, scopes: [] };
+    await savePermissions(device, `role:${device.device_id}`, nextPermissions);
+  }
+
   async function handleTogglePermission(
     device: Device,
-    permissionKey: keyof DevicePermissions,
+    area: PermissionArea,
     checked: boolean
   ) {
-    const busyId = `permission:${device.device_id}:${permissionKey}`;
+    // Toggling an area turns the device's current access into explicit scopes.
+    const scopes = device.permissions.granted.filter(
+      (scope) => scope !== area && !scope.startsWith(`${area}.`)
+    );
+    const nextPermissions = {
+      ...device.permissions,
+      role: null,
+      scopes: checked ? [...scopes, area] : scopes,
+      [area]: checked,
+    };
+    await savePermissions(device, `permission:${device.device_id}:${area}`, nextPermissions);
+  }
+
+  async function savePermissions(
+    device: Device,
+    busyId: string,
+    nextPermissions: Device['permissions']
+  ) {
     const previous = devices;
-    const nextPermissions = { ...device.permissions, [permissionKey]: checked };
     setBusyKey(busyId);
     setError(null);
     setDevices((current) =>
@@ -147,7 +181,10 @@ export function DevicePermissionsSection() {
     );
 
     try {
-      await devicesService.updateDevicePermissions(device.device_id, nextPermissions);
+      await devicesService.updateDevicePermissions(device.device_id, {
+        role: nextPermissions.role,
+        scopes: nextPermissions.scopes,
+      });
     } catch (err) {
       setDevices(previous);
       setError(err instanceof Error ? err.message : t('Unable to update device permissions.'));
@@ -214,6 +251,9 @@ export function DevicePermissionsSection() {
                   <TableHead className="px-4 py-2.5 text-xs font-semibold uppercase tracking-widest text-muted-foreground">
                     {t('Last seen')}
                   </TableHead>
+                  <TableHead className="px-4 py-2.5 text-xs font-semibold uppercase tracking-widest text-muted-foreground">
+                    {t('Role')}
+                  </TableHead>
                   {PERMISSION_FEATURES.map((feature) => (
                     <TableHead
                       key={feature.id}
@@ -251,6 +291,25 @@ export function DevicePermissionsSection() {
                     <TableCell className="px-4 py-3 text-xs text-muted-foreground">
                       {formatLastSeen(t, device.last_connected_at)}
                     </TableCell>
+                    <TableCell className="px-4 py-3">
+                      <Select
+                        value={device.permissions.role ?? CUSTOM_ROLE}
+                        disabled={busyKey === `role:${device.device_id}` || !device.is_active}
+                        onValueChange={(value) => handleChangeRole(device, value)}
+                      >
+                        <SelectTrigger className="w-36">
+                          <SelectValue />
+                        </SelectTrigger>
+                        <SelectContent>
+                          {roles.map((role) => (
+                            <SelectItem key={role.role_id} value={role.role_id}>
+                              {t(role.name)}
+                            </SelectItem>
+                          ))}
+                          <SelectItem value={CUSTOM_ROLE}>{t('Custom')}</SelectItem>
+                        </SelectContent>
+                      </Select>
+                    </TableCell>
                     {PERMISSION_FEATURES.map((feature) => (
                       <TableCell key={feature.id} className="px-4 py-3 text-center">
                         <Checkbox
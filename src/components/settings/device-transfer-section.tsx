@@ -0,0 +1,213 @@
+'use client';
+
+import { useTranslation } from '@/lib/i18n';
+import { useState } from 'react';
+import type { TransferPreview } from '@/services';
+import { deviceTransferService } from '@/services';
+import { Button } from '../ui/button';
+import { Card } from '../ui/card';
+import { Checkbox } from '../ui/checkbox';
+import { Input } from '../ui/input';
+import { Switch } from '../ui/switch';
+
+const MIN_PASSWORD_CHARS = 8;
+const FILE_FILTERS = [{ name: 'Lumen remote access', extensions: ['json'] }];
+
+export function DeviceTransferSection() {
+  const { t } = useTranslation();
+  const [password, setPassword] = useState('');
+  const [importPath, setImportPath] = useState<string | null>(null);
+  const [preview, setPreview] = useState<TransferPreview | null>(null);
+  const [selected, setSelected] = useState<Set<string>>(new Set());
+  const [importSettings, setImportSettings] = useState(true);
+  const [busy, setBusy] = useState(false);
+  const [message, setMessage] = useState<string | null>(null);
+  const [error, setError] = useState<string | null>(null);
+
+  const run = async (action: () => Promise<void>) => {
+    try {
+      setBusy(true);
+      setError(null);
+      setMessage(null);
+      await action();
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err));
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  const handleExport = () =>
+    run(async () => {
+      const { save } = await import('@tauri-apps/plugin-dialog');
+      const path = await save({ defaultPath: 'lumen-remote-access.json', filters: FILE_FILTERS });
+      if (!path) return;
+      const summary = await deviceTransferService.exportRemoteAccess(path, password);
+      setMessage(t('Exported {{count}} devices.', { count: summary.devices }));
+    });
+
+  const handleOpen = () =>
+    run(async () => {
+      const { open } = await import('@tauri-apps/plugin-dialog');
+      const path = await open({ multiple: false, filters: FILE_FILTERS });
+      if (typeof path !== 'string') return;
+      const loaded = await deviceTransferService.previewImport(path, password);
+      setImportPath(path);
+      setPreview(loaded);
+      // Devices already paired here are left out until the operator picks them.
+      setSelected(
+        new Set(loaded.devices.filter((device) => !device.conflict).map((device) => device.device_id))
+      );
+    });
+
+  const handleImport = () =>
+    run(async () => {
+      if (!importPath) return;
+      const summary = await deviceTransferService.importRemoteAccess(
+        importPath,
+        password,
+        [...selected],
+        importSettings
+      );
+      setPreview(null);
+      setImportPath(null);
+      setMessage(
+        [t('Imported {{count}} devices.', { count: summary.devices }), ...summary.warnings].join(' ')
+      );
+    });
+
+  const toggleDevice = (deviceId: string, checked: boolean) => {
+    setSelected((current) => {
+      const next = new Set(current);
+      if (checked) {
+        next.add(deviceId);
+      } else {
+        next.delete(deviceId);
+      }
+      return next;
+    });
+  };
+
+  const passwordValid = password.length >= MIN_PASSWORD_CHARS;
+  const conflictingRoles = preview?.roles.filter((role) => role.conflict) ?? [];
+
+  return (
+    <Card className="gap-4 bg-background/55 p-4">
+      <div>
+        <p className="text-sm font-medium">{t('Move to Another Computer')}</p>
+        <p className="mt-0.5 text-xs text-muted-foreground">
+          {t(
+            'Export paired devices, roles and remote access settings to a password-protected file, then import it on the new computer so devices do not have to pair again.'
+          )}
+        </p>
+      </div>
+
+      <div className="grid gap-3 text-xs sm:grid-cols-[8rem_1fr]">
+        <label className="self-center text-muted-foreground" htmlFor="transfer-password">
+          {t('Password')}
+        </label>
+        <Input
+          id="transfer-password"
+          type="password"
+          autoComplete="new-password"
+          value={password}
+          placeholder={t('At least 8 characters')}
+          onChange={(event) => setPassword(event.target.value)}
+        />
+      </div>
+
+      <div className="flex justify-end gap-2">
+        <Button size="sm" variant="secondary" disabled={busy || !passwordValid} onClick={handleExport}>
+          {t('Export')}
+        </Button>
+        <Button size="sm" variant="secondary" disabled={busy || !password} onClick={handleOpen}>
+          {t('Import…')}
+        </Button>
+      </div>
+
+      {preview && (
+        <div className="space-y-3 rounded-md border border-border/60 p-3">
+          <p className="text-xs text-muted-foreground">
+            {t('Exported from {{name}} on {{date}}', {
+              name: preview.desktop_name,
+              date: new Date(preview.exported_at * 1000).toLocaleString(),
+            })}
+          </p>
+
+          <div className="space-y-2">
+            {preview.devices.map((device) => (
+              <label key={device.device_id} className="flex items-start gap-3 text-sm">
+                <Checkbox
+                  checked={selected.has(device.device_id)}
+                  disabled={busy}
+                  onCheckedChange={(checked) => toggleDevice(device.device_id, checked === true)}
+                />
+                <span>
+                  <span className="font-medium">{device.device_name}</span>
+                  <span className="ml-2 text-xs text-muted-foreground">
+                    {device.device_type} · {device.os}
+                    {!device.is_active && ` · ${t('Inactive')}`}
+                  </span>
+                  {device.conflict && (
+                    <span className="block text-xs text-amber-500">
+                      {t('Already paired here as {{name}}; importing replaces it.', {
+                        name: device.conflict.device_name,
+                      })}
+                    </span>
+                  )}
+                </span>
+              </label>
+            ))}
+            {preview.devices.length === 0 && (
+              <p className="text-xs text-muted-foreground">{t('The export has no devices.')}</p>
+            )}
+          </div>
+
+          {conflictingRoles.length > 0 && (
+            <p className="text-xs text-amber-500">
+              {t('These roles already exist here and keep their current scopes: {{roles}}', {
+                roles: conflictingRoles.map((role) => role.name).join(', '),
+              })}
+            </p>
+          )}
+
+          <div className="flex items-center justify-between gap-4">
+            <div>
+              <p className="text-sm">{t('Import settings')}</p>
+              <p className="text-xs text-muted-foreground">
+                {preview.tls_ca
+                  ? t('Remote access settings and the TLS certificate authority.')
+                  : t('Remote access settings.')}
+              </p>
+            </div>
+            <Switch checked={importSettings} disabled={busy} onCheckedChange={setImportSettings} />
+          </div>
+
+          <div className="flex justify-end gap-2">
+            <Button
+              size="sm"
+              variant="ghost"
+              disabled={busy}
+              onClick={() => {
+                setPreview(null);
+                setImportPath(null);
+              }}
+            >
+              {t('Cancel')}
+            </Button>
+            <Button
+              size="sm"
+              disabled={busy || (selected.size === 0 && !importSettings)}
+              onClick={handleImport}
+            >
+              {t('Import')}
+            </Button>
+          </div>
+        </div>
+      )}
+
+      {message && <p className="text-sm text-muted-foreground">{message}</p>}
+      {error && <p className="text-sm text-destructive">{error}</p>}
+    </Card>
+  );
+}
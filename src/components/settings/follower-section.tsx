@@ -0,0 +1,180 @@
+'use client';
+
+import { useTranslation } from '@/lib/i18n';
+import { useEffect, useState } from 'react';
+import type { FollowerPhase, FollowerStatus } from '@/services';
+import { followerService } from '@/services';
+import { Button } from '../ui/button';
+import { Card } from '../ui/card';
+import { Input } from '../ui/input';
+import { Switch } from '../ui/switch';
+
+const PHASE_LABELS: Record<FollowerPhase, string> = {
+  disabled: 'Not following',
+  connecting: 'Connecting…',
+  following: 'Following',
+  leader_lost: 'Leader not responding',
+  taken_over: 'Taken over',
+};
+
+export function FollowerSection() {
+  const { t } = useTranslation();
+  const [status, setStatus] = useState<FollowerStatus | null>(null);
+  const [code, setCode] = useState('');
+  const [missed, setMissed] = useState('');
+  const [busy, setBusy] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    let mounted = true;
+
+    followerService
+      .getStatus()
+      .then((loaded) => {
+        if (!mounted) return;
+        setStatus(loaded);
+        setMissed(String(loaded.missed_heartbeats));
+      })
+      .catch(() => {});
+    const unlisten = followerService.onStatus((next) => {
+      if (mounted) setStatus(next);
+    });
+
+    return () => {
+      mounted = false;
+      unlisten.then((f) => f());
+    };
+  }, []);
+
+  const run = async (action: () => Promise<FollowerStatus>) => {
+    try {
+      setBusy(true);
+      setError(null);
+      const next = await action();
+      setStatus(next);
+      setMissed(String(next.missed_heartbeats));
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err));
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  if (!status) return null;
+
+  const following = status.phase !== 'disabled' && status.phase !== 'taken_over';
+
+  return (
+    <Card className="gap-4 bg-background/55 p-4">
+      <div className="flex items-center justify-between gap-4">
+        <div>
+          <p className="text-sm font-medium">{t('Standby Follower')}</p>
+          <p className="mt-0.5 text-xs text-muted-foreground">
+            {t(
+              'Mirror another Lumen desktop as a backup. If it stops responding, take over from this computer with one click.'
+            )}
+          </p>
+        </div>
+        {status.paired && (
+          <Switch
+            checked={following}
+            disabled={busy}
+            onCheckedChange={(checked) => run(() => followerService.setEnabled(checked))}
+          />
+        )}
+      </div>
+
+      {status.paired ? (
+        <>
+          <div className="grid gap-2 text-xs text-muted-foreground">
+            <div className="flex items-center gap-2">
+              <span className="font-medium text-foreground">{t('Leader')}</span>
+              <span>
+                {status.desktop_name ? `${status.desktop_name} · ${status.leader}` : status.leader}
+              </span>
+            </div>
+            <div className="flex items-center gap-2">
+              <span className="font-medium text-foreground">{t('Status')}</span>
+              <span className={status.phase === 'leader_lost' ? 'text-amber-500' : undefined}>
+                {t(PHASE_LABELS[status.phase])}
+              </span>
+            </div>
+            {status.last_seen_at && (
+              <div className="flex items-center gap-2">
+                <span className="font-medium text-foreground">{t('Last heard')}</span>
+                <span>{new Date(status.last_seen_at * 1000).toLocaleTimeString()}</span>
+              </div>
+            )}
+          </div>
+
+          <div className="grid gap-3 text-xs sm:grid-cols-[8rem_1fr]">
+            <label className="self-center text-muted-foreground" htmlFor="follower-missed">
+              {t('Missed heartbeats')}
+            </label>
+            <Input
+              id="follower-missed"
+              inputMode="numeric"
+              value={missed}
+              onChange={(event) => setMissed(event.target.value)}
+              onBlur={() => {
+                if (Number(missed) !== status.missed_heartbeats) {
+                  void run(() => followerService.setMissedHeartbeats(Number(missed)));
+                }
+              }}
+            />
+          </div>
+
+          <div className="flex justify-end gap-2">
+            <Button
+              size="sm"
+              variant="ghost"
+              disabled={busy}
+              onClick={() => run(() => followerService.unpair())}
+            >
+              {t('Unpair')}
+            </Button>
+            {following && (
+              <Button
+                size="sm"
+                variant={status.phase === 'leader_lost' ? 'default' : 'secondary'}
+                disabled={busy}
+                onClick={() => run(() => followerService.takeOver())}
+              >
+                {t('Take over')}
+              </Button>
+            )}
+          </div>
+        </>
+      ) : (
+        <div className="grid gap-3 text-xs sm:grid-cols-[8rem_1fr_auto]">
+          <label className="self-center text-muted-foreground" htmlFor="follower-code">
+            {t('Pairing code')}
+          </label>
+          <Input
+            id="follower-code"
+            value={code}
+            placeholder={t('Copy it from the leader’s Remote Access settings')}
+            onChange={(event) => setCode(event.target.value)}
+          />
+          <Button
+            size="sm"
+            variant="secondary"
+            disabled={busy || !code.trim()}
+            onClick={() =>
+              run(async () => {
+                const next = await followerService.pair(code.trim());
+                setCode('');
+                return next;
+              })
+            }
+          >
+            {t('Pair')}
+          </Button>
+        </div>
+      )}
+
+      {status.error && <p className="text-sm text-amber-500">{status.error}</p>}
+      {error && <p className="text-sm text-destructive">{error}</p>}
+    </Card>
+  );
+}
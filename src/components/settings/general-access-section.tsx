@@ -1,14 +1,21 @@
 'use client';
 
 import { useTranslation } from '@/lib/i18n';
-import { Loader2, QrCode, RefreshCw } from 'lucide-react';
+import { Copy, Loader2, QrCode, RefreshCw } from 'lucide-react';
 import QRCode from 'qrcode';
 import { useCallback, useEffect, useMemo, useState } from 'react';
+import { toast } from 'sonner';
 import type { RegistrationTokenPayload, RemoteAccessSettings } from '@/services';
 import { devicesService } from '@/services';
 import { Button } from '../ui/button';
 import { Card } from '../ui/card';
 import { Switch } from '../ui/switch';
+import { ControlLocksSection } from './control-locks-section';
+import { DeviceTransferSection } from './device-transfer-section';
+import { FollowerSection } from './follower-section';
+import { OscSection } from './osc-section';
+import { PairingSection } from './pairing-section';
+import { UploadsSection } from './uploads-section';
 
 const DEFAULT_SETTINGS: RemoteAccessSettings = {
   remote_enabled: true,
@@ -102,24 +109,35 @@ export function GeneralAccessSection() {
     return () => window.clearInterval(interval);
   }, []);
 
+  // The same payload is scanned by the mobile app and pasted into a standby follower.
+  const pairingCode = useMemo(() => {
+    if (!registration || !localIp || !settings.remote_enabled) return null;
+    return JSON.stringify({
+      ip: registration.ip || localIp,
+      port: registration.port || 8080,
+      token: registration.token,
+      ...(registration.tls
+        ? {
+            tls: true,
+            fingerprint: registration.cert_fingerprint,
+            ca_fingerprint: registration.ca_fingerprint,
+          }
+        : {}),
+    });
+  }, [registration, localIp, settings.remote_enabled]);
+
   // biome-ignore lint/correctness/useExhaustiveDependencies: t is stable in practice
   useEffect(() => {
     let cancelled = false;
 
     async function buildQrCode() {
-      if (!registration || !localIp || !settings.remote_enabled) {
+      if (!pairingCode) {
         setQrDataUrl('');
         return;
       }
 
-      const payload = JSON.stringify({
-        ip: localIp,
-        port: 8080,
-        token: registration.token,
-      });
-
       try {
-        const nextQr = await QRCode.toDataURL(payload, {
+        const nextQr = await QRCode.toDataURL(pairingCode, {
           margin: 1,
           width: 220,
         });
@@ -138,7 +156,7 @@ export function GeneralAccessSection() {
     return () => {
       cancelled = true;
     };
-  }, [registration, localIp, settings.remote_enabled]);
+  }, [pairingCode]);
 
   const expiresIn = useMemo(() => {
     if (!registration) return 0;
@@ -260,7 +278,7 @@ export function GeneralAccessSection() {
               <div className="grid gap-2 text-xs text-muted-foreground">
                 <div className="flex items-center gap-2">
                   <span className="font-medium text-foreground">{t('Host')}</span>
-                  <span>{localIp ? `${localIp}:8080` : '—'}</span>
+                  <span>{localIp ? `${localIp}:${registration?.port ?? 8080}` : '—'}</span>
                 </div>
                 <div className="flex items-center gap-2">
                   <span className="font-medium text-foreground">{t('Token')}</span>
@@ -284,12 +302,45 @@ export function GeneralAccessSection() {
                   <RefreshCw className="size-3.5" />
                   {t('Refresh QR')}
                 </Button>
+                <Button
+                  size="sm"
+                  variant="ghost"
+                  disabled={!pairingCode}
+                  onClick={() => {
+                    if (!pairingCode) return;
+                    navigator.clipboard
+                      .writeText(pairingCode)
+                      .then(() => toast.success(t('Pairing code copied')))
+                      .catch(() => setError(t('Unable to copy the pairing code.')));
+                  }}
+                >
+                  <Copy className="size-3.5" />
+                  {t('Copy pairing code')}
+                </Button>
               </div>
             </div>
           </div>
         </div>
       </Card>
 
+      <PairingSection
+        remoteEnabled={settings.remote_enabled}
+        approvalEnabled={settings.pairing_approval_enabled ?? false}
+        onApprovalChange={async (enabled) => {
+          setSettings(await devicesService.setPairingApproval(enabled));
+        }}
+      />
+
+      <ControlLocksSection />
+
+      <OscSection />
+
+      <UploadsSection />
+
+      <DeviceTransferSection />
+
+      <FollowerSection />
+
       {error && <p className="text-sm text-destructive">{error}</p>}
     </>
   );
@@ -0,0 +1,127 @@
+'use client';
+
+import { useTranslation } from '@/lib/i18n';
+import { useCallback, useEffect, useState } from 'react';
+import { type HtmlOutputInfo, streamingService } from '@/services/streaming-service';
+import { useStreamingStore } from '@/stores/streaming-store';
+import { Button } from '../ui/button';
+import { Input } from '../ui/input';
+
+const ALL_LANGUAGES = 'all';
+const REFRESH_MS = 5000;
+
+/**
+ * Lyrics language per HTML output. Browsers pick an output with
+ * `?output=<id>`; `all` shows every language of a slide stacked.
+ */
+export function HtmlOutputsSection({ active }: { active: boolean }) {
+  const { t } = useTranslation();
+  const [outputs, setOutputs] = useState<HtmlOutputInfo[]>([]);
+  const [drafts, setDrafts] = useState<Record<string, string>>({});
+  const [newOutputId, setNewOutputId] = useState('');
+  const [error, setError] = useState<string | null>(null);
+
+  const refresh = useCallback(async () => {
+    try {
+      setOutputs(await streamingService.getHtmlOutputs());
+    } catch {
+      setOutputs([]);
+    }
+  }, []);
+
+  useEffect(() => {
+    void refresh();
+    if (!active) return;
+    const interval = window.setInterval(() => void refresh(), REFRESH_MS);
+    return () => window.clearInterval(interval);
+  }, [active, refresh]);
+
+  const save = async (outputId: string, language: string) => {
+    try {
+      setError(null);
+      const config = await streamingService.setHtmlOutputLanguage(
+        outputId,
+        language.trim() || null
+      );
+      useStreamingStore.setState({ config });
+      setDrafts((current) => {
+        const next = { ...current };
+        delete next[outputId];
+        return next;
+      });
+      await refresh();
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err));
+    }
+  };
+
+  const addOutput = async () => {
+    const outputId = newOutputId.trim();
+    if (!outputId) return;
+    await save(outputId, ALL_LANGUAGES);
+    setNewOutputId('');
+  };
+
+  return (
+    <div className="space-y-3">
+      <div>
+        <p className="text-sm font-medium">{t('Output Languages')}</p>
+        <p className="text-xs text-muted-foreground mt-0.5">
+          {t(
+            'Open an output with ?output=<name>. Enter a language code such as en, or all to show every language stacked.'
+          )}
+        </p>
+      </div>
+
+      {outputs.map((output) => {
+        const draft = drafts[output.output_id] ?? output.language;
+        return (
+          <div key={output.output_id} className="flex items-center gap-3">
+            <div className="min-w-0 flex-1">
+              <p className="truncate text-sm">{output.output_id}</p>
+              <p className="text-xs text-muted-foreground">
+                {t('{{count}} connected', { count: output.connected })}
+              </p>
+            </div>
+            <Input
+              className="w-36"
+              placeholder={ALL_LANGUAGES}
+              value={draft}
+              onChange={(event) =>
+                setDrafts((current) => ({ ...current, [output.output_id]: event.target.value }))
+              }
+              onKeyDown={(event) => {
+                if (event.key === 'Enter') void save(output.output_id, draft);
+              }}
+            />
+            <Button
+              size="sm"
+              variant="secondary"
+              disabled={draft === output.language}
+              onClick={() => void save(output.output_id, draft)}
+            >
+              {t('Save')}
+            </Button>
+          </div>
+        );
+      })}
+
+      <div className="flex items-center gap-3">
+        <Input
+          className="flex-1"
+          placeholder={t('Output name')}
+          value={newOutputId}
+          onChange={(event) => setNewOutputId(event.target.value)}
+          onKeyDown={(event) => {
+            if (event.key === 'Enter') void addOutput();
+          }}
+        />
+        <Button size="sm" variant="secondary" disabled={!newOutputId.trim()} onClick={addOutput}>
+          {t('Add Output')}
+        </Button>
+      </div>
+
+      {error && <p className="text-sm text-destructive">{error}</p>}
+    </div>
+  );
+}
@@ -0,0 +1,137 @@
+'use client';
+
+import { useTranslation } from '@/lib/i18n';
+import { useEffect, useState } from 'react';
+import type { OscConfig, OscStatus } from '@/services';
+import { oscService } from '@/services';
+import { Button } from '../ui/button';
+import { Card } from '../ui/card';
+import { Input } from '../ui/input';
+import { Switch } from '../ui/switch';
+
+const splitList = (value: string) =>
+  value
+    .split(',')
+    .map((item) => item.trim())
+    .filter(Boolean);
+
+export function OscSection() {
+  const { t } = useTranslation();
+  const [config, setConfig] = useState<OscConfig | null>(null);
+  const [status, setStatus] = useState<OscStatus | null>(null);
+  const [port, setPort] = useState('');
+  const [sources, setSources] = useState('');
+  const [targets, setTargets] = useState('');
+  const [busy, setBusy] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    let mounted = true;
+    let unlisten: (() => void) | undefined;
+
+    Promise.all([oscService.getConfig(), oscService.getStatus()])
+      .then(([loaded, current]) => {
+        if (!mounted) return;
+        setConfig(loaded);
+        setStatus(current);
+        setPort(String(loaded.port));
+        setSources(loaded.allowed_sources.join(', '));
+        setTargets(loaded.feedback_targets.join(', '));
+      })
+      .catch(() => {});
+
+    oscService
+      .onStatus((next) => {
+        if (mounted) setStatus(next);
+      })
+      .then((fn) => {
+        unlisten = fn;
+      });
+
+    return () => {
+      mounted = false;
+      unlisten?.();
+    };
+  }, []);
+
+  const save = async (enabled: boolean) => {
+    if (!config) return;
+    const next: OscConfig = {
+      ...config,
+      enabled,
+      port: Number(port),
+      allowed_sources: splitList(sources),
+      feedback_targets: splitList(targets),
+    };
+
+    try {
+      setBusy(true);
+      setError(null);
+      setStatus(await oscService.setConfig(next));
+      setConfig(next);
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err));
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  if (!config) return null;
+
+  return (
+    <Card className="gap-4 bg-background/55 p-4">
+      <div className="flex items-center justify-between gap-4">
+        <div>
+          <p className="text-sm font-medium">{t('OSC Control')}</p>
+          <p className="mt-0.5 text-xs text-muted-foreground">
+            {status?.running
+              ? t('Listening on {{address}}', { address: status.address })
+              : status?.error ?? t('Control Lumen from lighting consoles and Companion over OSC.')}
+          </p>
+        </div>
+        <Switch checked={config.enabled} disabled={busy} onCheckedChange={(checked) => save(checked)} />
+      </div>
+
+      <div className="grid gap-3 text-xs sm:grid-cols-[8rem_1fr]">
+        <label className="self-center text-muted-foreground" htmlFor="osc-port">
+          {t('Port')}
+        </label>
+        <Input
+          id="osc-port"
+          inputMode="numeric"
+          value={port}
+          onChange={(event) => setPort(event.target.value)}
+        />
+        <label className="self-center text-muted-foreground" htmlFor="osc-sources">
+          {t('Allowed sources')}
+        </label>
+        <Input
+          id="osc-sources"
+          placeholder={t('Only this computer')}
+          value={sources}
+          onChange={(event) => setSources(event.target.value)}
+        />
+        <label className="self-center text-muted-foreground" htmlFor="osc-targets">
+          {t('Feedback targets')}
+        </label>
+        <Input
+          id="osc-targets"
+          placeholder="192.168.1.50:9001"
+          value={targets}
+          onChange={(event) => setTargets(event.target.value)}
+        />
+      </div>
+
+      <div className="flex items-center justify-between gap-4">
+        <p className="text-xs text-muted-foreground">
+          {t('Address mappings live in config/osc.json and are read at startup.')}
+        </p>
+        <Button size="sm" variant="secondary" disabled={busy} onClick={() => save(config.enabled)}>
+          {t('Save')}
+        </Button>
+      </div>
+
+      {error && <p className="text-sm text-destructive">{error}</p>}
+    </Card>
+  );
+}
@@ -0,0 +1,183 @@
+'use client';
+
+import { useTranslation } from '@/lib/i18n';
+import { Check, KeyRound, X } from 'lucide-react';
+import { useCallback, useEffect, useState } from 'react';
+import type { PairingRequest, RegistrationTokenPayload } from '@/services';
+import { devicesService } from '@/services';
+import { Button } from '../ui/button';
+import { Card } from '../ui/card';
+import { Switch } from '../ui/switch';
+
+interface PairingSectionProps {
+  remoteEnabled: boolean;
+  approvalEnabled: boolean;
+  onApprovalChange: (enabled: boolean) => Promise<void>;
+}
+
+export function PairingSection({
+  remoteEnabled,
+  approvalEnabled,
+  onApprovalChange,
+}: PairingSectionProps) {
+  const { t } = useTranslation();
+  const [pin, setPin] = useState<RegistrationTokenPayload | null>(null);
+  const [requests, setRequests] = useState<PairingRequest[]>([]);
+  const [busy, setBusy] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+  const [now, setNow] = useState(() => Math.floor(Date.now() / 1000));
+
+  const loadRequests = useCallback(async () => {
+    setRequests(await devicesService.getPairingRequests());
+  }, []);
+
+  useEffect(() => {
+    let mounted = true;
+    const unlisteners: Array<() => void> = [];
+
+    loadRequests().catch(() => {});
+
+    Promise.all([
+      devicesService.onPairingRequest(() => {
+        if (mounted) loadRequests().catch(() => {});
+      }),
+      devicesService.onPairingRequestResolved((resolved) => {
+        if (!mounted) return;
+        setRequests((current) =>
+          current.filter((request) => request.request_id !== resolved.request_id)
+        );
+      }),
+      devicesService.onPairingPinsInvalidated(() => {
+        if (mounted) setPin(null);
+      }),
+      devicesService.onDeviceAuthenticated(() => {
+        if (mounted) setPin(null);
+      }),
+    ]).then((values) => {
+      unlisteners.push(...values);
+    });
+
+    return () => {
+      mounted = false;
+      for (const unlisten of unlisteners) unlisten();
+    };
+  }, [loadRequests]);
+
+  useEffect(() => {
+    const interval = window.setInterval(() => {
+      setNow(Math.floor(Date.now() / 1000));
+    }, 1000);
+
+    return () => window.clearInterval(interval);
+  }, []);
+
+  const pinExpiresIn = pin ? Math.max(0, pin.expires_at - now) : 0;
+
+  useEffect(() => {
+    if (pin && pinExpiresIn === 0) setPin(null);
+  }, [pin, pinExpiresIn]);
+
+  // biome-ignore lint/correctness/useExhaustiveDependencies: t is stable in practice
+  const run = useCallback(async (action: () => Promise<void>) => {
+    try {
+      setBusy(true);
+      setError(null);
+      await action();
+    } catch (err) {
+      setError(err instanceof Error ? err.message : t('Unable to update pairing.'));
+    } finally {
+      setBusy(false);
+    }
+  }, []);
+
+  return (
+    <Card className="gap-4 bg-background/55 p-4">
+      <div className="flex items-center justify-between gap-4">
+        <div>
+          <p className="text-sm font-medium">{t('Approve on Desktop')}</p>
+          <p className="mt-0.5 text-xs text-muted-foreground">
+            {t('Devices can ask to pair without a code. You accept or reject each request here.')}
+          </p>
+        </div>
+        <Switch
+          checked={approvalEnabled}
+          disabled={!remoteEnabled || busy}
+          onCheckedChange={(checked) => run(() => onApprovalChange(checked))}
+        />
+      </div>
+
+      <div className="flex items-center justify-between gap-4">
+        <div>
+          <p className="text-sm font-medium">{t('Pairing PIN')}</p>
+          <p className="mt-0.5 text-xs text-muted-foreground">
+            {pin
+              ? t('Enter this PIN on the device. Expires in {{seconds}}s.', {
+                  seconds: pinExpiresIn,
+                })
+              : t('For devices that cannot scan a QR code.')}
+          </p>
+        </div>
+        <div className="flex items-center gap-3">
+          {pin && <span className="font-mono text-lg tracking-[0.3em]">{pin.token}</span>}
+          <Button
+            size="sm"
+            variant="secondary"
+            disabled={!remoteEnabled || busy}
+            onClick={() =>
+              run(async () => {
+                setPin(await devicesService.generatePairingPin());
+              })
+            }
+          >
+            <KeyRound className="size-3.5" />
+            {pin ? t('New PIN') : t('Show PIN')}
+          </Button>
+        </div>
+      </div>
+
+      {requests.length > 0 && (
+        <div className="space-y-2">
+          <p className="text-sm font-medium">{t('Pairing Requests')}</p>
+          {requests.map((request) => (
+            <div
+              key={request.request_id}
+              className="flex items-center justify-between gap-4 rounded-md border px-3 py-2"
+            >
+              <div className="text-xs">
+                <p className="font-medium text-foreground">{request.device_name}</p>
+                <p className="text-muted-foreground">
+                  {request.device_type} · {request.os} · {request.ip}
+                </p>
+              </div>
+              <div className="flex items-center gap-2">
+                <Button
+                  size="sm"
+                  disabled={busy}
+                  onClick={() =>
+                    run(() => devicesService.approvePairingRequest(request.request_id))
+                  }
+                >
+                  <Check className="size-3.5" />
+                  {t('Approve')}
+                </Button>
+                <Button
+                  size="sm"
+                  variant="outline"
+                  disabled={busy}
+                  onClick={() =>
+                    run(() => devicesService.rejectPairingRequest(request.request_id))
+                  }
+                >
+                  <X className="size-3.5" />
+                  {t('Reject')}
+                </Button>
+              </div>
+            </div>
+          ))}
+        </div>
+      )}
+
+      {error && <p className="text-sm text-destructive">{error}</p>}
+    </Card>
+  );
+}
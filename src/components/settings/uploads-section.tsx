@@ -0,0 +1,103 @@
+'use client';
+
+import { useTranslation } from '@/lib/i18n';
+import { useEffect, useState } from 'react';
+import type { UploadConfig } from '@/services';
+import { mediaUploadService } from '@/services';
+import { Button } from '../ui/button';
+import { Card } from '../ui/card';
+import { Input } from '../ui/input';
+import { Switch } from '../ui/switch';
+
+const MIB = 1024 * 1024;
+
+export function UploadsSection() {
+  const { t } = useTranslation();
+  const [config, setConfig] = useState<UploadConfig | null>(null);
+  const [maxFileMb, setMaxFileMb] = useState('');
+  const [quotaMb, setQuotaMb] = useState('');
+  const [busy, setBusy] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    let mounted = true;
+
+    mediaUploadService
+      .getConfig()
+      .then((loaded) => {
+        if (!mounted) return;
+        setConfig(loaded);
+        setMaxFileMb(String(Math.round(loaded.max_file_bytes / MIB)));
+        setQuotaMb(String(Math.round(loaded.device_quota_bytes / MIB)));
+      })
+      .catch(() => {});
+
+    return () => {
+      mounted = false;
+    };
+  }, []);
+
+  const save = async (enabled: boolean) => {
+    if (!config) return;
+    const next: UploadConfig = {
+      enabled,
+      max_file_bytes: Number(maxFileMb) * MIB,
+      device_quota_bytes: Number(quotaMb) * MIB,
+    };
+
+    try {
+      setBusy(true);
+      setError(null);
+      setConfig(await mediaUploadService.setConfig(next));
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err));
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  if (!config) return null;
+
+  return (
+    <Card className="gap-4 bg-background/55 p-4">
+      <div className="flex items-center justify-between gap-4">
+        <div>
+          <p className="text-sm font-medium">{t('Remote Uploads')}</p>
+          <p className="mt-0.5 text-xs text-muted-foreground">
+            {t('Let paired devices with the media.upload permission send images, videos and audio.')}
+          </p>
+        </div>
+        <Switch checked={config.enabled} disabled={busy} onCheckedChange={(checked) => save(checked)} />
+      </div>
+
+      <div className="grid gap-3 text-xs sm:grid-cols-[8rem_1fr]">
+        <label className="self-center text-muted-foreground" htmlFor="upload-max-file">
+          {t('Max file size (MB)')}
+        </label>
+        <Input
+          id="upload-max-file"
+          inputMode="numeric"
+          value={maxFileMb}
+          onChange={(event) => setMaxFileMb(event.target.value)}
+        />
+        <label className="self-center text-muted-foreground" htmlFor="upload-quota">
+          {t('Quota per device (MB)')}
+        </label>
+        <Input
+          id="upload-quota"
+          inputMode="numeric"
+          value={quotaMb}
+          onChange={(event) => setQuotaMb(event.target.value)}
+        />
+      </div>
+
+      <div className="flex justify-end">
+        <Button size="sm" variant="secondary" disabled={busy} onClick={() => save(config.enabled)}>
+          {t('Save')}
+        </Button>
+      </div>
+
+      {error && <p className="text-sm text-destructive">{error}</p>}
+    </Card>
+  );
+}
@@ -4,6 +4,7 @@ import { readFile } from '@tauri-apps/plugin-fs';
 import { LucidePause, LucidePlay, LucideVolume2, LucideVolumeOff } from 'lucide-react';
 import { useCallback, useEffect, useRef, useState } from 'react';
 import ReactPlayer from 'react-player';
+import { controlSocketUrl } from '@/lib/control-socket';
 import { cn } from '@/lib/utils';
 import { thumbnailService } from '@/services/thumbnail-service';
 import { urlMediaService } from '@/services/url-media-service';
@@ -117,7 +118,7 @@ export const Videoplayer = ({
   };
 
   useEffect(() => {
-    const socket = new WebSocket('ws://localhost:8080');
+    const socket = new WebSocket(controlSocketUrl());
 
     socket.onopen = () => {
       setWs(socket);
@@ -143,9 +144,9 @@ export const Videoplayer = ({
     const registered: Array<() => void> = [];
 
     Promise.all([
-      listen('set-volume', (event) => {
+      listen<{ value: number }>('set-volume', (event) => {
         if (!mounted) return;
-        const v = event.payload as number;
+        const v = event.payload.value;
         if (typeof v === 'number' && v >= 0 && v <= 100) setVolume(v / 100);
       }),
       listen('mute', () => {
@@ -156,17 +157,17 @@ export const Videoplayer = ({
         if (!mounted) return;
         handlePlayPause();
       }),
-      listen('seek', (event) => {
+      listen<{ value: number }>('seek', (event) => {
         if (!mounted) return;
-        const seconds = event.payload as number;
+        const seconds = event.payload.value;
         if (playerRef.current) {
           playerRef.current.currentTime = seconds;
           setPlayed(seconds / (playerRef.current.duration || 1));
         }
       }),
-      listen('video-loop', (event) => {
+      listen<{ value: boolean }>('video-loop', (event) => {
         if (!mounted) return;
-        setIsLooping(event.payload as boolean);
+        setIsLooping(event.payload.value);
       }),
       listen('stop', () => {
         if (!mounted) return;
@@ -0,0 +1,27 @@
+import { useEffect } from 'react';
+import { setControlSocketPort } from '@/lib/control-socket';
+import { devicesService } from '@/services';
+
+/** Keeps the cached control socket port in sync with the backend listener. */
+export function useControlSocketPort() {
+  useEffect(() => {
+    let unlisten: (() => void) | undefined;
+
+    devicesService
+      .getControlServerStatus()
+      .then((status) => {
+        if (status.port) setControlSocketPort(status.port);
+      })
+      .catch((error) => console.error('Failed to read control server status:', error));
+
+    devicesService
+      .onControlServerStatus((status) => {
+        if (status.port) setControlSocketPort(status.port);
+      })
+      .then((fn) => {
+        unlisten = fn;
+      });
+
+    return () => unlisten?.();
+  }, []);
+}
@@ -0,0 +1,48 @@
+import { useEffect } from 'react';
+import { toast } from 'sonner';
+import { useTranslation } from '@/lib/i18n';
+import { type FollowerStatus, followerService } from '@/services';
+
+const TAKEOVER_TOAST_ID = 'follower-leader-lost';
+
+/** Offers a one-click takeover while this desktop follows a leader that stopped answering. */
+export function useFollowerTakeover(enabled = true) {
+  const { t } = useTranslation();
+
+  // biome-ignore lint/correctness/useExhaustiveDependencies: t is stable in practice
+  useEffect(() => {
+    if (!enabled) return;
+
+    const handleStatus = (status: FollowerStatus) => {
+      if (status.phase !== 'leader_lost') {
+        toast.dismiss(TAKEOVER_TOAST_ID);
+        return;
+      }
+      const leader = status.desktop_name ?? status.leader ?? '';
+      toast.warning(t('Lost contact with {{name}}', { name: leader }), {
+        id: TAKEOVER_TOAST_ID,
+        description: t('Take over to keep the presentation running from this computer.'),
+        duration: Number.POSITIVE_INFINITY,
+        action: {
+          label: t('Take over'),
+          onClick: () => {
+            void followerService.takeOver().catch((err) => {
+              toast.error(err instanceof Error ? err.message : String(err));
+            });
+          },
+        },
+      });
+    };
+
+    const unlisten = followerService.onStatus(handleStatus);
+    followerService
+      .getStatus()
+      .then(handleStatus)
+      .catch(() => {});
+
+    return () => {
+      unlisten.then((f) => f());
+      toast.dismiss(TAKEOVER_TOAST_ID);
+    };
+  }, [enabled]);
+}
@@ -0,0 +1,43 @@
+import { invoke } from '@tauri-apps/api/core';
+
+const STORAGE_KEY = 'lumen-control-port';
+const DEFAULT_CONTROL_PORT = 8080;
+
+let internalSecret: string | null = null;
+
+/** Port the desktop's control socket listens on, as last reported by the backend. */
+export function controlSocketPort(): number {
+  try {
+    const stored = Number(localStorage.getItem(STORAGE_KEY));
+    return Number.isInteger(stored) && stored > 0 ? stored : DEFAULT_CONTROL_PORT;
+  } catch {
+    return DEFAULT_CONTROL_PORT;
+  }
+}
+
+export function setControlSocketPort(port: number) {
+  try {
+    localStorage.setItem(STORAGE_KEY, String(port));
+  } catch {
+    // localStorage unavailable
+  }
+}
+
+/**
+ * Fetches the per-launch secret that marks this window's control socket
+ * connections as internal. Only the main and media windows get one; anywhere
+ * else the socket is treated like any other device.
+ */
+export async function loadInternalSecret(): Promise<void> {
+  try {
+    internalSecret = await invoke<string>('internal_socket_secret');
+  } catch {
+    internalSecret = null;
+  }
+}
+
+/** URL for the desktop's own windows to reach the control socket. */
+export function controlSocketUrl(): string {
+  const url = `ws://localhost:${controlSocketPort()}`;
+  return internalSecret ? `${url}/?internal=${encodeURIComponent(internalSecret)}` : url;
+}
@@ -2,6 +2,7 @@ import { createRouter, RouterProvider } from "@tanstack/react-router";
 import React, { StrictMode } from "react";
 import { createRoot } from "react-dom/client";
 import "./App.css";
+import { loadInternalSecret } from "./lib/control-socket";
 
 // Import the generated route tree
 import { routeTree } from "./routeTree.gen";
@@ -65,21 +66,24 @@ const ErrorBoundary = ({
 
 console.log("Aplicativo iniciando...");
 
-try {
-  createRoot(document.getElementById("root")!).render(
-    <StrictMode>
-      <GlobalErrorBoundary>
-        <RouterProvider router={router} />
-      </GlobalErrorBoundary>
-    </StrictMode>,
-  );
-} catch (error) {
-  console.error("Erro fatal ao renderizar aplicativo:", error);
-  document.getElementById("root")!.innerHTML = `
-    <div style="padding: 20px; font-family: monospace;">
-      <h1>Erro Fatal</h1>
-      <pre>${error instanceof Error ? error.message : String(error)}</pre>
-      <button onclick="window.location.reload()">Recarregar</button>
-    </div>
-  `;
-}
+// Control socket connections opened while rendering need the internal secret.
+void loadInternalSecret().finally(() => {
+  try {
+    createRoot(document.getElementById("root")!).render(
+      <StrictMode>
+        <GlobalErrorBoundary>
+          <RouterProvider router={router} />
+        </GlobalErrorBoundary>
+      </StrictMode>,
+    );
+  } catch (error) {
+    console.error("Erro fatal ao renderizar aplicativo:", error);
+    document.getElementById("root")!.innerHTML = `
+      <div style="padding: 20px; font-family: monospace;">
+        <h1>Erro Fatal</h1>
+        <pre>${error instanceof Error ? error.message : String(error)}</pre>
+        <button onclick="window.location.reload()">Recarregar</button>
+      </div>
+    `;
+  }
+});
@@ -0,0 +1,76 @@
+import { invoke } from '@tauri-apps/api/core';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+
+export interface AudiencePoll {
+  id: string;
+  question: string;
+  options: string[];
+  is_open: boolean;
+  created_at: number;
+  closed_at: number | null;
+  /** Vote count per option, in option order. */
+  results: number[];
+  total_votes: number;
+}
+
+export type AudienceQuestionStatus = 'pending' | 'approved' | 'rejected' | 'answered';
+
+export interface AudienceQuestion {
+  id: string;
+  text: string;
+  author: string | null;
+  status: AudienceQuestionStatus;
+  submitted_at: number;
+}
+
+class AudienceService {
+  async getPolls(): Promise<AudiencePoll[]> {
+    return invoke<AudiencePoll[]>('get_audience_polls');
+  }
+
+  /** Opening a poll closes whichever poll is currently open. */
+  async openPoll(question: string, options: string[]): Promise<AudiencePoll> {
+    return invoke<AudiencePoll>('open_audience_poll', { question, options });
+  }
+
+  async closePoll(pollId: string): Promise<AudiencePoll> {
+    return invoke<AudiencePoll>('close_audience_poll', { pollId });
+  }
+
+  async getQuestions(status?: AudienceQuestionStatus): Promise<AudienceQuestion[]> {
+    return invoke<AudienceQuestion[]>('get_audience_questions', { status: status ?? null });
+  }
+
+  async moderateQuestion(
+    questionId: string,
+    status: AudienceQuestionStatus
+  ): Promise<AudienceQuestion> {
+    return invoke<AudienceQuestion>('moderate_audience_question', { questionId, status });
+  }
+
+  async pushPollResults(pollId: string): Promise<void> {
+    await invoke('push_audience_poll_results', { pollId });
+  }
+
+  async pushQuestion(questionId: string): Promise<void> {
+    await invoke('push_audience_question', { questionId });
+  }
+
+  async onPollUpdated(handler: (poll: AudiencePoll) => void): Promise<UnlistenFn> {
+    return listen<AudiencePoll>('audience_poll_updated', (event) => handler(event.payload));
+  }
+
+  async onQuestionSubmitted(handler: (question: AudienceQuestion) => void): Promise<UnlistenFn> {
+    return listen<AudienceQuestion>('audience_question_submitted', (event) =>
+      handler(event.payload)
+    );
+  }
+
+  async onQuestionUpdated(handler: (question: AudienceQuestion) => void): Promise<UnlistenFn> {
+    return listen<AudienceQuestion>('audience_question_updated', (event) =>
+      handler(event.payload)
+    );
+  }
+}
+
+export const audienceService = new AudienceService();
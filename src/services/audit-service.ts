@@ -0,0 +1,56 @@
+import { invoke } from '@tauri-apps/api/core';
+import type { RemoteAccessSettings } from './devices-service';
+
+export type AuditCategory = 'auth' | 'pairing' | 'command' | 'permissions' | 'stream';
+
+export type AuditOutcome = 'ok' | 'denied' | 'failed';
+
+export interface AuditEntry {
+  id: number;
+  at: number;
+  category: AuditCategory;
+  action: string;
+  device_id: string | null;
+  session_id: string | null;
+  peer: string | null;
+  summary: string;
+  outcome: AuditOutcome;
+  reason: string | null;
+}
+
+export interface AuditFilter {
+  device_id?: string;
+  category?: AuditCategory;
+  outcome?: AuditOutcome;
+  /** Unix seconds, inclusive. */
+  since?: number;
+  /** Unix seconds, exclusive. */
+  until?: number;
+  search?: string;
+  offset?: number;
+  limit?: number;
+}
+
+export interface AuditPage {
+  entries: AuditEntry[];
+  total: number;
+  offset: number;
+  limit: number;
+}
+
+class AuditService {
+  async getAuditLog(filter: AuditFilter = {}): Promise<AuditPage> {
+    return invoke<AuditPage>('get_audit_log', { filter });
+  }
+
+  /** Writes every entry matching `filter` to `path` as CSV. */
+  async exportAuditLog(path: string, filter: AuditFilter = {}): Promise<number> {
+    return invoke<number>('export_audit_log', { filter, path });
+  }
+
+  async setAuditRetention(retentionDays: number): Promise<RemoteAccessSettings> {
+    return invoke<RemoteAccessSettings>('set_audit_retention', { retentionDays });
+  }
+}
+
+export const auditService = new AuditService();
@@ -0,0 +1,77 @@
+import { invoke } from '@tauri-apps/api/core';
+import type { RemoteAccessSettings } from './devices-service';
+
+export interface TransferExportSummary {
+  devices: number;
+  roles: number;
+  /** Whether the local TLS CA was included. */
+  tls_ca: boolean;
+}
+
+export interface TransferDeviceConflict {
+  device_name: string;
+  last_connected_at: number | null;
+}
+
+export interface TransferDevice {
+  device_id: string;
+  device_name: string;
+  device_type: string;
+  os: string;
+  role: string | null;
+  is_active: boolean;
+  last_connected_at: number | null;
+  /** Set when the device is already paired here; importing it replaces that device. */
+  conflict: TransferDeviceConflict | null;
+}
+
+export interface TransferRole {
+  role_id: string;
+  name: string;
+  /** A different role with this id exists here and is kept. */
+  conflict: boolean;
+}
+
+export interface TransferPreview {
+  exported_at: number;
+  desktop_name: string;
+  settings: RemoteAccessSettings;
+  devices: TransferDevice[];
+  roles: TransferRole[];
+  tls_ca: boolean;
+}
+
+export interface TransferImportSummary {
+  devices: number;
+  replaced: number;
+  roles: number;
+  settings: boolean;
+  warnings: string[];
+}
+
+class DeviceTransferService {
+  async exportRemoteAccess(path: string, password: string): Promise<TransferExportSummary> {
+    return invoke<TransferExportSummary>('export_remote_access', { path, password });
+  }
+
+  /** Decrypts an export and checks it against this desktop without changing anything. */
+  async previewImport(path: string, password: string): Promise<TransferPreview> {
+    return invoke<TransferPreview>('preview_remote_access_import', { path, password });
+  }
+
+  async importRemoteAccess(
+    path: string,
+    password: string,
+    deviceIds: string[],
+    importSettings: boolean
+  ): Promise<TransferImportSummary> {
+    return invoke<TransferImportSummary>('import_remote_access', {
+      path,
+      password,
+      deviceIds,
+      importSettings,
+    });
+  }
+}
+
+export const deviceTransferService = new DeviceTransferService();
@@ -1,7 +1,15 @@
 import { invoke } from '@tauri-apps/api/core';
 import { listen, type UnlistenFn } from '@tauri-apps/api/event';
 
+/** Top-level areas; each has a derived flag on `DevicePermissions`. */
+export type PermissionArea = 'player' | 'lyrics' | 'bible' | 'media' | 'streaming';
+
 export interface DevicePermissions {
+  role: string | null;
+  /** Scopes granted on top of the role, e.g. `player.seek`. */
+  scopes: string[];
+  /** Effective scopes, resolved by the backend. */
+  granted: string[];
   player: boolean;
   lyrics: boolean;
   bible: boolean;
@@ -9,15 +17,30 @@ export interface DevicePermissions {
   streaming: boolean;
 }
 
+export interface PermissionScope {
+  scope: string;
+  description: string;
+  module_id?: string;
+}
+
+export interface PermissionRole {
+  role_id: string;
+  name: string;
+  scopes: string[];
+  built_in: boolean;
+}
+
 export interface Device {
   device_id: string;
   device_name: string;
   device_type: string;
   os: string;
   version: string;
-  access_token: string;
+  /** Unix seconds after which the device must pair again. */
+  token_expires_at?: number | null;
   is_active: boolean;
   permissions: DevicePermissions;
+  lyrics_language: string;
   registered_at: number;
   last_connected_at?: number | null;
 }
@@ -25,11 +48,119 @@ export interface Device {
 export interface RemoteAccessSettings {
   remote_enabled: boolean;
   transmission_enabled: boolean;
+  preferred_interface?: string | null;
+  tls_enabled?: boolean;
+  heartbeat_interval_secs?: number;
+  session_timeout_secs?: number;
+  limits?: ControlLimits;
+  token_lifetime_days?: number | null;
+  rotate_tokens_on_auth?: boolean;
+  pairing_approval_enabled?: boolean;
+  audit_retention_days?: number;
+  /** `null` listens on every IPv4 and IPv6 address. */
+  control_bind_address?: string | null;
+  control_port?: number;
+  control_port_fallback?: boolean;
+}
+
+export interface ControlLimits {
+  max_message_bytes: number;
+  messages_per_second: number;
+  burst: number;
+  max_connections_per_ip: number;
+}
+
+export interface DevicePresence {
+  device_id: string;
+  session_id: string;
+  online: boolean;
+  reason: 'connected' | 'disconnected' | 'timed_out';
+  at: number;
 }
 
 export interface RegistrationTokenPayload {
   token: string;
   expires_at: number;
+  ip: string;
+  port: number;
+  tls: boolean;
+  /** SHA-256 of the server certificate, present while TLS is enabled. */
+  cert_fingerprint?: string;
+  /** SHA-256 of the local CA, present while TLS is enabled. */
+  ca_fingerprint?: string;
+}
+
+export type PairingKind = 'qr' | 'pin' | 'approval';
+
+export interface PairingToken {
+  token: string;
+  kind: PairingKind;
+  created_at: number;
+  expires_at: number;
+  is_used: boolean;
+  /** Set on approval tokens, which only this device can redeem. */
+  device_id?: string;
+}
+
+export interface PairingRequest {
+  request_id: string;
+  device_id: string;
+  device_name: string;
+  device_type: string;
+  os: string;
+  version: string;
+  ip: string;
+  requested_at: number;
+  expires_at: number;
+}
+
+export interface PairingRequestResolved {
+  request_id: string;
+  device_id: string;
+  outcome: 'approved' | 'rejected' | 'expired' | 'withdrawn' | 'replaced';
+}
+
+export interface ControlServerStatus {
+  running: boolean;
+  addresses: string[];
+  /** Differs from `requested_port` when the configured port was taken. */
+  port: number | null;
+  requested_port: number;
+  error: string | null;
+}
+
+export type ControlLockScope = 'player' | 'lyrics' | 'bible' | 'media';
+
+export interface ControlLock {
+  scope: ControlLockScope;
+  /** `null` while the desktop holds the lock. */
+  device_id: string | null;
+  device_name: string;
+  acquired_at: number;
+  /** `null` for desktop locks, which last until released. */
+  expires_at: number | null;
+}
+
+export interface ControlLockChanged {
+  scope: ControlLockScope;
+  holder: ControlLock | null;
+  reason: 'acquired' | 'released' | 'expired' | 'taken_over' | 'disconnected';
+}
+
+export interface TlsInfo {
+  enabled: boolean;
+  fingerprint: string | null;
+  ca_fingerprint: string | null;
+  ca_pem: string | null;
+}
+
+export type NetworkInterfaceKind = 'loopback' | 'ethernet' | 'wifi' | 'vpn' | 'virtual' | 'other';
+
+export interface NetworkInterfaceInfo {
+  name: string;
+  addresses: string[];
+  kind: NetworkInterfaceKind;
+  is_preferred: boolean;
 }
 
 class DevicesService {
@@ -54,6 +185,57 @@ class DevicesService {
     return invoke<string>('get_local_ip');
   }
 
+  async listNetworkInterfaces(): Promise<NetworkInterfaceInfo[]> {
+    return invoke<NetworkInterfaceInfo[]>('list_network_interfaces');
+  }
+
+  /** Pass `null` to go back to automatic interface selection. */
+  async setPreferredInterface(name: string | null): Promise<string | null> {
+    return invoke<string | null>('set_preferred_interface', { name });
+  }
+
+  async getControlLocks(): Promise<ControlLock[]> {
+    return invoke<ControlLock[]>('get_control_locks');
+  }
+
+  /** Locks `scope` for the desktop, taking it from any remote holder. */
+  async takeControlLock(scope: ControlLockScope): Promise<ControlLock> {
+    return invoke<ControlLock>('take_control_lock', { scope });
+  }
+
+  async releaseControlLock(scope: ControlLockScope): Promise<void> {
+    return invoke('release_control_lock', { scope });
+  }
+
+  async getControlServerStatus(): Promise<ControlServerStatus> {
+    return invoke<ControlServerStatus>('get_control_server_status');
+  }
+
+  /** Rebinds the control socket; the previous listener stays if this fails. */
+  async setControlServer(
+    bindAddress: string | null,
+    port: number,
+    portFallback: boolean
+  ): Promise<ControlServerStatus> {
+    return invoke<ControlServerStatus>('set_control_server', {
+      bindAddress,
+      port,
+      portFallback,
+    });
+  }
+
+  async getTlsInfo(): Promise<TlsInfo> {
+    return invoke<TlsInfo>('get_tls_info');
+  }
+
+  async setRemoteTlsEnabled(enabled: boolean): Promise<TlsInfo> {
+    return invoke<TlsInfo>('set_remote_tls_enabled', { enabled });
+  }
+
+  async regenerateTlsCertificate(): Promise<TlsInfo> {
+    return invoke<TlsInfo>('regenerate_tls_certificate');
+  }
+
   async generateRegistrationToken(): Promise<RegistrationTokenPayload> {
     return invoke<RegistrationTokenPayload>('gen_reg_token');
   }
@@ -64,15 +246,96 @@ class DevicesService {
 
   async updateDevicePermissions(
     deviceId: string,
-    permissions: DevicePermissions
+    permissions: Pick<DevicePermissions, 'role' | 'scopes'>
   ): Promise<void> {
     await invoke('update_device_permissions', { deviceId, permissions });
   }
 
+  async getPermissionScopes(): Promise<PermissionScope[]> {
+    return invoke<PermissionScope[]>('get_permission_scopes');
+  }
+
+  async getPermissionRoles(): Promise<PermissionRole[]> {
+    return invoke<PermissionRole[]>('get_permission_roles');
+  }
+
+  async savePermissionRole(
+    role: Omit<PermissionRole, 'built_in'>
+  ): Promise<PermissionRole> {
+    return invoke<PermissionRole>('save_permission_role', { role });
+  }
+
+  async deletePermissionRole(roleId: string): Promise<void> {
+    await invoke('delete_permission_role', { roleId });
+  }
+
+  async setDeviceLyricsLanguage(deviceId: string, language: string | null): Promise<void> {
+    await invoke('set_device_lyrics_language', { deviceId, language });
+  }
+
+  async setControlLimits(limits: ControlLimits): Promise<RemoteAccessSettings> {
+    return invoke<RemoteAccessSettings>('set_control_limits', { limits });
+  }
+
+  /** `tokenLifetimeDays: null` keeps tokens valid until revoked. */
+  async setTokenPolicy(
+    tokenLifetimeDays: number | null,
+    rotateTokensOnAuth: boolean
+  ): Promise<RemoteAccessSettings> {
+    return invoke<RemoteAccessSettings>('set_token_policy', {
+      tokenLifetimeDays,
+      rotateTokensOnAuth,
+    });
+  }
+
+  /** Disconnects every paired device; each one has to pair again. */
+  async revokeAllDeviceTokens(): Promise<number> {
+    return invoke<number>('revoke_all_device_tokens');
+  }
+
+  /** A 6-digit PIN for devices without a camera, valid for five minutes. */
+  async generatePairingPin(): Promise<RegistrationTokenPayload> {
+    return invoke<RegistrationTokenPayload>('generate_pairing_pin');
+  }
+
+  async getPairingTokens(): Promise<PairingToken[]> {
+    return invoke<PairingToken[]>('get_pairing_tokens');
+  }
+
+  async cancelPairingToken(token: string): Promise<void> {
+    await invoke('cancel_pairing_token', { token });
+  }
+
+  async setPairingApproval(enabled: boolean): Promise<RemoteAccessSettings> {
+    return invoke<RemoteAccessSettings>('set_pairing_approval', { enabled });
+  }
+
+  async getPairingRequests(): Promise<PairingRequest[]> {
+    return invoke<PairingRequest[]>('get_pairing_requests');
+  }
+
+  async approvePairingRequest(requestId: string): Promise<void> {
+    await invoke('approve_pairing_request', { requestId });
+  }
+
+  async rejectPairingRequest(requestId: string): Promise<void> {
+    await invoke('reject_pairing_request', { requestId });
+  }
+
   async removeDevice(deviceId: string): Promise<void> {
     await invoke('remove_device', { deviceId });
   }
 
+  async setSessionHeartbeat(
+    heartbeatIntervalSecs: number,
+    sessionTimeoutSecs: number
+  ): Promise<RemoteAccessSettings> {
+    return invoke<RemoteAccessSettings>('set_session_heartbeat', {
+      heartbeatIntervalSecs,
+      sessionTimeoutSecs,
+    });
+  }
+
   async onDeviceRegistered(handler: (device: Device) => void): Promise<UnlistenFn> {
     return listen<Device>('device_registered', (event) => handler(event.payload));
   }
@@ -96,6 +359,36 @@ class DevicesService {
   async onDeviceRemoved(handler: (deviceId: string) => void): Promise<UnlistenFn> {
     return listen<string>('device_removed', (event) => handler(event.payload));
   }
+
+  async onPairingRequest(handler: (request: PairingRequest) => void): Promise<UnlistenFn> {
+    return listen<PairingRequest>('pairing_request', (event) => handler(event.payload));
+  }
+
+  async onPairingRequestResolved(
+    handler: (resolved: PairingRequestResolved) => void
+  ): Promise<UnlistenFn> {
+    return listen<PairingRequestResolved>('pairing_request_resolved', (event) =>
+      handler(event.payload)
+    );
+  }
+
+  async onControlLockChanged(handler: (change: ControlLockChanged) => void): Promise<UnlistenFn> {
+    return listen<ControlLockChanged>('control_lock_changed', (event) => handler(event.payload));
+  }
+
+  async onPairingPinsInvalidated(handler: () => void): Promise<UnlistenFn> {
+    return listen<number>('pairing_pins_invalidated', () => handler());
+  }
+
+  async onControlServerStatus(
+    handler: (status: ControlServerStatus) => void
+  ): Promise<UnlistenFn> {
+    return listen<ControlServerStatus>('control_server_status', (event) => handler(event.payload));
+  }
+
+  async onDevicePresenceChanged(handler: (presence: DevicePresence) => void): Promise<UnlistenFn> {
+    return listen<DevicePresence>('device_presence_changed', (event) => handler(event.payload));
+  }
 }
 
 export const devicesService = new DevicesService();
@@ -0,0 +1,61 @@
+import { invoke } from '@tauri-apps/api/core';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+
+export type FollowerPhase = 'disabled' | 'connecting' | 'following' | 'leader_lost' | 'taken_over';
+
+export interface FollowerStatus {
+  phase: FollowerPhase;
+  paired: boolean;
+  /** `host:port` of the leader. */
+  leader: string | null;
+  desktop_name: string | null;
+  last_seen_at: number | null;
+  revision: number | null;
+  /** Heartbeats the leader may miss before takeover is offered. */
+  missed_heartbeats: number;
+  error: string | null;
+}
+
+export interface FollowerQueue {
+  paths: string[];
+  position: number | null;
+}
+
+class FollowerService {
+  async getStatus(): Promise<FollowerStatus> {
+    return invoke<FollowerStatus>('get_follower_status');
+  }
+
+  /** Pairs with the leader whose pairing code was pasted and starts following it. */
+  async pair(code: string): Promise<FollowerStatus> {
+    return invoke<FollowerStatus>('pair_follower', { code });
+  }
+
+  async setEnabled(enabled: boolean): Promise<FollowerStatus> {
+    return invoke<FollowerStatus>('set_follower_enabled', { enabled });
+  }
+
+  async setMissedHeartbeats(missedHeartbeats: number): Promise<FollowerStatus> {
+    return invoke<FollowerStatus>('set_follower_missed_heartbeats', { missedHeartbeats });
+  }
+
+  async unpair(): Promise<FollowerStatus> {
+    return invoke<FollowerStatus>('unpair_follower');
+  }
+
+  /** Stops mirroring the leader so this desktop carries on from where it left off. */
+  async takeOver(): Promise<FollowerStatus> {
+    return invoke<FollowerStatus>('take_over_from_leader');
+  }
+
+  async onStatus(handler: (status: FollowerStatus) => void): Promise<UnlistenFn> {
+    return listen<FollowerStatus>('follower_status', (event) => handler(event.payload));
+  }
+
+  /** Fires when the leader's queue changed, with paths mapped to this desktop. */
+  async onQueue(handler: (queue: FollowerQueue) => void): Promise<UnlistenFn> {
+    return listen<FollowerQueue>('follower-queue', (event) => handler(event.payload));
+  }
+}
+
+export const followerService = new FollowerService();
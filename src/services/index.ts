@@ -1,18 +1,64 @@
+export type {
+  AudiencePoll,
+  AudienceQuestion,
+  AudienceQuestionStatus,
+} from './audience-service';
+export { audienceService } from './audience-service';
+export type {
+  AuditCategory,
+  AuditEntry,
+  AuditFilter,
+  AuditOutcome,
+  AuditPage,
+} from './audit-service';
+export { auditService } from './audit-service';
 export type { FileInitService } from './file-init-service';
 export { fileInitService } from './file-init-service';
 export type { FileManagementService } from './file-management-service';
 export { fileManagementService } from './file-management-service';
 
 export { mediaDbService } from './media-db-service';
+export type { MediaUploaded, UploadConfig } from './media-upload-service';
+export { mediaUploadService } from './media-upload-service';
+export type {
+  OscAction,
+  OscConfig,
+  OscFeedback,
+  OscMapping,
+  OscStatus,
+} from './osc-service';
+export { oscService } from './osc-service';
 export type { QueueDbItem } from './queue-db-service';
 export { queueDbService } from './queue-db-service';
 export type {
+  ControlLock,
+  ControlLockChanged,
+  ControlLockScope,
+  ControlServerStatus,
   Device,
   DevicePermissions,
+  PairingKind,
+  PairingRequest,
+  PairingRequestResolved,
+  PairingToken,
+  PermissionArea,
+  PermissionRole,
+  PermissionScope,
   RegistrationTokenPayload,
   RemoteAccessSettings,
 } from './devices-service';
 export { devicesService } from './devices-service';
+export type {
+  TransferDevice,
+  TransferDeviceConflict,
+  TransferExportSummary,
+  TransferImportSummary,
+  TransferPreview,
+  TransferRole,
+} from './device-transfer-service';
+export { deviceTransferService } from './device-transfer-service';
+export type { FollowerPhase, FollowerQueue, FollowerStatus } from './follower-service';
+export { followerService } from './follower-service';
 
 export type { DownloadStatus, FileInfo, MediaType } from './types';
 export { remoteSyncService } from './remote-sync-service';
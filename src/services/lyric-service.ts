@@ -11,11 +11,18 @@ export interface LyricMetadata {
   fontSize: string;
   alignment: string;
   globalBackground: string;
+  language?: string;
+}
+
+export interface LyricTranslation {
+  language: string;
+  lines: string[];
 }
 
 export interface LyricSlide {
   lines: string[];
   background?: string;
+  translations?: LyricTranslation[];
 }
 
 export interface LyricData {
@@ -25,6 +32,7 @@ export interface LyricData {
 
 const SLIDE_BG_PREFIX = '<!-- bg:';
 const SLIDE_BG_SUFFIX = '-->';
+const SLIDE_LANG_PREFIX = '<!-- lang:';
 
 function serializeLyric(data: LyricData): string {
   const lines: string[] = [];
@@ -39,6 +47,9 @@ function serializeLyric(data: LyricData): string {
   if (data.metadata.globalBackground) {
     lines.push(`globalBackground: ${data.metadata.globalBackground}`);
   }
+  if (data.metadata.language) {
+    lines.push(`language: ${data.metadata.language}`);
+  }
   lines.push('---');
 
   for (const slide of data.slides) {
@@ -50,6 +61,13 @@ function serializeLyric(data: LyricData): string {
     for (const line of slide.lines) {
       lines.push(line);
     }
+    for (const translation of slide.translations ?? []) {
+      if (translation.lines.length === 0) continue;
+      lines.push(`${SLIDE_LANG_PREFIX} ${translation.language} ${SLIDE_BG_SUFFIX}`);
+      for (const line of translation.lines) {
+        lines.push(line);
+      }
+    }
   }
 
   return `${lines.join('\n').trim()}\n`;
@@ -91,9 +109,28 @@ function parseSlides(body: string): LyricSlide[] {
       if (bgMatch) background = bgMatch[1];
     }
 
-    const contentLines = lines.filter(Boolean);
+    const contentLines: string[] = [];
+    const translations: LyricTranslation[] = [];
+    let current = contentLines;
+    for (const line of lines) {
+      if (line.startsWith(SLIDE_LANG_PREFIX)) {
+        const langMatch = line.match(/<!-- lang:\s*(.*?)\s*-->/);
+        if (langMatch?.[1]) {
+          const translation: LyricTranslation = { language: langMatch[1], lines: [] };
+          translations.push(translation);
+          current = translation.lines;
+        }
+        continue;
+      }
+      if (line) current.push(line);
+    }
+
     if (contentLines.length > 0) {
-      acc.push({ lines: contentLines, background });
+      acc.push(
+        translations.length > 0
+          ? { lines: contentLines, background, translations }
+          : { lines: contentLines, background }
+      );
     }
 
     return acc;
@@ -107,6 +144,11 @@ export function buildLyricSearchContent(data: LyricData): string {
     for (const line of slide.lines) {
       if (line.trim()) parts.push(line);
     }
+    for (const translation of slide.translations ?? []) {
+      for (const line of translation.lines) {
+        if (line.trim()) parts.push(line);
+      }
+    }
   }
   return parts.join('\n');
 }
@@ -124,6 +166,7 @@ export function parseLyricFile(content: string): LyricData {
       fontSize: metadata.fontSize ?? '48px',
       alignment: metadata.alignment ?? 'center',
       globalBackground: metadata.globalBackground ?? '',
+      language: metadata.language || undefined,
     },
     slides,
   };
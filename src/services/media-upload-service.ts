@@ -0,0 +1,40 @@
+import { invoke } from '@tauri-apps/api/core';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+import type { MediaType } from './types';
+
+export interface UploadConfig {
+  enabled: boolean;
+  max_file_bytes: number;
+  /** Total size of the files one device has uploaded that are still in the library. */
+  device_quota_bytes: number;
+}
+
+export interface MediaUploaded {
+  /** `null` when the library table did not exist yet. */
+  media_id: number | null;
+  name: string;
+  path: string;
+  extension: string;
+  media_type: Extract<MediaType, 'image' | 'video' | 'audio'>;
+  size: number;
+  thumbnail_path: string | null;
+  device_id: string;
+  device_name: string;
+}
+
+class MediaUploadService {
+  async getConfig(): Promise<UploadConfig> {
+    return invoke<UploadConfig>('get_upload_config');
+  }
+
+  async setConfig(config: UploadConfig): Promise<UploadConfig> {
+    return invoke<UploadConfig>('set_upload_config', { config });
+  }
+
+  /** Fires once a file sent from a paired device is stored in the library. */
+  async onUploaded(handler: (uploaded: MediaUploaded) => void): Promise<UnlistenFn> {
+    return listen<MediaUploaded>('remote_media_uploaded', (event) => handler(event.payload));
+  }
+}
+
+export const mediaUploadService = new MediaUploadService();
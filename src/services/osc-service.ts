@@ -0,0 +1,66 @@
+import { invoke } from '@tauri-apps/api/core';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+
+export type OscAction =
+  | 'next'
+  | 'previous'
+  | 'play_pause'
+  | 'stop'
+  | 'mute'
+  | 'volume'
+  | 'seek'
+  | 'loop'
+  | 'lyrics_slide';
+
+export interface OscMapping {
+  address: string;
+  action: OscAction;
+  /** Multiplies the first argument, e.g. `100` for a 0–1 fader on volume. */
+  scale: number;
+}
+
+export interface OscFeedback {
+  address: string;
+  /** Dot path into the live state, e.g. `playback.is_playing`. */
+  field: string;
+}
+
+export interface OscConfig {
+  enabled: boolean;
+  /** `null` listens on every IPv4 address. */
+  bind_address: string | null;
+  port: number;
+  /** Empty allows only this computer. */
+  allowed_sources: string[];
+  /** `host:port` pairs. */
+  feedback_targets: string[];
+  mappings: OscMapping[];
+  feedback: OscFeedback[];
+}
+
+export interface OscStatus {
+  running: boolean;
+  address: string | null;
+  error: string | null;
+}
+
+class OscService {
+  async getConfig(): Promise<OscConfig> {
+    return invoke<OscConfig>('get_osc_config');
+  }
+
+  /** Saves and rebinds; the previous config stays if the new one cannot bind. */
+  async setConfig(config: OscConfig): Promise<OscStatus> {
+    return invoke<OscStatus>('set_osc_config', { config });
+  }
+
+  async getStatus(): Promise<OscStatus> {
+    return invoke<OscStatus>('get_osc_status');
+  }
+
+  async onStatus(handler: (status: OscStatus) => void): Promise<UnlistenFn> {
+    return listen<OscStatus>('osc_status', (event) => handler(event.payload));
+  }
+}
+
+export const oscService = new OscService();
@@ -26,9 +26,21 @@ export interface PlayerSyncPayload {
     slide_index?: number;
     total_slides?: number;
   };
+  queue?: {
+    /** Queued file paths, in play order. */
+    paths: string[];
+    /** Index of the playing item, when it is queued. */
+    position?: number;
+  };
   action?: string;
 }
 
+/**
+ * Payload of a command event forwarded from a remote client. `command_id` is
+ * set when the client waits for an answer.
+ */
+export type RemoteCommand<T = object> = T & { command_id?: string };
+
 class RemoteSyncService {
   async broadcast(payload: PlayerSyncPayload, requiredPermission?: string): Promise<void> {
     const mediaType = payload.media.type;
@@ -40,6 +52,24 @@ class RemoteSyncService {
       required_permission: requiredPermission ?? null,
     });
   }
+
+  /**
+   * Reports how a remote command went, answering the client that sent it.
+   * Call it from the listener that applies the command, with the
+   * `command_id` from the event payload. Does nothing without one.
+   */
+  async completeCommand(
+    commandId: string | undefined,
+    ok: boolean,
+    data?: Record<string, unknown>,
+    message?: string
+  ): Promise<void> {
+    if (!commandId) return;
+    await invoke('complete_remote_command', {
+      commandId,
+      outcome: { ok, data: data ?? null, message: message ?? null },
+    });
+  }
 }
 
 export const remoteSyncService = new RemoteSyncService();
@@ -8,6 +8,9 @@ export interface StreamingConfig {
   html_server_port: number;
   hardware_encoding: boolean;
   content_protection: boolean;
+  html_output_languages: Record<string, string>;
+  /** Accept votes and questions on the HTML server's `/audience` page. */
+  audience_enabled: boolean;
 }
 
 export interface StreamingStatus {
@@ -18,6 +21,12 @@ export interface StreamingStatus {
   html_url: string | null;
 }
 
+export interface HtmlOutputInfo {
+  output_id: string;
+  language: string;
+  connected: number;
+}
+
 class StreamingService {
   async getConfig(): Promise<StreamingConfig> {
     return invoke<StreamingConfig>('get_streaming_config');
@@ -38,6 +47,15 @@ class StreamingService {
   async pushBlank(): Promise<void> {
     await invoke('push_stream_blank');
   }
+
+  async getHtmlOutputs(): Promise<HtmlOutputInfo[]> {
+    return invoke<HtmlOutputInfo[]>('get_html_outputs');
+  }
+
+  /** `language` of `null` or `'all'` shows every language stacked. */
+  async setHtmlOutputLanguage(outputId: string, language: string | null): Promise<StreamingConfig> {
+    return invoke<StreamingConfig>('set_html_output_language', { outputId, language });
+  }
 }
 
 export const streamingService = new StreamingService();
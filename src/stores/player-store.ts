@@ -3,9 +3,17 @@ import { emit, listen, type UnlistenFn } from '@tauri-apps/api/event';
 import { WebviewWindow } from '@tauri-apps/api/webviewWindow';
 import { readFile } from '@tauri-apps/plugin-fs';
 import { create } from 'zustand';
+import { controlSocketUrl } from '@/lib/control-socket';
 import { useModuleStore } from '@/modules/store';
 import { getSetting, saveSetting } from '@/services/db';
-import { remoteSyncService } from '@/services/remote-sync-service';
+import { followerService } from '@/services/follower-service';
+import { mediaDbService } from '@/services/media-db-service';
+import { mediaUploadService } from '@/services/media-upload-service';
+import {
+  type PlayerSyncPayload,
+  type RemoteCommand,
+  remoteSyncService,
+} from '@/services/remote-sync-service';
 import { urlMediaService } from '@/services/url-media-service';
 import { useQueueEntriesStore } from '@/stores/queue-entries-store';
 import { useQueueStore } from '@/stores/queue-store';
@@ -207,7 +215,7 @@ export const usePlayerStore = create<PlayerStore>((set, get) => ({
   currentImagePath: null,
 
   initWs: () => {
-    const socket = new WebSocket('ws://localhost:8080');
+    const socket = new WebSocket(controlSocketUrl());
     socket.onopen = () => set({ ws: socket });
     socket.onclose = () => set({ ws: null });
     socket.onerror = () => set({ ws: null });
@@ -279,8 +287,9 @@ export const usePlayerStore = create<PlayerStore>((set, get) => ({
       }
     );
 
-    const unlistenStop = listen('stop', () => {
+    const unlistenStop = listen<RemoteCommand>('stop', (event) => {
       set({ isPlaying: false, isScreenOpen: false });
+      completeCommand(event.payload.command_id, get);
       saveSetting('last_time', '0').catch(() => {});
       void broadcastPlayerSync(get, 'stop');
       invoke('push_stream_blank').catch(() => {});
@@ -291,60 +300,86 @@ export const usePlayerStore = create<PlayerStore>((set, get) => ({
       }
     });
 
-    const unlistenSetVolume = listen<number>('set-volume', (event) => {
-      const nextVolume = Math.max(0, Math.min(100, Number(event.payload) || 0));
+    const unlistenSetVolume = listen<RemoteCommand<{ value: number }>>('set-volume', (event) => {
+      const nextVolume = Math.max(0, Math.min(100, Number(event.payload.value) || 0));
       set({
         volume: nextVolume,
         isMuted: nextVolume === 0,
       });
+      completeCommand(event.payload.command_id, get);
     });
 
-    const unlistenMute = listen('mute', () => {
-      if (pendingMute) {
+    const unlistenMute = listen<RemoteCommand>('mute', (event) => {
+      const commandId = event.payload.command_id;
+      // The desktop's own toggle comes back through the socket without a
+      // command id and is already applied.
+      if (pendingMute && !commandId) {
         pendingMute = false;
         return;
       }
       const current = get();
       set({ isMuted: !current.isMuted });
+      completeCommand(commandId, get);
     });
 
-    const unlistenPlayPause = listen('play-pause', () => {
+    const unlistenPlayPause = listen<RemoteCommand>('play-pause', (event) => {
+      const commandId = event.payload.command_id;
+      if (!get().currentFilePath) {
+        rejectCommand(commandId, 'Nothing is loaded');
+        return;
+      }
       set((state) => ({ isPlaying: !state.isPlaying }));
+      completeCommand(commandId, get);
     });
 
-    const unlistenSeek = listen<number>('seek', (event) => {
-      if (get().isDragging) return;
-      set({ localTime: Number(event.payload) || 0 });
+    const unlistenSeek = listen<RemoteCommand<{ value: number }>>('seek', (event) => {
+      const commandId = event.payload.command_id;
+      if (!get().currentFilePath) {
+        rejectCommand(commandId, 'Nothing is loaded');
+        return;
+      }
+      if (get().isDragging) {
+        rejectCommand(commandId, 'The operator is seeking');
+        return;
+      }
+      set({ localTime: Number(event.payload.value) || 0 });
+      completeCommand(commandId, get);
     });
 
-    const unlistenLoop = listen<boolean>('video-loop', (event) => {
-      set({ isLoop: Boolean(event.payload) });
+    const unlistenLoop = listen<RemoteCommand<{ value: boolean }>>('video-loop', (event) => {
+      set({ isLoop: Boolean(event.payload.value) });
+      completeCommand(event.payload.command_id, get);
     });
 
-    const unlistenLoadUrl = listen<{ url: string; time: number }>('load-url', (event) => {
-      const filePath = normalizeMediaSource(event.payload.url);
-      const seekTime = Number(event.payload.time) || 0;
-      set({
-        isPlaying: true,
-        currentFilePath: filePath,
-        currentLyricPath: null,
-        currentImagePath: null,
-        currentLyricSlideIndex: 0,
-        currentLyricTotalSlides: 0,
-        localMediaType: getMediaTypeFromPath(filePath),
-        isLiveStream: false,
-        localTime: seekTime,
-        localDuration: seekTime > 0 ? get().localDuration : 0,
-      });
-    });
+    const unlistenLoadUrl = listen<RemoteCommand<{ url: string; time: number }>>(
+      'load-url',
+      (event) => {
+        const filePath = normalizeMediaSource(event.payload.url);
+        const seekTime = Number(event.payload.time) || 0;
+        set({
+          isPlaying: true,
+          currentFilePath: filePath,
+          currentLyricPath: null,
+          currentImagePath: null,
+          currentLyricSlideIndex: 0,
+          currentLyricTotalSlides: 0,
+          localMediaType: getMediaTypeFromPath(filePath),
+          isLiveStream: false,
+          localTime: seekTime,
+          localDuration: seekTime > 0 ? get().localDuration : 0,
+        });
+        completeCommand(event.payload.command_id, get);
+      }
+    );
 
-    const unlistenLoadLyric = listen<{ url: string }>('load-lyric', (event) => {
+    const unlistenLoadLyric = listen<RemoteCommand<{ url: string }>>('load-lyric', (event) => {
       set({
         currentLyricPath: event.payload.url,
         currentImagePath: null,
         currentLyricSlideIndex: 0,
         currentLyricTotalSlides: 0,
       });
+      completeCommand(event.payload.command_id, get);
     });
 
     const unlistenLyricSlideChanged = listen<{
@@ -360,6 +395,66 @@ export const usePlayerStore = create<PlayerStore>((set, get) => ({
       void broadcastPlayerSync(get, 'lyric_slide_changed');
     });
 
+    const unlistenRemoteMediaShow = listen<RemoteCommand<{ path: string; media_type: string }>>(
+      'remote-media-show',
+      (event) => {
+        const commandId = event.payload.command_id;
+        const shown =
+          event.payload.media_type === 'lyrics'
+            ? get().presentLyric(event.payload.path)
+            : get().loadFile(event.payload.path);
+        void shown
+          .then(() => completeCommand(commandId, get))
+          .catch((error) => rejectCommand(commandId, String(error)));
+      }
+    );
+
+    const unlistenRemoteMediaQueue = listen<RemoteCommand<{ path: string }>>(
+      'remote-media-queue',
+      (event) => {
+        const commandId = event.payload.command_id;
+        void mediaDbService
+          .getFileInfoByPath(event.payload.path)
+          .then(async (file) => {
+            if (!file) {
+              rejectCommand(commandId, 'The file is no longer in the library');
+              return;
+            }
+            await useQueueStore.getState().addToQueue(file);
+            completeCommand(commandId, get);
+          })
+          .catch((error) => rejectCommand(commandId, String(error)));
+      }
+    );
+
+    // The desktop already added a bare library row; this fills in duration,
+    // artist and the thumbnail like a file added on the desktop.
+    const unlistenRemoteMediaUploaded = mediaUploadService.onUploaded((uploaded) => {
+      void mediaDbService
+        .insertFile(
+          {
+            name: uploaded.name,
+            path: uploaded.path,
+            size: uploaded.size,
+            modifiedAt: new Date(),
+            extension: uploaded.extension,
+            thumbnailPath: uploaded.thumbnail_path ?? undefined,
+          },
+          uploaded.media_type
+        )
+        .catch(() => {});
+    });
+
+    const unlistenFollowerQueue = followerService.onQueue((queue) => {
+      void mirrorLeaderQueue(queue.paths).catch(() => {});
+    });
+
+    const unsubscribeQueue = useQueueStore.subscribe((next, previous) => {
+      if (next.queue !== previous.queue) {
+        void broadcastPlayerSync(get, 'queue');
+      }
+    });
+
     return () => {
       unlistenProgress.then((f) => f());
       unlistenMeta.then((f) => f());
@@ -372,6 +467,11 @@ export const usePlayerStore = create<PlayerStore>((set, get) => ({
       unlistenLoadUrl.then((f) => f());
       unlistenLoadLyric.then((f) => f());
       unlistenLyricSlideChanged.then((f) => f());
+      unlistenRemoteMediaShow.then((f) => f());
+      unlistenRemoteMediaQueue.then((f) => f());
+      unlistenRemoteMediaUploaded.then((f) => f());
+      unlistenFollowerQueue.then((f) => f());
+      unsubscribeQueue();
     };
   },
 
@@ -723,8 +823,51 @@ async function broadcastPlayerSync(get: () => PlayerStore, action?: string): Pro
           ? state.currentLyricTotalSlides || undefined
           : undefined,
       },
+      queue: queueSyncState(state.currentFilePath),
       action,
     },
     undefined
   );
 }
+
+/** Answers a remote client whose command this listener just applied. */
+function completeCommand(commandId: string | undefined, get: () => PlayerStore): void {
+  const state = get();
+  void remoteSyncService
+    .completeCommand(commandId, true, {
+      is_playing: state.isPlaying,
+      position: state.localTime,
+      volume: state.volume,
+      is_muted: state.isMuted,
+      is_loop: state.isLoop,
+      url: state.currentFilePath,
+    })
+    .catch(() => {});
+}
+
+/** Answers a remote client whose command this listener could not apply. */
+function rejectCommand(commandId: string | undefined, message: string): void {
+  void remoteSyncService.completeCommand(commandId, false, undefined, message).catch(() => {});
+}
+
+function queueSyncState(currentFilePath: string | null): PlayerSyncPayload['queue'] {
+  const paths = useQueueStore.getState().queue.map((item) => item.file.path);
+  const position = currentFilePath ? paths.indexOf(currentFilePath) : -1;
+  return { paths, position: position >= 0 ? position : undefined };
+}
+
+/** Rebuilds the local queue from the leader's, keeping items this desktop also has. */
+async function mirrorLeaderQueue(paths: string[]): Promise<void> {
+  const queueStore = useQueueStore.getState();
+  const current = queueStore.queue.map((item) => item.file.path);
+  if (current.length === paths.length && current.every((path, index) => path === paths[index])) {
+    return;
+  }
+  await queueStore.clearQueue();
+  for (const path of paths) {
+    const file = await mediaDbService.getFileInfoByPath(path);
+    if (file) {
+      await useQueueStore.getState().addToQueue(file);
+    }
+  }
+}
@@ -34,6 +34,8 @@ const DEFAULT_CONFIG: StreamingConfig = {
   html_server_port: 8090,
   hardware_encoding: false,
   content_protection: true,
+  html_output_languages: {},
+  audience_enabled: false,
 };
 
 const DEFAULT_STATUS: StreamingStatus = {